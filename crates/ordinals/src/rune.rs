@@ -88,6 +88,32 @@ impl Rune {
     self.0 >= Self::RESERVED
   }
 
+  /// First height at which `self` may be etched on `network`, or `None` if
+  /// it is reserved. Runes of a given length unlock gradually, so a rune may
+  /// unlock before shorter runes that sort after it. No rune unlocks before
+  /// `first_rune_height`.
+  pub fn unlock_height(self, network: Network) -> Option<Height> {
+    if self.is_reserved() {
+      return None;
+    }
+
+    // the minimum never increases with height, and is zero at `high`
+    let mut low = Self::first_rune_height(network);
+    let mut high = Self::first_rune_height(network) + SUBSIDY_HALVING_INTERVAL;
+
+    while low < high {
+      let mid = low + (high - low) / 2;
+
+      if self >= Self::minimum_at_height(network, Height(mid)) {
+        high = mid;
+      } else {
+        low = mid + 1;
+      }
+    }
+
+    Some(Height(low))
+  }
+
   pub fn reserved(block: u64, tx: u32) -> Self {
     Self(
       Self::RESERVED
@@ -354,6 +380,43 @@ mod tests {
     case(Network::Regtest, 1, "ZZXZUDIVTVQA");
   }
 
+  #[test]
+  fn unlock_height() {
+    #[track_caller]
+    fn case(network: Network, rune: &str, height: Option<u32>) {
+      let rune = rune.parse::<Rune>().unwrap();
+      let unlock = rune.unlock_height(network);
+
+      assert_eq!(unlock, height.map(Height));
+
+      if let Some(Height(height)) = unlock {
+        assert!(rune >= Rune::minimum_at_height(network, Height(height)));
+
+        if height > Rune::first_rune_height(network) {
+          assert!(rune < Rune::minimum_at_height(network, Height(height - 1)));
+        }
+      }
+    }
+
+    const START: u32 = SUBSIDY_HALVING_INTERVAL * 4;
+    const INTERVAL: u32 = SUBSIDY_HALVING_INTERVAL / 12;
+
+    case(Network::Bitcoin, "AAAAAAAAAAAAA", Some(START));
+    case(Network::Bitcoin, "ZZZZZZZZZZZZZZZZZZZZZZZZZZ", Some(START));
+    case(Network::Bitcoin, "ZZZZZZZZZZZZ", Some(START));
+    case(Network::Bitcoin, "AAAAAAAAAAAA", Some(START + INTERVAL - 1));
+    case(Network::Bitcoin, "A", Some(START + INTERVAL * 12 - 1));
+    case(Network::Bitcoin, "B", Some(START + INTERVAL * 11 + 16826));
+    case(Network::Regtest, "ZZYZXBRKWXVA", Some(0));
+    case(Network::Regtest, "ZZXZUDIVTVQA", Some(1));
+    case(
+      Network::Testnet,
+      "AAAAAAAAAAAAA",
+      Some(SUBSIDY_HALVING_INTERVAL * 12),
+    );
+    case(Network::Bitcoin, "AAAAAAAAAAAAAAAAAAAAAAAAAAA", None);
+  }
+
   #[test]
   fn serde() {
     let rune = Rune(0);
@@ -1,2 +0,0 @@
--- Active: 1703754780028@@127.0.0.1@3306@runes
-Drop TABLE `rune_balance`;
\ No newline at end of file
@@ -0,0 +1,60 @@
+-- Tables as the sql files that predate the versioned migrations created them,
+-- before those files gained the columns of the current schema. Used to test
+-- that migrating upgrades such a database.
+CREATE TABLE `rune_entry` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `block` BIGINT UNSIGNED NOT NULL,
+  `burned` decimal(40,0) NOT NULL DEFAULT 0,
+  `divisibility` TinyInt UNSIGNED NOT NULL DEFAULT 0,
+  `etching` VARCHAR(256) NOT NULL,
+  `spaced_rune` VARCHAR(64) NOT NULL,
+  `premine` decimal(40,0) NOT NULL DEFAULT 0,
+  `mints` decimal(40,0)  NOT NULL  DEFAULT 0,
+  `number` BIGINT UNSIGNED NOT NULL DEFAULT 0,
+  `timestamp` BIGINT UNSIGNED NOT NULL DEFAULT 0,
+  `rune` decimal(40,0) NOT NULL DEFAULT 0,
+  `rune_id` VARCHAR(64) NOT NULL,
+  `turbo` boolean NOT NULL,
+  `symbol` VARCHAR(8) NOT NULL,
+  `amount` decimal(40,0) NULL,
+  `cap` decimal(40,0)  NULL,
+  `height_start` BIGINT UNSIGNED  NULL,
+  `height_end` BIGINT UNSIGNED NULL,
+  `offset_start` BIGINT UNSIGNED NULL,
+  `offset_end` BIGINT  UNSIGNED NULL,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
+  CONSTRAINT `index_spaced_rune` UNIQUE (`spaced_rune`)
+);
+CREATE TABLE `rune_event` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `block` BIGINT UNSIGNED NOT NULL,
+  `tx_id` VARCHAR(256) NOT NULL,
+  `event_type` TinyInt UNSIGNED NOT NULL DEFAULT 0 COMMENT '0:etch/1:mint/2:transfer',
+  `rune_id` VARCHAR(64) NOT NULL,
+  `address` VARCHAR(256) NOT NULL DEFAULT '',
+  `pk_script_hex` VARCHAR(256) NOT NULL DEFAULT '',
+  `amount` decimal(40,0) NULL,
+  `vout` INT UNSIGNED NOT NULL DEFAULT 0,
+  `rune_stone` TEXT NOT NULL,
+  `timestamp` BIGINT UNSIGNED NOT NULL DEFAULT 0,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`)
+);
+CREATE TABLE `rune_balance` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `block` BIGINT UNSIGNED NOT NULL,
+  `rune_id` VARCHAR(64) NOT NULL,
+  `address` VARCHAR(256) NOT NULL DEFAULT '',
+  `pk_script_hex` VARCHAR(256) NOT NULL DEFAULT '',
+  `out_point` VARCHAR(266) NOT NULL DEFAULT '',
+  `amount` decimal(40, 0) NOT NULL DEFAULT 0,
+  `spent` BOOLEAN NOT NULL DEFAULT 0,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
+  INDEX `index_rune_id` (`rune_id`)
+);
+
+-- an output whose balance was written twice by indexing its block again
+INSERT INTO rune_balance (block, rune_id, address, out_point, amount)
+  VALUES (840000, '840000:1', 'bc1qfoo', '1111111111111111111111111111111111111111111111111111111111111111:0', 100),
+    (840000, '840000:1', 'bc1qfoo', '1111111111111111111111111111111111111111111111111111111111111111:0', 100);
+INSERT INTO rune_event (block, tx_id, event_type, rune_id, amount, rune_stone)
+  VALUES (840000, '1111111111111111111111111111111111111111111111111111111111111111', 1, '840000:1', 100, '{}');
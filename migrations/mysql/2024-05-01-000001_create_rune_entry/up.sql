@@ -1,5 +1,4 @@
--- Active: 1703754780028@@127.0.0.1@3306@runes
-CREATE TABLE `rune_entry` ( 
+CREATE TABLE IF NOT EXISTS `rune_entry` (
   `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
   `block` BIGINT UNSIGNED NOT NULL,
   `burned` decimal(40,0) NOT NULL DEFAULT 0,
@@ -22,4 +21,4 @@ CREATE TABLE `rune_entry` (
   `offset_end` BIGINT  UNSIGNED NULL,
   CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
   CONSTRAINT `index_spaced_rune` UNIQUE (`spaced_rune`)
-);
\ No newline at end of file
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS `rune_tx`;
+DROP TABLE IF EXISTS `rune_event`;
@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS `rune_event` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `block` BIGINT UNSIGNED NOT NULL,
+  `tx_id` VARCHAR(256) NOT NULL,
+  `event_type` TinyInt UNSIGNED NOT NULL DEFAULT 0 COMMENT '1:etch/2:mint/3:transfer/4:burn/5:cenotaph/6:mint rejected/7:etch rejected',
+  `rune_id` VARCHAR(64) NOT NULL,
+  `address` VARCHAR(256) NOT NULL DEFAULT '',
+  `pk_script_hex` TEXT NOT NULL,
+  `script_type` VARCHAR(16) NOT NULL DEFAULT '',
+  `amount` decimal(40,0) NULL,
+  `vout` INT UNSIGNED NOT NULL DEFAULT 0,
+  `timestamp` BIGINT UNSIGNED NOT NULL DEFAULT 0,
+  `reason` VARCHAR(256) NOT NULL DEFAULT '' COMMENT 'why a cenotaph, rejected mint or rejected etching did nothing',
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`)
+);
+
+-- value spent by each transaction with rune events and the fee it paid,
+-- stored once rather than with each of its events
+CREATE TABLE IF NOT EXISTS `rune_tx` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `block` BIGINT UNSIGNED NOT NULL,
+  `tx_id` VARCHAR(64) NOT NULL,
+  `input_value` BIGINT UNSIGNED NULL COMMENT 'sats spent by the tx',
+  `fee` BIGINT UNSIGNED NULL COMMENT 'sats paid as fee by the tx',
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
+  UNIQUE INDEX `index_tx_id` (`tx_id`),
+  INDEX `index_block` (`block`)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS `rune_block` (
+  `height` BIGINT UNSIGNED NOT NULL,
+  `block_hash` VARCHAR(64) NOT NULL,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`height`)
+);
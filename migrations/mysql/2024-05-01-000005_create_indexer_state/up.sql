@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS `indexer_state` (
+  `id` INT UNSIGNED NOT NULL,
+  `network` VARCHAR(16) NOT NULL,
+  `height` BIGINT UNSIGNED NOT NULL,
+  `block_hash` VARCHAR(64) NOT NULL,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS `rune_address_balance` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `address` VARCHAR(256) NOT NULL,
+  `rune_id` VARCHAR(64) NOT NULL,
+  `amount` decimal(40, 0) NOT NULL DEFAULT 0,
+  `utxo_count` BIGINT UNSIGNED NOT NULL DEFAULT 0,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
+  UNIQUE INDEX `index_address_rune_id` (`address`, `rune_id`),
+  INDEX `index_rune_id_amount` (`rune_id`, `amount`)
+);
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS `etching` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `rune` decimal(40,0) NOT NULL,
+  `state` TINYINT UNSIGNED NOT NULL DEFAULT 0,
+  `destination` VARCHAR(256) NOT NULL,
+  `commit_key` VARCHAR(64) NOT NULL,
+  `runestone` TEXT NOT NULL,
+  `fee_rate` BIGINT UNSIGNED NOT NULL,
+  `postage` BIGINT UNSIGNED NOT NULL,
+  `commit_tx_id` VARCHAR(256) NOT NULL,
+  `commit_tx` TEXT NOT NULL,
+  `reveal_tx_id` VARCHAR(256) NOT NULL DEFAULT '',
+  `reveal_tx` TEXT NOT NULL,
+  `inscription_output` TEXT NOT NULL,
+  `create_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `update_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
+  UNIQUE INDEX `index_commit_tx_id` (`commit_tx_id`),
+  INDEX `index_state` (`state`)
+);
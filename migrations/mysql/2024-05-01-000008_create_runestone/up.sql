@@ -0,0 +1,41 @@
+CREATE TABLE IF NOT EXISTS `runestone` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `block` BIGINT UNSIGNED NOT NULL,
+  `tx_id` VARCHAR(64) NOT NULL,
+  `cenotaph` BOOLEAN NOT NULL DEFAULT FALSE,
+  `flaw` VARCHAR(32) NULL COMMENT 'kebab-case flaw of a cenotaph',
+  `pointer` INT UNSIGNED NULL,
+  `mint` VARCHAR(64) NULL,
+  `etching` BOOLEAN NOT NULL DEFAULT FALSE,
+  `rune` decimal(40,0) NULL,
+  `spacers` INT UNSIGNED NULL,
+  `divisibility` TINYINT UNSIGNED NULL,
+  `premine` decimal(40,0) NULL,
+  `symbol` VARCHAR(8) NULL,
+  `turbo` BOOLEAN NOT NULL DEFAULT FALSE,
+  `terms` BOOLEAN NOT NULL DEFAULT FALSE,
+  `amount` decimal(40,0) NULL,
+  `cap` decimal(40,0) NULL,
+  `height_start` BIGINT UNSIGNED NULL,
+  `height_end` BIGINT UNSIGNED NULL,
+  `offset_start` BIGINT UNSIGNED NULL,
+  `offset_end` BIGINT UNSIGNED NULL,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
+  UNIQUE INDEX `index_tx_id` (`tx_id`),
+  INDEX `index_block` (`block`),
+  INDEX `index_flaw` (`flaw`)
+);
+
+CREATE TABLE IF NOT EXISTS `runestone_edict` (
+  `id` BIGINT UNSIGNED AUTO_INCREMENT NOT NULL,
+  `block` BIGINT UNSIGNED NOT NULL,
+  `tx_id` VARCHAR(64) NOT NULL,
+  `edict_index` INT UNSIGNED NOT NULL COMMENT 'position of the edict in the runestone',
+  `rune_id` VARCHAR(64) NOT NULL COMMENT '0:0 is resolved to the rune etched by the tx, if it was etched',
+  `amount` decimal(40,0) NOT NULL,
+  `output` INT UNSIGNED NOT NULL,
+  CONSTRAINT `PRIMARY` PRIMARY KEY (`id`),
+  UNIQUE INDEX `index_tx_id_edict_index` (`tx_id`, `edict_index`),
+  INDEX `index_rune_id` (`rune_id`),
+  INDEX `index_block` (`block`)
+);
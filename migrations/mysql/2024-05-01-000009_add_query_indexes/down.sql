@@ -0,0 +1,16 @@
+ALTER TABLE `rune_balance`
+  DROP INDEX `index_out_point_rune_id`,
+  DROP INDEX `index_address_spent`,
+  DROP INDEX `index_block`;
+
+ALTER TABLE `rune_event`
+  DROP INDEX `index_tx_id`,
+  DROP INDEX `index_rune_id`,
+  DROP INDEX `index_address`,
+  DROP INDEX `index_block`;
+
+ALTER TABLE `rune_entry`
+  DROP INDEX `index_rune_id`,
+  DROP INDEX `index_rune`,
+  DROP INDEX `index_number`,
+  DROP INDEX `index_block`;
@@ -0,0 +1,24 @@
+ALTER TABLE `rune_entry`
+  ADD CONSTRAINT `index_rune_id` UNIQUE (`rune_id`),
+  ADD CONSTRAINT `index_rune` UNIQUE (`rune`),
+  ADD INDEX `index_number` (`number`),
+  ADD INDEX `index_block` (`block`);
+
+ALTER TABLE `rune_event`
+  ADD INDEX `index_tx_id` (`tx_id`),
+  ADD INDEX `index_rune_id` (`rune_id`),
+  ADD INDEX `index_address` (`address`),
+  ADD INDEX `index_block` (`block`);
+
+-- an output holds one row per rune, so copies of its balances written by
+-- the sql files that predate these migrations are deleted first
+DELETE `b1` FROM `rune_balance` `b1`
+  JOIN `rune_balance` `b2`
+  ON `b1`.`out_point` = `b2`.`out_point`
+    AND `b1`.`rune_id` = `b2`.`rune_id`
+    AND `b1`.`id` > `b2`.`id`;
+
+ALTER TABLE `rune_balance`
+  ADD CONSTRAINT `index_out_point_rune_id` UNIQUE (`out_point`, `rune_id`),
+  ADD INDEX `index_address_spent` (`address`, `spent`),
+  ADD INDEX `index_block` (`block`);
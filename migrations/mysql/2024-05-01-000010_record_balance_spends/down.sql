@@ -0,0 +1,6 @@
+ALTER TABLE `rune_balance`
+  DROP INDEX `index_spent_txid`,
+  DROP INDEX `index_spent_block`,
+  DROP COLUMN `spent_txid`,
+  DROP COLUMN `spent_vin`,
+  DROP COLUMN `spent_block`;
@@ -0,0 +1,7 @@
+-- outputs spent before this migration keep only the spent flag
+ALTER TABLE `rune_balance`
+  ADD COLUMN `spent_txid` VARCHAR(64) NULL COMMENT 'tx that spent the output',
+  ADD COLUMN `spent_vin` INT UNSIGNED NULL COMMENT 'input of the tx that spent the output',
+  ADD COLUMN `spent_block` BIGINT UNSIGNED NULL,
+  ADD INDEX `index_spent_txid` (`spent_txid`),
+  ADD INDEX `index_spent_block` (`spent_block`);
@@ -0,0 +1,32 @@
+-- Tables created by the migrations above already had the shape this
+-- upgrades to, and are left as they are. Tables of the sql files that
+-- predate the migrations, told apart by their `rune_stone` column, lose the
+-- columns it added. Events indexed since have no artifact copy, and their
+-- scripts must fit in VARCHAR(256) again, or reverting fails. Backfilled
+-- `rune_tx` rows are kept, as they hold nothing the indexer wouldn't write.
+SET @legacy = (SELECT COUNT(*) FROM information_schema.columns
+  WHERE table_schema = DATABASE() AND table_name = 'rune_event' AND column_name = 'rune_stone');
+
+SET @sql = IF(
+  @legacy = 1,
+  'UPDATE `rune_event` SET `rune_stone` = '''' WHERE `rune_stone` IS NULL',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
+
+SET @sql = IF(
+  @legacy = 1,
+  'ALTER TABLE `rune_event` DROP COLUMN `script_type`, DROP COLUMN `reason`, MODIFY COLUMN `pk_script_hex` VARCHAR(256) NOT NULL DEFAULT '''', MODIFY COLUMN `rune_stone` TEXT NOT NULL',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
+
+SET @sql = IF(
+  @legacy = 1,
+  'ALTER TABLE `rune_balance` DROP COLUMN `script_type`, MODIFY COLUMN `pk_script_hex` VARCHAR(256) NOT NULL DEFAULT ''''',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
@@ -0,0 +1,61 @@
+-- Tables created by the sql files that predate these migrations, which
+-- created `rune_entry`, `rune_event` and `rune_balance`, keep the columns
+-- they had then, since the migrations above create tables only if they
+-- don't exist. Bring them to the shape of the tables above. Only those
+-- tables have a `rune_event`.`rune_stone` column, which tells them apart.
+SET @legacy = (SELECT COUNT(*) FROM information_schema.columns
+  WHERE table_schema = DATABASE() AND table_name = 'rune_event' AND column_name = 'rune_stone');
+
+SET @sql = IF(
+  (SELECT COUNT(*) FROM information_schema.columns
+    WHERE table_schema = DATABASE() AND table_name = 'rune_balance' AND column_name = 'script_type') = 0,
+  'ALTER TABLE `rune_balance` ADD COLUMN `script_type` VARCHAR(16) NOT NULL DEFAULT '''' AFTER `pk_script_hex`',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
+
+SET @sql = IF(
+  (SELECT COUNT(*) FROM information_schema.columns
+    WHERE table_schema = DATABASE() AND table_name = 'rune_event' AND column_name = 'script_type') = 0,
+  'ALTER TABLE `rune_event` ADD COLUMN `script_type` VARCHAR(16) NOT NULL DEFAULT '''' AFTER `pk_script_hex`',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
+
+SET @sql = IF(
+  (SELECT COUNT(*) FROM information_schema.columns
+    WHERE table_schema = DATABASE() AND table_name = 'rune_event' AND column_name = 'reason') = 0,
+  'ALTER TABLE `rune_event` ADD COLUMN `reason` VARCHAR(256) NOT NULL DEFAULT '''' COMMENT ''why a cenotaph, rejected mint or rejected etching did nothing'' AFTER `timestamp`',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
+
+-- scripts are up to 10,000 bytes, more than VARCHAR(256) holds in hex
+ALTER TABLE `rune_balance` MODIFY COLUMN `pk_script_hex` TEXT NOT NULL;
+ALTER TABLE `rune_event` MODIFY COLUMN `pk_script_hex` TEXT NOT NULL;
+
+-- artifacts are stored once per transaction in `runestone` and
+-- `runestone_edict` now, and events are inserted without one, so the copies
+-- of the events indexed before are kept but no longer required
+SET @sql = IF(
+  @legacy = 1,
+  'ALTER TABLE `rune_event` MODIFY COLUMN `rune_stone` TEXT NULL COMMENT ''JSON artifact of events indexed before the migrations''',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
+
+-- events were indexed without the value their tx spent
+SET @sql = IF(
+  @legacy = 1,
+  'INSERT IGNORE INTO `rune_tx` (`block`, `tx_id`) SELECT MIN(`block`), `tx_id` FROM `rune_event` GROUP BY `tx_id`',
+  'DO 0');
+PREPARE statement FROM @sql;
+EXECUTE statement;
+DEALLOCATE PREPARE statement;
+
+ALTER TABLE `rune_event` MODIFY COLUMN `event_type` TinyInt UNSIGNED NOT NULL DEFAULT 0
+  COMMENT '1:etch/2:mint/3:transfer/4:burn/5:cenotaph/6:mint rejected/7:etch rejected';
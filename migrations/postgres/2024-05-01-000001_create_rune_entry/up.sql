@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS rune_entry (
+  id BIGSERIAL NOT NULL,
+  block BIGINT NOT NULL,
+  burned NUMERIC(39,0) NOT NULL DEFAULT 0,
+  divisibility SMALLINT NOT NULL DEFAULT 0,
+  etching VARCHAR(256) NOT NULL,
+  spaced_rune VARCHAR(64) NOT NULL,
+  premine NUMERIC(39,0) NOT NULL DEFAULT 0,
+  mints NUMERIC(39,0) NOT NULL DEFAULT 0,
+  number BIGINT NOT NULL DEFAULT 0,
+  timestamp BIGINT NOT NULL DEFAULT 0,
+  rune NUMERIC(39,0) NOT NULL DEFAULT 0,
+  rune_id VARCHAR(64) NOT NULL,
+  turbo BOOLEAN NOT NULL,
+  symbol VARCHAR(8) NOT NULL,
+  amount NUMERIC(39,0) NULL,
+  cap NUMERIC(39,0) NULL,
+  height_start BIGINT NULL,
+  height_end BIGINT NULL,
+  offset_start BIGINT NULL,
+  offset_end BIGINT NULL,
+  CONSTRAINT rune_entry_pkey PRIMARY KEY (id),
+  CONSTRAINT index_spaced_rune UNIQUE (spaced_rune)
+);
@@ -0,0 +1,34 @@
+CREATE TABLE IF NOT EXISTS rune_event (
+  id BIGSERIAL NOT NULL,
+  block BIGINT NOT NULL,
+  tx_id VARCHAR(256) NOT NULL,
+  event_type SMALLINT NOT NULL DEFAULT 0,
+  rune_id VARCHAR(64) NOT NULL,
+  address VARCHAR(256) NOT NULL DEFAULT '',
+  pk_script_hex TEXT NOT NULL,
+  script_type VARCHAR(16) NOT NULL DEFAULT '',
+  amount NUMERIC(39,0) NULL,
+  vout BIGINT NOT NULL DEFAULT 0,
+  timestamp BIGINT NOT NULL DEFAULT 0,
+  reason VARCHAR(256) NOT NULL DEFAULT '',
+  CONSTRAINT rune_event_pkey PRIMARY KEY (id)
+);
+
+COMMENT ON COLUMN rune_event.event_type IS '1:etch/2:mint/3:transfer/4:burn/5:cenotaph/6:mint rejected/7:etch rejected';
+
+-- value spent by each transaction with rune events and the fee it paid,
+-- stored once rather than with each of its events
+CREATE TABLE IF NOT EXISTS rune_tx (
+  id BIGSERIAL NOT NULL,
+  block BIGINT NOT NULL,
+  tx_id VARCHAR(64) NOT NULL,
+  input_value BIGINT NULL,
+  fee BIGINT NULL,
+  CONSTRAINT rune_tx_pkey PRIMARY KEY (id),
+  CONSTRAINT rune_tx_index_tx_id UNIQUE (tx_id)
+);
+
+CREATE INDEX IF NOT EXISTS rune_tx_index_block ON rune_tx (block);
+
+COMMENT ON COLUMN rune_tx.input_value IS 'sats spent by the tx';
+COMMENT ON COLUMN rune_tx.fee IS 'sats paid as fee by the tx';
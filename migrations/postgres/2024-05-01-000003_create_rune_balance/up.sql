@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS rune_balance (
+  id BIGSERIAL NOT NULL,
+  block BIGINT NOT NULL,
+  rune_id VARCHAR(64) NOT NULL,
+  address VARCHAR(256) NOT NULL DEFAULT '',
+  pk_script_hex TEXT NOT NULL,
+  script_type VARCHAR(16) NOT NULL DEFAULT '',
+  out_point VARCHAR(266) NOT NULL DEFAULT '',
+  amount NUMERIC(39,0) NOT NULL DEFAULT 0,
+  spent BOOLEAN NOT NULL DEFAULT FALSE,
+  CONSTRAINT rune_balance_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS rune_balance_index_rune_id ON rune_balance (rune_id);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS rune_block (
+  height BIGINT NOT NULL,
+  block_hash VARCHAR(64) NOT NULL,
+  CONSTRAINT rune_block_pkey PRIMARY KEY (height)
+);
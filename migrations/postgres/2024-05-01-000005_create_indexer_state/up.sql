@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS indexer_state (
+  id INTEGER NOT NULL,
+  network VARCHAR(16) NOT NULL,
+  height BIGINT NOT NULL,
+  block_hash VARCHAR(64) NOT NULL,
+  CONSTRAINT indexer_state_pkey PRIMARY KEY (id)
+);
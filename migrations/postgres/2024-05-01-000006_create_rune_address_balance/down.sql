@@ -0,0 +1 @@
+DROP TABLE IF EXISTS rune_address_balance;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS rune_address_balance (
+  id BIGSERIAL NOT NULL,
+  address VARCHAR(256) NOT NULL,
+  rune_id VARCHAR(64) NOT NULL,
+  amount NUMERIC(39,0) NOT NULL DEFAULT 0,
+  utxo_count BIGINT NOT NULL DEFAULT 0,
+  CONSTRAINT rune_address_balance_pkey PRIMARY KEY (id),
+  CONSTRAINT index_address_rune_id UNIQUE (address, rune_id)
+);
+
+CREATE INDEX IF NOT EXISTS index_rune_id_amount ON rune_address_balance (rune_id, amount);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS etching (
+  id BIGSERIAL NOT NULL,
+  rune NUMERIC(39,0) NOT NULL,
+  state SMALLINT NOT NULL DEFAULT 0,
+  destination VARCHAR(256) NOT NULL,
+  commit_key VARCHAR(64) NOT NULL,
+  runestone TEXT NOT NULL,
+  fee_rate BIGINT NOT NULL,
+  postage BIGINT NOT NULL,
+  commit_tx_id VARCHAR(256) NOT NULL,
+  commit_tx TEXT NOT NULL,
+  reveal_tx_id VARCHAR(256) NOT NULL DEFAULT '',
+  reveal_tx TEXT NOT NULL,
+  inscription_output TEXT NOT NULL,
+  create_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  update_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT etching_pkey PRIMARY KEY (id),
+  CONSTRAINT index_commit_tx_id UNIQUE (commit_tx_id)
+);
+
+CREATE INDEX IF NOT EXISTS index_state ON etching (state);
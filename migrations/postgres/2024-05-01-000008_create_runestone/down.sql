@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS runestone_edict;
+DROP TABLE IF EXISTS runestone;
@@ -0,0 +1,48 @@
+CREATE TABLE IF NOT EXISTS runestone (
+  id BIGSERIAL NOT NULL,
+  block BIGINT NOT NULL,
+  tx_id VARCHAR(64) NOT NULL,
+  cenotaph BOOLEAN NOT NULL DEFAULT FALSE,
+  flaw VARCHAR(32) NULL,
+  pointer BIGINT NULL,
+  mint VARCHAR(64) NULL,
+  etching BOOLEAN NOT NULL DEFAULT FALSE,
+  rune NUMERIC(39,0) NULL,
+  spacers BIGINT NULL,
+  divisibility SMALLINT NULL,
+  premine NUMERIC(39,0) NULL,
+  symbol VARCHAR(8) NULL,
+  turbo BOOLEAN NOT NULL DEFAULT FALSE,
+  terms BOOLEAN NOT NULL DEFAULT FALSE,
+  amount NUMERIC(39,0) NULL,
+  cap NUMERIC(39,0) NULL,
+  height_start BIGINT NULL,
+  height_end BIGINT NULL,
+  offset_start BIGINT NULL,
+  offset_end BIGINT NULL,
+  CONSTRAINT runestone_pkey PRIMARY KEY (id),
+  CONSTRAINT index_tx_id UNIQUE (tx_id)
+);
+
+CREATE INDEX IF NOT EXISTS runestone_index_block ON runestone (block);
+CREATE INDEX IF NOT EXISTS index_flaw ON runestone (flaw);
+
+COMMENT ON COLUMN runestone.flaw IS 'kebab-case flaw of a cenotaph';
+
+CREATE TABLE IF NOT EXISTS runestone_edict (
+  id BIGSERIAL NOT NULL,
+  block BIGINT NOT NULL,
+  tx_id VARCHAR(64) NOT NULL,
+  edict_index BIGINT NOT NULL,
+  rune_id VARCHAR(64) NOT NULL,
+  amount NUMERIC(39,0) NOT NULL,
+  output BIGINT NOT NULL,
+  CONSTRAINT runestone_edict_pkey PRIMARY KEY (id),
+  CONSTRAINT index_tx_id_edict_index UNIQUE (tx_id, edict_index)
+);
+
+CREATE INDEX IF NOT EXISTS runestone_edict_index_rune_id ON runestone_edict (rune_id);
+CREATE INDEX IF NOT EXISTS runestone_edict_index_block ON runestone_edict (block);
+
+COMMENT ON COLUMN runestone_edict.edict_index IS 'position of the edict in the runestone';
+COMMENT ON COLUMN runestone_edict.rune_id IS '0:0 is resolved to the rune etched by the tx, if it was etched';
@@ -0,0 +1,14 @@
+DROP INDEX rune_balance_index_block;
+DROP INDEX rune_balance_index_address_spent;
+ALTER TABLE rune_balance DROP CONSTRAINT rune_balance_index_out_point_rune_id;
+
+DROP INDEX rune_event_index_block;
+DROP INDEX rune_event_index_address;
+DROP INDEX rune_event_index_rune_id;
+DROP INDEX rune_event_index_tx_id;
+
+DROP INDEX rune_entry_index_block;
+DROP INDEX rune_entry_index_number;
+ALTER TABLE rune_entry
+  DROP CONSTRAINT rune_entry_index_rune,
+  DROP CONSTRAINT rune_entry_index_rune_id;
@@ -0,0 +1,17 @@
+ALTER TABLE rune_entry
+  ADD CONSTRAINT rune_entry_index_rune_id UNIQUE (rune_id),
+  ADD CONSTRAINT rune_entry_index_rune UNIQUE (rune);
+
+CREATE INDEX rune_entry_index_number ON rune_entry (number);
+CREATE INDEX rune_entry_index_block ON rune_entry (block);
+
+CREATE INDEX rune_event_index_tx_id ON rune_event (tx_id);
+CREATE INDEX rune_event_index_rune_id ON rune_event (rune_id);
+CREATE INDEX rune_event_index_address ON rune_event (address);
+CREATE INDEX rune_event_index_block ON rune_event (block);
+
+ALTER TABLE rune_balance
+  ADD CONSTRAINT rune_balance_index_out_point_rune_id UNIQUE (out_point, rune_id);
+
+CREATE INDEX rune_balance_index_address_spent ON rune_balance (address, spent);
+CREATE INDEX rune_balance_index_block ON rune_balance (block);
@@ -0,0 +1,7 @@
+DROP INDEX rune_balance_index_spent_block;
+DROP INDEX rune_balance_index_spent_txid;
+
+ALTER TABLE rune_balance
+  DROP COLUMN spent_txid,
+  DROP COLUMN spent_vin,
+  DROP COLUMN spent_block;
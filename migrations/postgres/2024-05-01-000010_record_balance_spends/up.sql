@@ -0,0 +1,11 @@
+-- outputs spent before this migration keep only the spent flag
+ALTER TABLE rune_balance
+  ADD COLUMN spent_txid VARCHAR(64) NULL,
+  ADD COLUMN spent_vin BIGINT NULL,
+  ADD COLUMN spent_block BIGINT NULL;
+
+CREATE INDEX rune_balance_index_spent_txid ON rune_balance (spent_txid);
+CREATE INDEX rune_balance_index_spent_block ON rune_balance (spent_block);
+
+COMMENT ON COLUMN rune_balance.spent_txid IS 'tx that spent the output';
+COMMENT ON COLUMN rune_balance.spent_vin IS 'input of the tx that spent the output';
@@ -0,0 +1,4 @@
+-- The sql files that predate these migrations only created MySQL tables,
+-- which the MySQL migration of the same name upgrades. It is kept here so
+-- that both backends apply the same migrations.
+SELECT 1;
@@ -0,0 +1,885 @@
+use {
+    self::error::{OptionExt, ServerError, ServerResult},
+    super::*,
+    crate::{
+        dao::{
+            convert_model_to_artifact, convert_model_to_rune_entry, decimal_to_u128, parse_flaw,
+            RuneStore, StorePool,
+        },
+        indexer::{EventType, IntoUsize},
+        lineage::{lineage, MAX_LINEAGE_OUTPUTS},
+        mempool::PendingState,
+        model::{RuneBalanceEntity, RuneEventEntity, RuneTxEntity, RunestoneEntity},
+        psbt::TxDetail,
+        subcommand::{availability, status},
+    },
+    axum::{
+        extract::{Path, Query, State},
+        http::Method,
+        routing::{get, post},
+        Json, Router,
+    },
+    tower_http::cors::{Any, CorsLayer},
+};
+
+mod error;
+mod mint;
+mod pending;
+mod psbt;
+
+/// Number of items in a page of a paginated response.
+const PAGE_SIZE: u32 = 100;
+
+/// Number of holders listed for a rune.
+const TOP_HOLDERS: i64 = 100;
+
+#[derive(Clone)]
+pub(crate) struct ApiState {
+    client: Arc<Client>,
+    stores: StorePool,
+    network: Network,
+    pending: Arc<Mutex<Arc<PendingState>>>,
+}
+
+impl ApiState {
+    pub(crate) fn new(client: Client, stores: StorePool, network: Network) -> Self {
+        Self {
+            client: Arc::new(client),
+            stores,
+            network,
+            pending: default(),
+        }
+    }
+
+    /// Rune state of the mempool, replaced as the mempool is polled.
+    pub(crate) fn pending(&self) -> Arc<Mutex<Arc<PendingState>>> {
+        self.pending.clone()
+    }
+
+    fn pending_state(&self) -> ServerResult<Arc<PendingState>> {
+        Ok(self
+            .pending
+            .lock()
+            .map_err(|_| anyhow!("pending state mutex poisoned"))?
+            .clone())
+    }
+
+    // stores are synchronous, so queries run on the blocking pool, each with
+    // a store of its own
+    async fn query<T, F>(&self, f: F) -> ServerResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut dyn RuneStore) -> ServerResult<T> + Send + 'static,
+    {
+        let stores = self.stores.clone();
+
+        task::spawn_blocking(move || stores.run(f)?)
+            .await
+            .map_err(|err| ServerError::Internal(err.into()))?
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuneJson {
+    pub id: RuneId,
+    pub spaced_rune: SpacedRune,
+    pub number: u64,
+    pub block: u64,
+    pub etching: Txid,
+    pub divisibility: u8,
+    pub symbol: Option<char>,
+    pub premine: String,
+    pub mints: u128,
+    pub burned: String,
+    pub supply: String,
+    pub terms: Option<Terms>,
+    pub turbo: bool,
+    pub timestamp: u64,
+    pub mintable: bool,
+    pub mint_error: Option<String>,
+    pub holders: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AddressBalanceJson {
+    pub rune_id: RuneId,
+    pub spaced_rune: SpacedRune,
+    pub amount: String,
+    pub utxo_count: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct OutpointBalanceJson {
+    pub rune_id: RuneId,
+    pub spaced_rune: SpacedRune,
+    pub amount: String,
+    pub address: String,
+    pub spent: bool,
+    pub spent_by: Option<SpendJson>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpendJson {
+    pub txid: Txid,
+    pub vin: u32,
+    pub block: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct LineageJson {
+    pub outputs: Vec<LineageOutputJson>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct LineageOutputJson {
+    pub outpoint: OutPoint,
+    pub rune_id: RuneId,
+    pub spaced_rune: SpacedRune,
+    pub amount: String,
+    pub address: String,
+    pub block: u64,
+    pub spent_by: Option<SpendJson>,
+    pub parents: Vec<OutPoint>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventJson {
+    pub block: u64,
+    pub event_type: String,
+    pub txid: Txid,
+    pub rune_id: Option<RuneId>,
+    pub spaced_rune: Option<SpacedRune>,
+    pub amount: Option<String>,
+    pub address: String,
+    pub vout: u32,
+    pub input_value: Option<u64>,
+    pub fee: Option<u64>,
+    pub timestamp: u64,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunestoneJson {
+    pub block: u64,
+    pub txid: Txid,
+    pub artifact: Artifact,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct EdictJson {
+    pub block: u64,
+    pub txid: Txid,
+    pub edict_index: u32,
+    pub amount: String,
+    pub output: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PageJson<T> {
+    pub items: Vec<T>,
+    pub page: u32,
+    pub more: bool,
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    page: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct FlawQuery {
+    flaw: Option<String>,
+}
+
+impl PageQuery {
+    fn page(&self) -> u32 {
+        self.page.unwrap_or_default()
+    }
+
+    // one extra row is loaded to tell whether there is a next page
+    fn offset_and_limit(&self) -> (i64, i64) {
+        (
+            i64::from(self.page()) * i64::from(PAGE_SIZE),
+            i64::from(PAGE_SIZE) + 1,
+        )
+    }
+
+    fn paginate<T>(&self, mut items: Vec<T>) -> PageJson<T> {
+        let more = items.len() > PAGE_SIZE.into_usize();
+        items.truncate(PAGE_SIZE.into_usize());
+
+        PageJson {
+            items,
+            page: self.page(),
+            more,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum RuneQuery {
+    Id(RuneId),
+    Name(SpacedRune),
+}
+
+impl FromStr for RuneQuery {
+    type Err = ServerError;
+
+    fn from_str(s: &str) -> ServerResult<Self> {
+        if let Ok(id) = s.parse() {
+            return Ok(Self::Id(id));
+        }
+
+        s.parse()
+            .map(Self::Name)
+            .map_err(|err| ServerError::BadRequest(format!("invalid rune `{s}`: {err}")))
+    }
+}
+
+impl RuneQuery {
+    fn load(&self, store: &mut dyn RuneStore) -> ServerResult<(RuneId, RuneEntry)> {
+        let entities = match self {
+            Self::Id(id) => store.gets_rune_entry(vec![id.to_string()])?,
+            Self::Name(spaced_rune) => store.gets_rune_entry_by_runes(vec![spaced_rune.rune])?,
+        };
+
+        let entity = entities
+            .first()
+            .ok_or_not_found(|| format!("rune {self}"))?;
+
+        let entry = convert_model_to_rune_entry(entity)?;
+
+        // names are looked up without their spacers, so a name spaced
+        // differently from the etched one names no rune
+        if let Self::Name(spaced_rune) = self {
+            if entry.spaced_rune != *spaced_rune {
+                return Err(ServerError::NotFound(format!("rune {self} not found")));
+            }
+        }
+
+        Ok((
+            RuneId::from_str(&entity.rune_id).map_err(Error::from)?,
+            entry,
+        ))
+    }
+}
+
+impl Display for RuneQuery {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "{id}"),
+            Self::Name(spaced_rune) => write!(f, "{spaced_rune}"),
+        }
+    }
+}
+
+pub(crate) fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/availability/:rune", get(rune_availability))
+        .route("/runes", get(runes))
+        .route("/rune/:rune", get(rune))
+        .route("/rune/:rune/events", get(rune_events))
+        .route("/rune/:rune/edicts", get(rune_edicts))
+        .route("/rune/:rune/holders", get(rune_holders))
+        .route("/rune/:rune/mint", get(mint::mint_status))
+        .route("/address/:address/balances", get(address_balances))
+        .route("/address/:address/events", get(address_events))
+        .route("/address/:address/pending", get(pending::address_pending))
+        .route("/mempool", get(pending::mempool))
+        .route("/psbt", post(psbt::analyze))
+        .route("/transfer", post(psbt::transfer))
+        .route("/outpoint/:outpoint/balances", get(outpoint_balances))
+        .route("/outpoint/:outpoint/lineage", get(outpoint_lineage))
+        .route("/tx/:txid/events", get(tx_events))
+        .route("/tx/:txid/runestone", get(tx_runestone))
+        .route("/cenotaphs", get(cenotaphs))
+        .layer(
+            CorsLayer::new()
+                .allow_methods([Method::GET, Method::POST])
+                .allow_origin(Any),
+        )
+        .with_state(state)
+}
+
+fn rune_json(id: RuneId, entry: &RuneEntry, height: u64, holders: Option<u64>) -> RuneJson {
+    let mint_error = entry.mintable(height).err();
+
+    RuneJson {
+        id,
+        spaced_rune: entry.spaced_rune,
+        number: entry.number,
+        block: entry.block,
+        etching: entry.etching,
+        divisibility: entry.divisibility,
+        symbol: entry.symbol,
+        premine: entry.pile(entry.premine).to_string(),
+        mints: entry.mints,
+        burned: entry.pile(entry.burned).to_string(),
+        supply: entry.pile(entry.supply()).to_string(),
+        terms: entry.terms,
+        turbo: entry.turbo,
+        timestamp: entry.timestamp,
+        mintable: mint_error.is_none(),
+        mint_error: mint_error.map(|err| err.to_string()),
+        holders,
+    }
+}
+
+// entries of the runes referenced by a response, used to format amounts
+fn load_entries(
+    store: &mut dyn RuneStore,
+    ids: impl IntoIterator<Item = String>,
+) -> ServerResult<HashMap<String, RuneEntry>> {
+    let ids = ids.into_iter().collect::<HashSet<String>>();
+
+    Ok(store
+        .gets_rune_entry(ids.into_iter().collect())?
+        .iter()
+        .map(|entity| Ok((entity.rune_id.clone(), convert_model_to_rune_entry(entity)?)))
+        .collect::<Result<HashMap<String, RuneEntry>>>()?)
+}
+
+fn entry_for<'a>(
+    entries: &'a HashMap<String, RuneEntry>,
+    rune_id: &str,
+) -> ServerResult<(RuneId, &'a RuneEntry)> {
+    let entry = entries
+        .get(rune_id)
+        .ok_or_else(|| anyhow!("missing entry for rune {rune_id}"))?;
+
+    Ok((RuneId::from_str(rune_id).map_err(Error::from)?, entry))
+}
+
+// outputs spent before spends were recorded only have the spent flag
+fn spend_json(balance: &RuneBalanceEntity) -> ServerResult<Option<SpendJson>> {
+    let (Some(txid), Some(vin), Some(block)) =
+        (&balance.spent_txid, balance.spent_vin, balance.spent_block)
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(SpendJson {
+        txid: txid.parse().map_err(Error::from)?,
+        vin,
+        block,
+    }))
+}
+
+fn events_json(
+    store: &mut dyn RuneStore,
+    events: Vec<RuneEventEntity>,
+) -> ServerResult<Vec<EventJson>> {
+    let entries = load_entries(store, events.iter().map(|event| event.rune_id.clone()))?;
+
+    let txids = events
+        .iter()
+        .map(|event| event.tx_id.clone())
+        .collect::<HashSet<String>>();
+    let txs = store
+        .load_txs(txids.into_iter().collect())?
+        .into_iter()
+        .map(|tx| (tx.tx_id.clone(), tx))
+        .collect::<HashMap<String, RuneTxEntity>>();
+
+    events
+        .into_iter()
+        .map(|event| {
+            // cenotaphs and rejected etchings have no rune, and rejected mints
+            // may name one that does not exist
+            let rune_id = (!event.rune_id.is_empty())
+                .then(|| RuneId::from_str(&event.rune_id))
+                .transpose()
+                .map_err(Error::from)?;
+            let entry = entries.get(&event.rune_id);
+
+            Ok(EventJson {
+                block: event.block,
+                event_type: EventType::try_from(event.event_type)?.to_string(),
+                txid: Txid::from_str(&event.tx_id).map_err(Error::from)?,
+                rune_id,
+                spaced_rune: entry.map(|entry| entry.spaced_rune),
+                amount: event
+                    .amount
+                    .as_ref()
+                    .map(decimal_to_u128)
+                    .transpose()?
+                    .zip(entry)
+                    .map(|(amount, entry)| entry.pile(amount).to_string()),
+                address: event.address,
+                vout: event.vout,
+                input_value: txs.get(&event.tx_id).and_then(|tx| tx.input_value),
+                fee: txs.get(&event.tx_id).and_then(|tx| tx.fee),
+                timestamp: event.timestamp,
+                reason: (!event.reason.is_empty()).then_some(event.reason),
+            })
+        })
+        .collect()
+}
+
+// cenotaphs carry no edicts
+fn runestone_json(entity: &RunestoneEntity) -> ServerResult<RunestoneJson> {
+    Ok(RunestoneJson {
+        block: entity.block,
+        txid: Txid::from_str(&entity.tx_id).map_err(Error::from)?,
+        artifact: convert_model_to_artifact(entity, &[], None)?,
+    })
+}
+
+fn indexed_height(store: &mut dyn RuneStore) -> ServerResult<u64> {
+    Ok(store
+        .load_indexer_state()?
+        .map(|state| state.height)
+        .unwrap_or_default())
+}
+
+async fn status(State(state): State<ApiState>) -> ServerResult<Json<status::Output>> {
+    let client = state.client.clone();
+    let network = state.network;
+
+    state
+        .query(move |store| Ok(Json(status::Output::load(&client, store, network)?)))
+        .await
+}
+
+async fn rune_availability(
+    State(state): State<ApiState>,
+    Path(rune): Path<String>,
+) -> ServerResult<Json<availability::Output>> {
+    let rune = rune
+        .parse::<SpacedRune>()
+        .map_err(|err| ServerError::BadRequest(format!("invalid rune `{rune}`: {err}")))?;
+
+    let client = state.client.clone();
+    let network = state.network;
+
+    state
+        .query(move |store| {
+            Ok(Json(availability::Output::load(
+                &client, store, network, rune,
+            )?))
+        })
+        .await
+}
+
+async fn runes(
+    State(state): State<ApiState>,
+    Query(page): Query<PageQuery>,
+) -> ServerResult<Json<PageJson<RuneJson>>> {
+    state
+        .query(move |store| {
+            let (offset, limit) = page.offset_and_limit();
+            let height = indexed_height(store)?;
+
+            let runes = store
+                .load_rune_entries(offset, limit)?
+                .iter()
+                .map(|entity| {
+                    Ok(rune_json(
+                        RuneId::from_str(&entity.rune_id).map_err(Error::from)?,
+                        &convert_model_to_rune_entry(entity)?,
+                        height + 1,
+                        None,
+                    ))
+                })
+                .collect::<Result<Vec<RuneJson>>>()?;
+
+            Ok(Json(page.paginate(runes)))
+        })
+        .await
+}
+
+async fn rune(
+    State(state): State<ApiState>,
+    Path(rune): Path<String>,
+) -> ServerResult<Json<RuneJson>> {
+    let rune = rune.parse::<RuneQuery>()?;
+
+    state
+        .query(move |store| {
+            let (id, entry) = rune.load(store)?;
+            let height = indexed_height(store)?;
+            let holders = store.count_holders(&id)?;
+
+            Ok(Json(rune_json(id, &entry, height + 1, Some(holders))))
+        })
+        .await
+}
+
+async fn rune_events(
+    State(state): State<ApiState>,
+    Path(rune): Path<String>,
+    Query(page): Query<PageQuery>,
+) -> ServerResult<Json<PageJson<EventJson>>> {
+    let rune = rune.parse::<RuneQuery>()?;
+
+    state
+        .query(move |store| {
+            let (id, _) = rune.load(store)?;
+            let (offset, limit) = page.offset_and_limit();
+            let events = store.load_events_by_rune(&id, offset, limit)?;
+
+            Ok(Json(page.paginate(events_json(store, events)?)))
+        })
+        .await
+}
+
+async fn rune_holders(
+    State(state): State<ApiState>,
+    Path(rune): Path<String>,
+) -> ServerResult<Json<Vec<(String, String)>>> {
+    let rune = rune.parse::<RuneQuery>()?;
+
+    state
+        .query(move |store| {
+            let (id, entry) = rune.load(store)?;
+
+            store
+                .load_top_holders(&id, TOP_HOLDERS)?
+                .into_iter()
+                .map(|holder| {
+                    let amount = decimal_to_u128(&holder.amount)
+                        .with_context(|| format!("invalid balance of {}", holder.address))?;
+
+                    Ok((holder.address, entry.pile(amount).to_string()))
+                })
+                .collect::<ServerResult<Vec<(String, String)>>>()
+                .map(Json)
+        })
+        .await
+}
+
+async fn address_balances(
+    State(state): State<ApiState>,
+    Path(address): Path<String>,
+) -> ServerResult<Json<Vec<AddressBalanceJson>>> {
+    state
+        .query(move |store| {
+            let balances = store.load_balances_by_address(&address)?;
+            let entries = load_entries(store, balances.iter().map(|b| b.rune_id.clone()))?;
+
+            balances
+                .into_iter()
+                .map(|balance| {
+                    let (rune_id, entry) = entry_for(&entries, &balance.rune_id)?;
+                    let amount = decimal_to_u128(&balance.amount)
+                        .with_context(|| format!("invalid balance of {rune_id} at {address}"))?;
+
+                    Ok(AddressBalanceJson {
+                        rune_id,
+                        spaced_rune: entry.spaced_rune,
+                        amount: entry.pile(amount).to_string(),
+                        utxo_count: balance.utxo_count,
+                    })
+                })
+                .collect::<ServerResult<Vec<AddressBalanceJson>>>()
+                .map(Json)
+        })
+        .await
+}
+
+async fn address_events(
+    State(state): State<ApiState>,
+    Path(address): Path<String>,
+    Query(page): Query<PageQuery>,
+) -> ServerResult<Json<PageJson<EventJson>>> {
+    state
+        .query(move |store| {
+            let (offset, limit) = page.offset_and_limit();
+            let events = store.load_events_by_address(&address, offset, limit)?;
+
+            Ok(Json(page.paginate(events_json(store, events)?)))
+        })
+        .await
+}
+
+async fn outpoint_balances(
+    State(state): State<ApiState>,
+    Path(outpoint): Path<String>,
+) -> ServerResult<Json<Vec<OutpointBalanceJson>>> {
+    let outpoint = outpoint
+        .parse::<OutPoint>()
+        .map_err(|err| ServerError::BadRequest(format!("invalid outpoint `{outpoint}`: {err}")))?;
+
+    state
+        .query(move |store| {
+            let balances = store.load_by_outpoint(&outpoint)?;
+            let entries = load_entries(store, balances.iter().map(|b| b.rune_id.clone()))?;
+
+            balances
+                .into_iter()
+                .map(|balance| {
+                    let (rune_id, entry) = entry_for(&entries, &balance.rune_id)?;
+                    let amount = decimal_to_u128(&balance.amount)
+                        .with_context(|| format!("invalid balance of {rune_id} at {outpoint}"))?;
+
+                    Ok(OutpointBalanceJson {
+                        rune_id,
+                        spaced_rune: entry.spaced_rune,
+                        amount: entry.pile(amount).to_string(),
+                        spent_by: spend_json(&balance)?,
+                        address: balance.address,
+                        spent: balance.spent,
+                    })
+                })
+                .collect::<ServerResult<Vec<OutpointBalanceJson>>>()
+                .map(Json)
+        })
+        .await
+}
+
+async fn outpoint_lineage(
+    State(state): State<ApiState>,
+    Path(outpoint): Path<String>,
+) -> ServerResult<Json<LineageJson>> {
+    let outpoint = outpoint
+        .parse::<OutPoint>()
+        .map_err(|err| ServerError::BadRequest(format!("invalid outpoint `{outpoint}`: {err}")))?;
+
+    state
+        .query(move |store| {
+            let lineage = lineage(store, &outpoint, MAX_LINEAGE_OUTPUTS)?;
+            let entries = load_entries(
+                store,
+                lineage
+                    .nodes
+                    .iter()
+                    .map(|node| node.balance.rune_id.clone()),
+            )?;
+
+            let outputs = lineage
+                .nodes
+                .into_iter()
+                .map(|node| {
+                    let balance = node.balance;
+                    let (rune_id, entry) = entry_for(&entries, &balance.rune_id)?;
+                    let amount = decimal_to_u128(&balance.amount).with_context(|| {
+                        format!("invalid balance of {rune_id} at {}", balance.out_point)
+                    })?;
+
+                    Ok(LineageOutputJson {
+                        outpoint: balance.out_point.parse().map_err(Error::from)?,
+                        rune_id,
+                        spaced_rune: entry.spaced_rune,
+                        amount: entry.pile(amount).to_string(),
+                        block: balance.block,
+                        spent_by: spend_json(&balance)?,
+                        address: balance.address,
+                        parents: node
+                            .parents
+                            .iter()
+                            .map(|parent| parent.parse())
+                            .collect::<Result<Vec<OutPoint>, _>>()
+                            .map_err(Error::from)?,
+                    })
+                })
+                .collect::<ServerResult<Vec<LineageOutputJson>>>()?;
+
+            Ok(Json(LineageJson {
+                outputs,
+                truncated: lineage.truncated,
+            }))
+        })
+        .await
+}
+
+async fn tx_events(
+    State(state): State<ApiState>,
+    Path(txid): Path<String>,
+) -> ServerResult<Json<Vec<EventJson>>> {
+    let txid = txid
+        .parse::<Txid>()
+        .map_err(|err| ServerError::BadRequest(format!("invalid txid `{txid}`: {err}")))?;
+
+    state
+        .query(move |store| {
+            let events = store.load_events_by_tx(&txid)?;
+
+            Ok(Json(events_json(store, events)?))
+        })
+        .await
+}
+
+async fn tx_runestone(
+    State(state): State<ApiState>,
+    Path(txid): Path<String>,
+) -> ServerResult<Json<RunestoneJson>> {
+    let txid = txid
+        .parse::<Txid>()
+        .map_err(|err| ServerError::BadRequest(format!("invalid txid `{txid}`: {err}")))?;
+
+    state
+        .query(move |store| {
+            let (entity, edicts) = store
+                .load_runestone(&txid)?
+                .ok_or_not_found(|| format!("runestone of {txid}"))?;
+
+            // edicts of the rune etched by the tx are stored with its ID
+            let etched = if entity.etching {
+                store
+                    .gets_rune_entry(edicts.iter().map(|edict| edict.rune_id.clone()).collect())?
+                    .into_iter()
+                    .find(|entry| entry.etching == entity.tx_id)
+                    .map(|entry| RuneId::from_str(&entry.rune_id))
+                    .transpose()
+                    .map_err(Error::from)?
+            } else {
+                None
+            };
+
+            Ok(Json(RunestoneJson {
+                block: entity.block,
+                txid,
+                artifact: convert_model_to_artifact(&entity, &edicts, etched)?,
+            }))
+        })
+        .await
+}
+
+async fn rune_edicts(
+    State(state): State<ApiState>,
+    Path(rune): Path<String>,
+    Query(page): Query<PageQuery>,
+) -> ServerResult<Json<PageJson<EdictJson>>> {
+    let rune = rune.parse::<RuneQuery>()?;
+
+    state
+        .query(move |store| {
+            let (id, entry) = rune.load(store)?;
+            let (offset, limit) = page.offset_and_limit();
+
+            let edicts = store
+                .load_edicts_by_rune(&id, offset, limit)?
+                .into_iter()
+                .map(|edict| {
+                    let amount = decimal_to_u128(&edict.amount)
+                        .with_context(|| format!("invalid edict amount in {}", edict.tx_id))?;
+
+                    Ok(EdictJson {
+                        block: edict.block,
+                        txid: Txid::from_str(&edict.tx_id).map_err(Error::from)?,
+                        edict_index: edict.edict_index,
+                        amount: entry.pile(amount).to_string(),
+                        output: edict.output,
+                    })
+                })
+                .collect::<ServerResult<Vec<EdictJson>>>()?;
+
+            Ok(Json(page.paginate(edicts)))
+        })
+        .await
+}
+
+async fn cenotaphs(
+    State(state): State<ApiState>,
+    Query(page): Query<PageQuery>,
+    Query(flaw): Query<FlawQuery>,
+) -> ServerResult<Json<PageJson<RunestoneJson>>> {
+    let flaw = flaw
+        .flaw
+        .map(|flaw| {
+            parse_flaw(&flaw)
+                .map_err(|err| ServerError::BadRequest(format!("invalid flaw `{flaw}`: {err}")))
+        })
+        .transpose()?;
+
+    state
+        .query(move |store| {
+            let (offset, limit) = page.offset_and_limit();
+
+            let cenotaphs = store
+                .load_cenotaphs(flaw, offset, limit)?
+                .iter()
+                .map(runestone_json)
+                .collect::<ServerResult<Vec<RunestoneJson>>>()?;
+
+            Ok(Json(page.paginate(cenotaphs)))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::dao::for_each_store};
+
+    #[test]
+    fn rune_query() {
+        assert_eq!(
+            "840000:1".parse::<RuneQuery>().unwrap(),
+            RuneQuery::Id(RuneId {
+                block: 840000,
+                tx: 1
+            })
+        );
+        assert_eq!(
+            "UNCOMMON•GOODS".parse::<RuneQuery>().unwrap(),
+            RuneQuery::Name("UNCOMMON•GOODS".parse().unwrap())
+        );
+        assert!("foo".parse::<RuneQuery>().is_err());
+    }
+
+    #[test]
+    fn rune_names_need_the_etched_spacers() {
+        for_each_store(|store| {
+            let id = RuneId {
+                block: 840000,
+                tx: 1,
+            };
+            store
+                .store_rune_entry(
+                    &id,
+                    &RuneEntry {
+                        spaced_rune: "A•B".parse().unwrap(),
+                        ..default()
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                "A•B".parse::<RuneQuery>().unwrap().load(store).unwrap().0,
+                id
+            );
+
+            assert!(matches!(
+                "AB".parse::<RuneQuery>().unwrap().load(store),
+                Err(ServerError::NotFound(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn paginate() {
+        let page = PageQuery { page: Some(2) };
+
+        assert_eq!(page.offset_and_limit(), (200, 101));
+
+        let json = page.paginate((0..101).collect::<Vec<i32>>());
+        assert_eq!(json.items.len(), 100);
+        assert!(json.more);
+
+        let json = page.paginate((0..100).collect::<Vec<i32>>());
+        assert!(!json.more);
+    }
+
+    #[test]
+    fn rune_json_mint_status() {
+        let entry = RuneEntry {
+            divisibility: 2,
+            premine: 1000,
+            mints: 1,
+            terms: Some(Terms {
+                amount: Some(500),
+                cap: Some(1),
+                ..default()
+            }),
+            ..default()
+        };
+
+        let json = rune_json(RuneId { block: 1, tx: 0 }, &entry, 2, None);
+
+        assert_eq!(json.supply, "15\u{A0}¤");
+        assert!(!json.mintable);
+        assert_eq!(json.mint_error.as_deref(), Some("limited to 1 mints"));
+    }
+}
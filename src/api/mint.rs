@@ -0,0 +1,175 @@
+use super::*;
+
+/// Minting state of a rune at a given height, derived from its terms.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MintStatus {
+    pub id: RuneId,
+    pub spaced_rune: SpacedRune,
+    pub height: u64,
+    pub open: bool,
+    pub mint_error: Option<String>,
+    pub amount: Option<String>,
+    pub mints: u128,
+    pub cap: Option<u128>,
+    pub mints_remaining: Option<u128>,
+    pub percent_minted: Option<f64>,
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+    pub blocks_until_start: Option<u64>,
+    pub blocks_until_end: Option<u64>,
+    pub supply: String,
+    pub max_supply: Option<String>,
+}
+
+impl MintStatus {
+    /// Status of minting `entry` in a block at `height`.
+    pub(crate) fn new(id: RuneId, entry: &RuneEntry, height: u64) -> Self {
+        let mint_error = entry.mintable(height).err();
+        let terms = entry.terms;
+        let cap = terms.and_then(|terms| terms.cap);
+        let start = entry.start();
+        let end = entry.end();
+
+        // the maximum supply is what the etching allows, once every mint is made
+        let max_supply = Etching {
+            premine: Some(entry.premine),
+            terms,
+            ..default()
+        }
+        .supply();
+
+        Self {
+            id,
+            spaced_rune: entry.spaced_rune,
+            height,
+            open: mint_error.is_none(),
+            mint_error: mint_error.map(|err| err.to_string()),
+            amount: terms
+                .and_then(|terms| terms.amount)
+                .map(|amount| entry.pile(amount).to_string()),
+            mints: entry.mints,
+            cap,
+            mints_remaining: cap.map(|cap| cap.saturating_sub(entry.mints)),
+            percent_minted: cap
+                .filter(|cap| *cap > 0)
+                .map(|cap| entry.mints as f64 / cap as f64 * 100.0),
+            start,
+            end,
+            blocks_until_start: start.map(|start| start.saturating_sub(height)),
+            blocks_until_end: end.map(|end| end.saturating_sub(height)),
+            supply: entry.pile(entry.supply()).to_string(),
+            max_supply: max_supply.map(|supply| entry.pile(supply).to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct HeightQuery {
+    height: Option<u64>,
+}
+
+pub(super) async fn mint_status(
+    State(state): State<ApiState>,
+    Path(rune): Path<String>,
+    Query(query): Query<HeightQuery>,
+) -> ServerResult<Json<MintStatus>> {
+    let rune = rune.parse::<RuneQuery>()?;
+
+    state
+        .query(move |store| {
+            let (id, entry) = rune.load(store)?;
+
+            // by default, whether a mint would be valid in the next block
+            let height = match query.height {
+                Some(height) => height,
+                None => indexed_height(store)? + 1,
+            };
+
+            Ok(Json(MintStatus::new(id, &entry, height)))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: RuneId = RuneId { block: 10, tx: 1 };
+
+    fn entry(mints: u128) -> RuneEntry {
+        RuneEntry {
+            block: 10,
+            divisibility: 1,
+            premine: 100,
+            mints,
+            terms: Some(Terms {
+                amount: Some(10),
+                cap: Some(4),
+                offset: (Some(5), None),
+                height: (None, Some(30)),
+            }),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn before_start() {
+        let status = MintStatus::new(ID, &entry(0), 12);
+
+        assert!(!status.open);
+        assert_eq!(
+            status.mint_error.as_deref(),
+            Some("mint starts on block 15")
+        );
+        assert_eq!(status.start, Some(15));
+        assert_eq!(status.end, Some(30));
+        assert_eq!(status.blocks_until_start, Some(3));
+        assert_eq!(status.blocks_until_end, Some(18));
+        assert_eq!(status.mints_remaining, Some(4));
+        assert_eq!(status.percent_minted, Some(0.0));
+    }
+
+    #[test]
+    fn open() {
+        let status = MintStatus::new(ID, &entry(1), 20);
+
+        assert!(status.open);
+        assert_eq!(status.mint_error, None);
+        assert_eq!(status.amount.as_deref(), Some("1\u{A0}¤"));
+        assert_eq!(status.mints_remaining, Some(3));
+        assert_eq!(status.percent_minted, Some(25.0));
+        assert_eq!(status.blocks_until_start, Some(0));
+        assert_eq!(status.supply, "11\u{A0}¤");
+        assert_eq!(status.max_supply.as_deref(), Some("14\u{A0}¤"));
+    }
+
+    #[test]
+    fn closed() {
+        assert_eq!(
+            MintStatus::new(ID, &entry(4), 20).mint_error.as_deref(),
+            Some("limited to 4 mints")
+        );
+        assert_eq!(
+            MintStatus::new(ID, &entry(1), 30).mint_error.as_deref(),
+            Some("mint ended on block 30")
+        );
+    }
+
+    #[test]
+    fn unmintable() {
+        let status = MintStatus::new(
+            ID,
+            &RuneEntry {
+                premine: 5,
+                ..default()
+            },
+            1,
+        );
+
+        assert!(!status.open);
+        assert_eq!(status.mint_error.as_deref(), Some("not mintable"));
+        assert_eq!(status.cap, None);
+        assert_eq!(status.percent_minted, None);
+        assert_eq!(status.max_supply.as_deref(), Some("5\u{A0}¤"));
+    }
+}
@@ -0,0 +1,98 @@
+use super::*;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct MempoolJson {
+    pub height: u32,
+    pub transactions: usize,
+    pub mints: Vec<PendingMintJson>,
+    pub etchings: Vec<PendingEtchingJson>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingMintJson {
+    pub rune_id: RuneId,
+    pub spaced_rune: SpacedRune,
+    pub mints: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingEtchingJson {
+    pub txid: Txid,
+    pub rune_id: RuneId,
+    pub spaced_rune: SpacedRune,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingBalanceJson {
+    pub rune_id: RuneId,
+    pub spaced_rune: SpacedRune,
+    pub incoming: String,
+    pub outgoing: String,
+}
+
+fn pending_entry(state: &PendingState, id: &RuneId) -> ServerResult<RuneEntry> {
+    state
+        .entries
+        .get(id)
+        .copied()
+        .ok_or_else(|| anyhow!("missing pending entry for rune {id}").into())
+}
+
+pub(super) async fn mempool(State(state): State<ApiState>) -> ServerResult<Json<MempoolJson>> {
+    let pending = state.pending_state()?;
+
+    Ok(Json(MempoolJson {
+        height: pending.height,
+        transactions: pending.transactions,
+        mints: pending
+            .mints
+            .iter()
+            .map(|(id, mints)| {
+                Ok(PendingMintJson {
+                    rune_id: *id,
+                    spaced_rune: pending_entry(&pending, id)?.spaced_rune,
+                    mints: *mints,
+                })
+            })
+            .collect::<ServerResult<Vec<PendingMintJson>>>()?,
+        etchings: pending
+            .etched
+            .iter()
+            .map(|id| {
+                let entry = pending_entry(&pending, id)?;
+
+                Ok(PendingEtchingJson {
+                    txid: entry.etching,
+                    rune_id: *id,
+                    spaced_rune: entry.spaced_rune,
+                })
+            })
+            .collect::<ServerResult<Vec<PendingEtchingJson>>>()?,
+    }))
+}
+
+pub(super) async fn address_pending(
+    State(state): State<ApiState>,
+    Path(address): Path<String>,
+) -> ServerResult<Json<Vec<PendingBalanceJson>>> {
+    let pending = state.pending_state()?;
+
+    let Some(balances) = pending.balances.get(&address) else {
+        return Ok(Json(Vec::new()));
+    };
+
+    balances
+        .iter()
+        .map(|(id, balance)| {
+            let entry = pending_entry(&pending, id)?;
+
+            Ok(PendingBalanceJson {
+                rune_id: *id,
+                spaced_rune: entry.spaced_rune,
+                incoming: entry.pile(balance.incoming).to_string(),
+                outgoing: entry.pile(balance.outgoing).to_string(),
+            })
+        })
+        .collect::<ServerResult<Vec<PendingBalanceJson>>>()
+        .map(Json)
+}
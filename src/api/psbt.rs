@@ -0,0 +1,106 @@
+use {
+    super::*,
+    crate::psbt::{Offer, RuneTransfer, TransferBuilder},
+    base64::Engine,
+};
+
+#[derive(Deserialize)]
+pub(super) struct TransferRequest {
+    /// address holding the runes to send
+    source: Address<NetworkUnchecked>,
+    /// outputs holding no runes, paying for postage and fees
+    funding: Vec<OutPoint>,
+    transfers: Vec<TransferJson>,
+    /// receives leftover runes and bitcoin, defaults to `source`
+    change: Option<Address<NetworkUnchecked>>,
+    /// in sats per vbyte
+    fee_rate: u64,
+}
+
+#[derive(Deserialize)]
+pub(super) struct TransferJson {
+    destination: Address<NetworkUnchecked>,
+    rune_id: RuneId,
+    amount: u128,
+}
+
+#[derive(Serialize)]
+pub(super) struct UnsignedTransferJson {
+    /// base64 encoded
+    psbt: String,
+    fee: u64,
+    vsize: u64,
+}
+
+/// Analyze a PSBT or transaction posted as hex or base64.
+pub(super) async fn analyze(
+    State(state): State<ApiState>,
+    body: String,
+) -> ServerResult<Json<TxDetail>> {
+    let offer = body
+        .parse::<Offer>()
+        .map_err(|err| ServerError::BadRequest(format!("invalid offer: {err:#}")))?;
+
+    let network = state.network;
+
+    state
+        .query(move |store| Ok(Json(offer.analyze(store, network)?)))
+        .await
+}
+
+/// Build an unsigned PSBT sending runes held by the source address.
+pub(super) async fn transfer(
+    State(state): State<ApiState>,
+    Json(request): Json<TransferRequest>,
+) -> ServerResult<Json<UnsignedTransferJson>> {
+    let network = state.network;
+    let require_network = |address: Address<NetworkUnchecked>| {
+        address
+            .require_network(network)
+            .map_err(|err| ServerError::BadRequest(format!("invalid address: {err}")))
+    };
+
+    let source = require_network(request.source)?;
+    let change = match request.change {
+        Some(change) => require_network(change)?,
+        None => source.clone(),
+    };
+
+    let transfers = request
+        .transfers
+        .into_iter()
+        .map(|transfer| {
+            Ok(RuneTransfer {
+                destination: require_network(transfer.destination)?.script_pubkey(),
+                id: transfer.rune_id,
+                amount: transfer.amount,
+            })
+        })
+        .collect::<ServerResult<Vec<RuneTransfer>>>()?;
+
+    let client = state.client.clone();
+
+    state
+        .query(move |store| {
+            let builder = TransferBuilder::load(
+                &client,
+                store,
+                &source.to_string(),
+                &request.funding,
+                transfers,
+                change.script_pubkey(),
+                request.fee_rate,
+            )?;
+
+            let transfer = builder
+                .build()
+                .map_err(|err| ServerError::BadRequest(format!("{err:#}")))?;
+
+            Ok(Json(UnsignedTransferJson {
+                psbt: base64::engine::general_purpose::STANDARD.encode(transfer.psbt.serialize()),
+                fee: transfer.fee,
+                vsize: transfer.vsize,
+            }))
+        })
+        .await
+}
@@ -0,0 +1,18 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+#[command(version)]
+pub(crate) struct Arguments {
+    #[command(flatten)]
+    pub(crate) options: Options,
+    #[command(subcommand)]
+    pub(crate) subcommand: Subcommand,
+}
+
+impl Arguments {
+    pub(crate) fn run(self) -> SubcommandResult {
+        let settings = Settings::load(self.options)?;
+
+        self.subcommand.run(settings)
+    }
+}
@@ -1,51 +1,453 @@
+use self::model::EtchingEntity;
+use self::model::IndexerStateEntity;
+use self::model::RuneAddressBalanceEntity;
 use self::model::RuneBalanceEntity;
+use self::model::RuneBlockEntity;
 use self::model::RuneEventEntity;
+use self::model::RuneTxEntity;
+use self::model::RunestoneEdictEntity;
+use self::model::RunestoneEntity;
 use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::MysqlConnection;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
 use super::*;
 
+mod etching;
+mod indexer_state;
+#[cfg(test)]
+mod memory;
+mod postgres;
+mod redb_store;
+mod runes_address_balance;
 mod runes_balance;
+mod runes_block;
 mod runes_entry;
 mod runes_event;
+mod runes_tx;
+mod runestone;
 
-pub struct RuneMysqlDao {}
+#[cfg(test)]
+pub(crate) use memory::{balance, for_each_store, outpoint, spend};
+pub(crate) use redb_store::RedbStore;
+pub(crate) use runes_entry::convert_model_to_rune_entry;
+pub(crate) use runestone::{convert_artifact_to_model, convert_model_to_artifact, parse_flaw};
 
+/// Storage of the rune index. Each table has its own trait, implemented by a
+/// store instance, so indexing and serving work against any backend that
+/// implements them all.
+pub(crate) trait RuneStore:
+    RuneEntryDao
+    + RuneEventDao
+    + RuneTxDao
+    + RuneBlanaceDao
+    + RuneAddressBalanceDao
+    + RuneBlockDao
+    + IndexerStateDao
+    + EtchingDao
+    + RunestoneDao
+    + Send
+{
+    /// Run `f`, keeping its writes only if it succeeds.
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn RuneStore) -> Result) -> Result;
+
+    /// Create or upgrade the tables, applying the migrations not yet run.
+    fn migrate(&mut self) -> Result;
+}
+
+/// Stores that concurrent readers, like API requests, each take one of.
+/// Database servers are reached over a pool of connections, so readers don't
+/// wait for each other, while a redb file is opened for every reader.
+#[derive(Clone)]
+pub(crate) enum StorePool {
+    Mysql(Pool<ConnectionManager<MysqlConnection>>),
+    Postgres(Pool<ConnectionManager<PgConnection>>),
+    Redb(PathBuf),
+}
+
+impl StorePool {
+    /// Run `f` against a store of the pool, waiting for one to be free.
+    pub(crate) fn run<T>(&self, f: impl FnOnce(&mut dyn RuneStore) -> T) -> Result<T> {
+        Ok(match self {
+            Self::Mysql(pool) => f(&mut *pool.get()?),
+            Self::Postgres(pool) => f(&mut *pool.get()?),
+            Self::Redb(path) => f(&mut RedbStore::open(path)?),
+        })
+    }
+}
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/mysql");
+
+impl RuneStore for MysqlConnection {
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn RuneStore) -> Result) -> Result {
+        self.transaction(|conn| f(conn))
+    }
+
+    fn migrate(&mut self) -> Result {
+        self.run_pending_migrations(MIGRATIONS)
+            .map_err(|err| anyhow!("failed to migrate database: {err}"))?;
+
+        Ok(())
+    }
+}
+
+/// Convert a `NUMERIC(39,0)` column to a u128, failing on negative,
+/// fractional or oversized values instead of panicking.
+pub(crate) fn decimal_to_u128(value: &BigDecimal) -> Result<u128> {
+    if !value.is_integer() {
+        bail!("{value} is not an integer");
+    }
+
+    value
+        .to_u128()
+        .ok_or_else(|| anyhow!("{value} is out of range for u128"))
+}
+
+#[cfg(test)]
 pub fn new_db_conn(database_url: &str) -> MysqlConnection {
-    MysqlConnection::establish(&database_url)
+    MysqlConnection::establish(database_url)
         .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
 }
 
-pub trait RuneEntryDao {
-    fn gets_rune_entry(
-        conn: &mut MysqlConnection,
-        ids: Vec<String>,
-    ) -> Result<Vec<RuneEntryEntity>>;
-    fn load_entry_by_rune(conn: &mut MysqlConnection, _rune: &Rune) -> Result<RuneEntry>;
-    fn load_rune_entry(conn: &mut MysqlConnection, id: &RuneId) -> Result<RuneEntry>;
-    fn store_rune_entry(conn: &mut MysqlConnection, id: &RuneId, entry: &RuneEntry) -> Result<()>;
-    fn update_rune_mints(conn: &mut MysqlConnection, id: &RuneId, _mints: u128) -> Result<()>;
-    fn update_rune_burned(conn: &mut MysqlConnection, id: &RuneId, _burned: u128) -> Result<()>;
-    fn delete_rune_entry(conn: &mut MysqlConnection, id: &RuneId) -> Result<()>;
-    fn gets_rune_number(conn: &mut MysqlConnection) -> Option<u64>;
-}
-
-pub trait RuneEventDao {
-    fn load(conn: &mut MysqlConnection, id: u64) -> Result<RuneEventEntity>;
-    fn store_events(conn: &mut MysqlConnection, entry: &Vec<RuneEventEntity>) -> Result<()>;
-    fn delete(conn: &mut MysqlConnection, id: &Txid) -> Result<()>;
-}
-
-pub trait RuneBlanaceDao {
-    fn load_by_outpoints(
-        conn: &mut MysqlConnection,
-        outpoints: Vec<String>,
-    ) -> Result<Vec<RuneBalanceEntity>>;
-    fn load_by_outpoint(
-        conn: &mut MysqlConnection,
-        outpoint: &OutPoint,
-    ) -> Result<Vec<RuneBalanceEntity>>;
-    fn updates_spend_out_point(conn: &mut MysqlConnection, outpoints: Vec<String>) -> Result;
-    fn update_spend_out_point(conn: &mut MysqlConnection, outpoint: &OutPoint) -> Result<()>;
-    fn store_balances(conn: &mut MysqlConnection, entry: &Vec<RuneBalanceEntity>) -> Result<()>;
+pub(crate) trait RuneEntryDao {
+    fn gets_rune_entry(&mut self, ids: Vec<String>) -> Result<Vec<RuneEntryEntity>>;
+    fn gets_rune_entry_by_runes(&mut self, runes: Vec<Rune>) -> Result<Vec<RuneEntryEntity>>;
+    fn load_entry_by_rune(&mut self, _rune: &Rune) -> Result<Option<RuneEntry>>;
+    fn store_rune_entry(&mut self, id: &RuneId, entry: &RuneEntry) -> Result<()>;
+    fn update_rune_mints(&mut self, id: &RuneId, _mints: u128) -> Result<()>;
+    fn update_rune_burned(&mut self, id: &RuneId, _burned: u128) -> Result<()>;
+    fn delete_rune_entries_after(&mut self, height: u64) -> Result<()>;
+    fn gets_rune_number(&mut self) -> Option<u64>;
+    fn load_rune_entries(&mut self, offset: i64, limit: i64) -> Result<Vec<RuneEntryEntity>>;
+}
+
+pub(crate) trait RuneEventDao {
+    fn store_events(&mut self, entities: &[RuneEventEntity]) -> Result<()>;
+    fn load_events_after(&mut self, height: u64) -> Result<Vec<RuneEventEntity>>;
+    fn delete_events_after(&mut self, height: u64) -> Result<()>;
+    fn load_events_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneEventEntity>>;
+    fn load_events_by_rune(
+        &mut self,
+        id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>>;
+    fn load_events_by_address(
+        &mut self,
+        address: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>>;
+}
+
+pub(crate) trait RuneTxDao {
+    fn store_txs(&mut self, entities: &[RuneTxEntity]) -> Result<()>;
+    fn load_txs(&mut self, txids: Vec<String>) -> Result<Vec<RuneTxEntity>>;
+    fn delete_txs_after(&mut self, height: u64) -> Result<()>;
+}
+
+pub(crate) trait RuneBlanaceDao {
+    fn load_by_outpoints(&mut self, outpoints: Vec<String>) -> Result<Vec<RuneBalanceEntity>>;
+    fn load_by_outpoint(&mut self, outpoint: &OutPoint) -> Result<Vec<RuneBalanceEntity>>;
+    fn updates_spend_out_point(&mut self, spends: &[Spend]) -> Result;
+    fn store_balances(&mut self, entry: &[RuneBalanceEntity]) -> Result<()>;
+    /// Clear the spends recorded by blocks after `height`.
+    fn unspend_out_points_after(&mut self, height: u64) -> Result<()>;
+    /// Clear the spent flag of those of `outpoints` spent before spends were
+    /// recorded, which have no spend block to be rolled back by.
+    fn unspend_unrecorded_out_points(&mut self, outpoints: Vec<String>) -> Result<()>;
+    /// Whether any balance was spent before spends were recorded.
+    fn has_unrecorded_spends(&mut self) -> Result<bool>;
+    fn delete_balances_after(&mut self, height: u64) -> Result<()>;
+    /// Addresses of balances created or spent after `height`.
+    fn load_addresses_after(&mut self, height: u64) -> Result<Vec<String>>;
+    fn load_unspent_by_address(&mut self, address: &str) -> Result<Vec<RuneBalanceEntity>>;
+    /// Balances spent by the inputs of `txid`.
+    fn load_spent_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneBalanceEntity>>;
+}
+
+/// Input of a transaction spending an outpoint that holds runes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Spend {
+    pub(crate) outpoint: OutPoint,
+    pub(crate) txid: Txid,
+    pub(crate) vin: u32,
+    pub(crate) block: u64,
+}
+
+/// Most spends recorded by a single update, keeping its binds well under the
+/// placeholder limits of MySQL and PostgreSQL.
+const SPEND_BATCH_SIZE: usize = 1_000;
+
+/// Update recording `count` spends of `rune_balance` at once, `placeholder(n)`
+/// being the backend's placeholder for the n-th bind, starting at 1. For each
+/// of `spent_txid`, `spent_vin` and `spent_block`, it binds the outpoint and
+/// value of every spend, then binds every outpoint.
+fn spends_update_sql(count: usize, placeholder: impl Fn(usize) -> String) -> String {
+    let mut binds = 0;
+    let mut bind = || {
+        binds += 1;
+        placeholder(binds)
+    };
+
+    let mut sets = vec!["spent = TRUE".to_string()];
+    for column in ["spent_txid", "spent_vin", "spent_block"] {
+        let mut set = format!("{column} = CASE out_point");
+        for _ in 0..count {
+            set += &format!(" WHEN {} THEN {}", bind(), bind());
+        }
+        sets.push(set + " END");
+    }
+
+    let outpoints = (0..count).map(|_| bind()).collect::<Vec<String>>();
+
+    format!(
+        "UPDATE rune_balance SET {} WHERE out_point IN ({}) AND spent = FALSE",
+        sets.join(", "),
+        outpoints.join(", ")
+    )
+}
+
+/// Error unless each of `spends` spends a different one of the `unspent`
+/// outpoints, so that no spend overwrites another or goes unrecorded.
+fn ensure_unspent(spends: &[Spend], mut unspent: HashSet<String>) -> Result {
+    for spend in spends {
+        ensure!(
+            unspent.remove(&spend.outpoint.to_string()),
+            "outpoint {} holds no unspent runes",
+            spend.outpoint
+        );
+    }
+
+    Ok(())
+}
+
+pub(crate) trait RuneAddressBalanceDao {
+    fn load_address_balances(
+        &mut self,
+        addresses: Vec<String>,
+    ) -> Result<Vec<RuneAddressBalanceEntity>>;
+    fn load_balances_by_address(&mut self, address: &str) -> Result<Vec<RuneAddressBalanceEntity>>;
+    fn load_top_holders(
+        &mut self,
+        id: &RuneId,
+        limit: i64,
+    ) -> Result<Vec<RuneAddressBalanceEntity>>;
+    fn count_holders(&mut self, id: &RuneId) -> Result<u64>;
+    fn store_address_balances(&mut self, entities: &[RuneAddressBalanceEntity]) -> Result<()>;
+    fn delete_address_balances(&mut self, ids: Vec<u64>) -> Result<()>;
+    fn rebuild_address_balances(&mut self, addresses: Vec<String>) -> Result<()>;
+}
+
+pub(crate) trait RuneBlockDao {
+    fn load_block_hash(&mut self, height: u64) -> Result<Option<BlockHash>>;
+    fn store_block_header(&mut self, height: u64, header: &Header) -> Result<()>;
+    fn delete_blocks_after(&mut self, height: u64) -> Result<()>;
+}
+
+pub(crate) trait IndexerStateDao {
+    fn load_indexer_state(&mut self) -> Result<Option<IndexerStateEntity>>;
+    fn store_indexer_state(
+        &mut self,
+        network: Network,
+        height: u64,
+        hash: &BlockHash,
+    ) -> Result<()>;
+    fn delete_indexer_state(&mut self) -> Result<()>;
+}
+
+pub(crate) trait EtchingDao {
+    fn store_etching(&mut self, entity: &EtchingEntity) -> Result<u64>;
+    fn load_etching(&mut self, id: u64) -> Result<Option<EtchingEntity>>;
+    fn load_etchings_in_states(&mut self, states: Vec<u8>) -> Result<Vec<EtchingEntity>>;
+    fn update_etching(&mut self, entity: &EtchingEntity) -> Result<()>;
+}
+
+pub(crate) trait RunestoneDao {
+    fn store_runestones(&mut self, entities: &[RunestoneEntity]) -> Result<()>;
+    fn store_edicts(&mut self, entities: &[RunestoneEdictEntity]) -> Result<()>;
+    fn load_runestone(
+        &mut self,
+        txid: &Txid,
+    ) -> Result<Option<(RunestoneEntity, Vec<RunestoneEdictEntity>)>>;
+    fn load_edicts_by_rune(
+        &mut self,
+        id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEdictEntity>>;
+    fn load_cenotaphs(
+        &mut self,
+        flaw: Option<Flaw>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEntity>>;
+    fn delete_runestones_after(&mut self, height: u64) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        diesel::{connection::SimpleConnection, dsl::sql, sql_types::Text},
+        ordinals::inscription_id::txid,
+        std::sync::{Mutex, MutexGuard, PoisonError},
+    };
+
+    /// Connection to the database at `RUNES_TEST_MYSQL_URL` with every table
+    /// dropped, or `None` if no test database was given. Tests sharing the
+    /// database run one at a time, for as long as they hold the guard.
+    pub(crate) fn empty_test_database() -> Option<(MutexGuard<'static, ()>, MysqlConnection)> {
+        static LOCK: Mutex<()> = Mutex::new(());
+
+        let url = env::var("RUNES_TEST_MYSQL_URL").ok()?;
+
+        let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let mut conn = new_db_conn(&url);
+
+        conn.batch_execute(
+            "DROP TABLE IF EXISTS __diesel_schema_migrations, rune_entry, rune_event, \
+             rune_balance, rune_block, indexer_state, rune_address_balance, etching, \
+             runestone, runestone_edict, rune_tx;",
+        )
+        .unwrap();
+
+        Some((guard, conn))
+    }
+
+    #[test]
+    fn spends_update_picks_values_by_outpoint() {
+        assert_eq!(
+            spends_update_sql(2, |n| format!("${n}")),
+            "UPDATE rune_balance SET spent = TRUE, \
+             spent_txid = CASE out_point WHEN $1 THEN $2 WHEN $3 THEN $4 END, \
+             spent_vin = CASE out_point WHEN $5 THEN $6 WHEN $7 THEN $8 END, \
+             spent_block = CASE out_point WHEN $9 THEN $10 WHEN $11 THEN $12 END \
+             WHERE out_point IN ($13, $14) AND spent = FALSE"
+        );
+    }
+
+    #[test]
+    fn legacy_schema_upgrades() {
+        let Some((_guard, mut conn)) = empty_test_database() else {
+            return;
+        };
+
+        conn.batch_execute(include_str!("../migrations/legacy/mysql.sql"))
+            .unwrap();
+
+        conn.migrate().unwrap();
+
+        // the sql files created no indexer state, so indexing starts over
+        assert!(conn.load_indexer_state().unwrap().is_none());
+
+        let rune_stone = |conn: &mut MysqlConnection| {
+            diesel::select(sql::<Text>(&format!(
+                "(SELECT rune_stone FROM rune_event WHERE tx_id = '{}')",
+                txid(1)
+            )))
+            .get_result::<String>(conn)
+            .unwrap()
+        };
+        assert_eq!(rune_stone(&mut conn), "{}");
+
+        // reverting the upgrade drops the columns it added and keeps the
+        // artifacts of legacy events
+        conn.revert_last_migration(MIGRATIONS).unwrap();
+        assert!(conn.batch_execute("SELECT reason FROM rune_event").is_err());
+        assert!(conn
+            .batch_execute("SELECT script_type FROM rune_balance")
+            .is_err());
+        assert_eq!(rune_stone(&mut conn), "{}");
+        conn.migrate().unwrap();
+
+        let legacy = outpoint(txid(1), 0);
+        assert_eq!(conn.load_by_outpoint(&legacy).unwrap().len(), 1);
+
+        let events = conn.load_events_by_tx(&txid(1)).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].reason, "");
+
+        // legacy events had no input value, so their tx is backfilled without one
+        let txs = conn.load_txs(vec![txid(1).to_string()]).unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].block, events[0].block);
+        assert_eq!(txs[0].input_value, None);
+
+        // scripts of outputs without an address can be over 128 bytes
+        let script = "51".repeat(200);
+
+        conn.store_events(&[RuneEventEntity {
+            block: 840001,
+            event_type: 6,
+            tx_id: txid(2).to_string(),
+            rune_id: "840000:1".into(),
+            pk_script_hex: script.clone(),
+            reason: "mint cap reached".into(),
+            ..default()
+        }])
+        .unwrap();
+
+        let events = conn.load_events_by_tx(&txid(2)).unwrap();
+        assert_eq!(events[0].reason, "mint cap reached");
+        assert_eq!(events[0].pk_script_hex, script);
+
+        let nonstandard = outpoint(txid(2), 0);
+        conn.store_balances(&[RuneBalanceEntity {
+            block: 840001,
+            address: String::new(),
+            pk_script_hex: script.clone(),
+            script_type: "nonstandard".into(),
+            ..balance(nonstandard, "840000:1", 100)
+        }])
+        .unwrap();
+        assert_eq!(
+            conn.load_by_outpoint(&nonstandard).unwrap()[0].pk_script_hex,
+            script
+        );
+
+        // the spends of a block are recorded by a single update
+        conn.updates_spend_out_point(&[
+            spend(legacy, txid(3), 0, 840002),
+            spend(nonstandard, txid(3), 1, 840002),
+        ])
+        .unwrap();
+
+        let mut spent = conn
+            .load_spent_by_tx(&txid(3))
+            .unwrap()
+            .into_iter()
+            .map(|balance| (balance.out_point, balance.spent_vin, balance.spent_block))
+            .collect::<Vec<(String, Option<u32>, Option<u64>)>>();
+        spent.sort();
+        assert_eq!(
+            spent,
+            [
+                (legacy.to_string(), Some(0), Some(840002)),
+                (nonstandard.to_string(), Some(1), Some(840002)),
+            ]
+        );
+
+        // spent outpoints and outpoints without runes can't be spent, and
+        // leave the spends already recorded as they are
+        for unspendable in [nonstandard, outpoint(txid(4), 0)] {
+            assert!(conn
+                .updates_spend_out_point(&[spend(unspendable, txid(5), 0, 840003)])
+                .is_err());
+        }
+        assert!(conn.load_spent_by_tx(&txid(5)).unwrap().is_empty());
+        assert_eq!(conn.load_spent_by_tx(&txid(3)).unwrap().len(), 2);
+
+        // only spends recorded without their block are cleared by outpoint
+        conn.batch_execute(&format!(
+            "UPDATE rune_balance SET spent_block = NULL WHERE out_point = '{legacy}'"
+        ))
+        .unwrap();
+        conn.unspend_unrecorded_out_points(vec![legacy.to_string(), nonstandard.to_string()])
+            .unwrap();
+        assert!(!conn.load_by_outpoint(&legacy).unwrap()[0].spent);
+        assert!(conn.load_by_outpoint(&nonstandard).unwrap()[0].spent);
+    }
 }
@@ -0,0 +1,66 @@
+use super::*;
+
+impl EtchingDao for MysqlConnection {
+    /// Store a new etching, returning its id.
+    fn store_etching(&mut self, entity: &EtchingEntity) -> Result<u64> {
+        use self::schema::etching::{commit_tx_id, id};
+
+        let insert_rows = diesel::insert_into(EtchingTable)
+            .values(entity)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_etching failed"));
+        }
+
+        let result = EtchingTable
+            .filter(commit_tx_id.eq(&entity.commit_tx_id))
+            .select(id)
+            .first(self);
+
+        match result {
+            Ok(etching_id) => Ok(etching_id),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_etching(&mut self, _id: u64) -> Result<Option<EtchingEntity>> {
+        use self::schema::etching::id;
+        let result = EtchingTable
+            .filter(id.eq(_id))
+            .select(EtchingEntity::as_select())
+            .first(self)
+            .optional();
+
+        match result {
+            Ok(entity) => Ok(entity),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_etchings_in_states(&mut self, states: Vec<u8>) -> Result<Vec<EtchingEntity>> {
+        use self::schema::etching::{id, state};
+        let results = EtchingTable
+            .filter(state.eq_any(states))
+            .order(id.asc())
+            .select(EtchingEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(entities) => Ok(entities),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn update_etching(&mut self, entity: &EtchingEntity) -> Result<()> {
+        let effect_rows = diesel::update(EtchingTable.find(entity.id))
+            .set(entity)
+            .execute(self)?;
+
+        if effect_rows == 0 {
+            return Err(anyhow!("update etching {} failed", entity.id));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+/// The indexer state table holds a single row.
+const INDEXER_STATE_ID: u32 = 1;
+
+impl IndexerStateDao for MysqlConnection {
+    fn load_indexer_state(&mut self) -> Result<Option<IndexerStateEntity>> {
+        use self::schema::indexer_state::id;
+        let result = IndexerStateTable
+            .filter(id.eq(INDEXER_STATE_ID))
+            .select(IndexerStateEntity::as_select())
+            .first(self)
+            .optional();
+
+        match result {
+            Ok(entity) => Ok(entity),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn store_indexer_state(
+        &mut self,
+        network: Network,
+        height: u64,
+        hash: &BlockHash,
+    ) -> Result<()> {
+        let entity = IndexerStateEntity {
+            id: INDEXER_STATE_ID,
+            network: network.to_string(),
+            height,
+            block_hash: hash.to_string(),
+        };
+        let insert_rows = diesel::replace_into(IndexerStateTable)
+            .values(&entity)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_indexer_state failed"));
+        }
+
+        Ok(())
+    }
+
+    fn delete_indexer_state(&mut self) -> Result<()> {
+        diesel::delete(IndexerStateTable).execute(self)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,713 @@
+use {super::*, tempfile::TempDir};
+
+/// Rune store kept in memory, with the same semantics as the MySQL store, for
+/// exercising indexing without a database.
+#[derive(Clone, Default)]
+pub(crate) struct MemoryStore {
+    next_id: u64,
+    pub(crate) entries: Vec<RuneEntryEntity>,
+    pub(crate) events: Vec<RuneEventEntity>,
+    pub(crate) txs: Vec<RuneTxEntity>,
+    pub(crate) balances: Vec<RuneBalanceEntity>,
+    pub(crate) address_balances: Vec<RuneAddressBalanceEntity>,
+    pub(crate) blocks: BTreeMap<u64, BlockHash>,
+    pub(crate) state: Option<IndexerStateEntity>,
+    pub(crate) etchings: Vec<EtchingEntity>,
+    pub(crate) runestones: Vec<RunestoneEntity>,
+    pub(crate) edicts: Vec<RunestoneEdictEntity>,
+}
+
+impl MemoryStore {
+    fn id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn entry_mut(&mut self, id: &RuneId) -> Option<&mut RuneEntryEntity> {
+        let id = id.to_string();
+        self.entries.iter_mut().find(|entity| entity.rune_id == id)
+    }
+}
+
+/// Run `f` against a new store of each backend that needs no database server,
+/// so they are held to the same behavior.
+pub(crate) fn for_each_store(f: impl Fn(&mut dyn RuneStore)) {
+    f(&mut MemoryStore::default());
+
+    let dir = TempDir::new().unwrap();
+    let mut store = RedbStore::open(&dir.path().join("index.redb")).unwrap();
+    store.migrate().unwrap();
+    f(&mut store);
+}
+
+/// Output `vout` of `txid`.
+pub(crate) fn outpoint(txid: Txid, vout: u32) -> OutPoint {
+    OutPoint { txid, vout }
+}
+
+/// Unspent balance of `amount` of `rune_id` at `outpoint`, created by block 1
+/// and held by `bc1qfoo`.
+pub(crate) fn balance(outpoint: OutPoint, rune_id: &str, amount: u128) -> RuneBalanceEntity {
+    RuneBalanceEntity {
+        id: 0,
+        block: 1,
+        rune_id: rune_id.into(),
+        amount: BigDecimal::from(amount),
+        address: "bc1qfoo".into(),
+        pk_script_hex: String::new(),
+        script_type: String::new(),
+        out_point: outpoint.to_string(),
+        spent: false,
+        spent_txid: None,
+        spent_vin: None,
+        spent_block: None,
+    }
+}
+
+/// Spend of `outpoint` by input `vin` of `txid`, in `block`.
+pub(crate) fn spend(outpoint: OutPoint, txid: Txid, vin: u32, block: u64) -> Spend {
+    Spend {
+        outpoint,
+        txid,
+        vin,
+        block,
+    }
+}
+
+fn page<T>(items: impl Iterator<Item = T>, offset: i64, limit: i64) -> Result<Vec<T>> {
+    Ok(items
+        .skip(offset.try_into()?)
+        .take(limit.try_into()?)
+        .collect())
+}
+
+impl RuneStore for MemoryStore {
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn RuneStore) -> Result) -> Result {
+        let snapshot = self.clone();
+
+        let result = f(self);
+
+        if result.is_err() {
+            *self = snapshot;
+        }
+
+        result
+    }
+
+    fn migrate(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+impl RuneEntryDao for MemoryStore {
+    fn gets_rune_entry(&mut self, ids: Vec<String>) -> Result<Vec<RuneEntryEntity>> {
+        Ok(self
+            .entries
+            .iter()
+            .filter(|entity| ids.contains(&entity.rune_id))
+            .cloned()
+            .collect())
+    }
+
+    fn gets_rune_entry_by_runes(&mut self, runes: Vec<Rune>) -> Result<Vec<RuneEntryEntity>> {
+        let runes = runes
+            .iter()
+            .map(|rune| BigDecimal::from(rune.n()))
+            .collect::<Vec<BigDecimal>>();
+
+        Ok(self
+            .entries
+            .iter()
+            .filter(|entity| runes.contains(&entity.rune))
+            .cloned()
+            .collect())
+    }
+
+    fn load_entry_by_rune(&mut self, rune: &Rune) -> Result<Option<RuneEntry>> {
+        self.gets_rune_entry_by_runes(vec![*rune])?
+            .first()
+            .map(convert_model_to_rune_entry)
+            .transpose()
+    }
+
+    fn store_rune_entry(&mut self, id: &RuneId, entry: &RuneEntry) -> Result<()> {
+        let mut entity = runes_entry::convert_rune_entry_to_model(id, entry);
+        entity.id = self.id();
+        self.entries.push(entity);
+
+        Ok(())
+    }
+
+    fn update_rune_mints(&mut self, id: &RuneId, mints: u128) -> Result<()> {
+        let entity = self
+            .entry_mut(id)
+            .ok_or_else(|| anyhow!("update_rune_mints failed"))?;
+        entity.mints = BigDecimal::from(mints);
+
+        Ok(())
+    }
+
+    fn update_rune_burned(&mut self, id: &RuneId, burned: u128) -> Result<()> {
+        let entity = self
+            .entry_mut(id)
+            .ok_or_else(|| anyhow!("update_rune_burned failed"))?;
+        entity.burned = BigDecimal::from(burned);
+
+        Ok(())
+    }
+
+    fn delete_rune_entries_after(&mut self, height: u64) -> Result<()> {
+        self.entries.retain(|entity| entity.block <= height);
+
+        Ok(())
+    }
+
+    fn gets_rune_number(&mut self) -> Option<u64> {
+        self.entries.iter().map(|entity| entity.number).max()
+    }
+
+    fn load_rune_entries(&mut self, offset: i64, limit: i64) -> Result<Vec<RuneEntryEntity>> {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|entity| entity.number);
+
+        page(entries.into_iter(), offset, limit)
+    }
+}
+
+impl RuneEventDao for MemoryStore {
+    fn store_events(&mut self, entities: &[RuneEventEntity]) -> Result<()> {
+        for entity in entities {
+            let id = self.id();
+            self.events.push(RuneEventEntity {
+                id,
+                ..entity.clone()
+            });
+        }
+
+        Ok(())
+    }
+
+    fn load_events_after(&mut self, height: u64) -> Result<Vec<RuneEventEntity>> {
+        Ok(self
+            .events
+            .iter()
+            .filter(|event| event.block > height)
+            .cloned()
+            .collect())
+    }
+
+    fn delete_events_after(&mut self, height: u64) -> Result<()> {
+        self.events.retain(|event| event.block <= height);
+
+        Ok(())
+    }
+
+    fn load_events_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneEventEntity>> {
+        let txid = txid.to_string();
+
+        Ok(self
+            .events
+            .iter()
+            .filter(|event| event.tx_id == txid)
+            .cloned()
+            .collect())
+    }
+
+    fn load_events_by_rune(
+        &mut self,
+        id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        let id = id.to_string();
+
+        page(
+            self.events
+                .iter()
+                .rev()
+                .filter(|event| event.rune_id == id)
+                .cloned(),
+            offset,
+            limit,
+        )
+    }
+
+    fn load_events_by_address(
+        &mut self,
+        address: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        page(
+            self.events
+                .iter()
+                .rev()
+                .filter(|event| event.address == address)
+                .cloned(),
+            offset,
+            limit,
+        )
+    }
+}
+
+impl RuneTxDao for MemoryStore {
+    fn store_txs(&mut self, entities: &[RuneTxEntity]) -> Result<()> {
+        for entity in entities {
+            let id = self.id();
+            self.txs.push(RuneTxEntity {
+                id,
+                ..entity.clone()
+            });
+        }
+
+        Ok(())
+    }
+
+    fn load_txs(&mut self, txids: Vec<String>) -> Result<Vec<RuneTxEntity>> {
+        Ok(self
+            .txs
+            .iter()
+            .filter(|tx| txids.contains(&tx.tx_id))
+            .cloned()
+            .collect())
+    }
+
+    fn delete_txs_after(&mut self, height: u64) -> Result<()> {
+        self.txs.retain(|tx| tx.block <= height);
+
+        Ok(())
+    }
+}
+
+impl RuneBlanaceDao for MemoryStore {
+    fn load_by_outpoints(&mut self, outpoints: Vec<String>) -> Result<Vec<RuneBalanceEntity>> {
+        Ok(self
+            .balances
+            .iter()
+            .filter(|balance| outpoints.contains(&balance.out_point))
+            .cloned()
+            .collect())
+    }
+
+    fn load_by_outpoint(&mut self, outpoint: &OutPoint) -> Result<Vec<RuneBalanceEntity>> {
+        self.load_by_outpoints(vec![outpoint.to_string()])
+    }
+
+    // like MySQL, only rows that actually change count as updated
+    fn updates_spend_out_point(&mut self, spends: &[Spend]) -> Result {
+        ensure_unspent(
+            spends,
+            self.balances
+                .iter()
+                .filter(|balance| !balance.spent)
+                .map(|balance| balance.out_point.clone())
+                .collect(),
+        )?;
+
+        let spends = spends
+            .iter()
+            .map(|spend| (spend.outpoint.to_string(), spend))
+            .collect::<HashMap<String, &Spend>>();
+
+        for balance in &mut self.balances {
+            let Some(spend) = spends.get(&balance.out_point) else {
+                continue;
+            };
+
+            if !balance.spent {
+                balance.spent = true;
+                balance.spent_txid = Some(spend.txid.to_string());
+                balance.spent_vin = Some(spend.vin);
+                balance.spent_block = Some(spend.block);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn store_balances(&mut self, entities: &[RuneBalanceEntity]) -> Result<()> {
+        for entity in entities {
+            let id = self.id();
+            self.balances.push(RuneBalanceEntity {
+                id,
+                ..entity.clone()
+            });
+        }
+
+        Ok(())
+    }
+
+    fn unspend_out_points_after(&mut self, height: u64) -> Result<()> {
+        for balance in &mut self.balances {
+            if balance.spent_block.is_some_and(|block| block > height) {
+                balance.spent = false;
+                balance.spent_txid = None;
+                balance.spent_vin = None;
+                balance.spent_block = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn unspend_unrecorded_out_points(&mut self, outpoints: Vec<String>) -> Result<()> {
+        for balance in &mut self.balances {
+            if balance.spent_block.is_none() && outpoints.contains(&balance.out_point) {
+                balance.spent = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn has_unrecorded_spends(&mut self) -> Result<bool> {
+        Ok(self
+            .balances
+            .iter()
+            .any(|balance| balance.spent && balance.spent_block.is_none()))
+    }
+
+    fn delete_balances_after(&mut self, height: u64) -> Result<()> {
+        self.balances.retain(|balance| balance.block <= height);
+
+        Ok(())
+    }
+
+    fn load_addresses_after(&mut self, height: u64) -> Result<Vec<String>> {
+        Ok(self
+            .balances
+            .iter()
+            .filter(|balance| {
+                balance.block > height || balance.spent_block.is_some_and(|block| block > height)
+            })
+            .map(|balance| balance.address.clone())
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect())
+    }
+
+    fn load_unspent_by_address(&mut self, address: &str) -> Result<Vec<RuneBalanceEntity>> {
+        Ok(self
+            .balances
+            .iter()
+            .filter(|balance| balance.address == address && !balance.spent)
+            .cloned()
+            .collect())
+    }
+
+    fn load_spent_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneBalanceEntity>> {
+        let txid = txid.to_string();
+        let mut balances = self
+            .balances
+            .iter()
+            .filter(|balance| balance.spent_txid.as_ref() == Some(&txid))
+            .cloned()
+            .collect::<Vec<RuneBalanceEntity>>();
+        balances.sort_by_key(|balance| balance.spent_vin);
+        Ok(balances)
+    }
+}
+
+impl RuneAddressBalanceDao for MemoryStore {
+    fn load_address_balances(
+        &mut self,
+        addresses: Vec<String>,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        Ok(self
+            .address_balances
+            .iter()
+            .filter(|balance| addresses.contains(&balance.address))
+            .cloned()
+            .collect())
+    }
+
+    fn load_balances_by_address(&mut self, address: &str) -> Result<Vec<RuneAddressBalanceEntity>> {
+        let mut balances = self.load_address_balances(vec![address.into()])?;
+        balances.sort_by(|a, b| a.rune_id.cmp(&b.rune_id));
+
+        Ok(balances)
+    }
+
+    fn load_top_holders(
+        &mut self,
+        id: &RuneId,
+        limit: i64,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        let id = id.to_string();
+        let mut holders = self
+            .address_balances
+            .iter()
+            .filter(|balance| balance.rune_id == id)
+            .cloned()
+            .collect::<Vec<RuneAddressBalanceEntity>>();
+        holders.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+        page(holders.into_iter(), 0, limit)
+    }
+
+    fn count_holders(&mut self, id: &RuneId) -> Result<u64> {
+        let id = id.to_string();
+
+        Ok(self
+            .address_balances
+            .iter()
+            .filter(|balance| balance.rune_id == id)
+            .count()
+            .try_into()?)
+    }
+
+    // replaces rows with the same id or the same address and rune
+    fn store_address_balances(&mut self, entities: &[RuneAddressBalanceEntity]) -> Result<()> {
+        for entity in entities {
+            self.address_balances.retain(|balance| {
+                balance.id != entity.id
+                    && (balance.address != entity.address || balance.rune_id != entity.rune_id)
+            });
+
+            let id = if entity.id == 0 { self.id() } else { entity.id };
+
+            self.address_balances.push(RuneAddressBalanceEntity {
+                id,
+                ..entity.clone()
+            });
+        }
+
+        Ok(())
+    }
+
+    fn delete_address_balances(&mut self, ids: Vec<u64>) -> Result<()> {
+        self.address_balances
+            .retain(|balance| !ids.contains(&balance.id));
+
+        Ok(())
+    }
+
+    fn rebuild_address_balances(&mut self, addresses: Vec<String>) -> Result<()> {
+        let mut rebuilt: BTreeMap<(String, String), (BigDecimal, u64)> = BTreeMap::new();
+        for balance in &self.balances {
+            if !balance.spent && addresses.contains(&balance.address) {
+                let (amount, utxo_count) = rebuilt
+                    .entry((balance.address.clone(), balance.rune_id.clone()))
+                    .or_default();
+                *amount += &balance.amount;
+                *utxo_count += 1;
+            }
+        }
+
+        self.address_balances
+            .retain(|balance| !addresses.contains(&balance.address));
+
+        for ((address, rune_id), (amount, utxo_count)) in rebuilt {
+            let id = self.id();
+            self.address_balances.push(RuneAddressBalanceEntity {
+                id,
+                address,
+                rune_id,
+                amount,
+                utxo_count,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl RuneBlockDao for MemoryStore {
+    fn load_block_hash(&mut self, height: u64) -> Result<Option<BlockHash>> {
+        Ok(self.blocks.get(&height).copied())
+    }
+
+    fn store_block_header(&mut self, height: u64, header: &Header) -> Result<()> {
+        self.blocks.insert(height, header.block_hash());
+
+        Ok(())
+    }
+
+    fn delete_blocks_after(&mut self, height: u64) -> Result<()> {
+        self.blocks.retain(|block, _| *block <= height);
+
+        Ok(())
+    }
+}
+
+impl IndexerStateDao for MemoryStore {
+    fn load_indexer_state(&mut self) -> Result<Option<IndexerStateEntity>> {
+        Ok(self.state.clone())
+    }
+
+    fn store_indexer_state(
+        &mut self,
+        network: Network,
+        height: u64,
+        hash: &BlockHash,
+    ) -> Result<()> {
+        self.state = Some(IndexerStateEntity {
+            id: 1,
+            network: network.to_string(),
+            height,
+            block_hash: hash.to_string(),
+        });
+
+        Ok(())
+    }
+
+    fn delete_indexer_state(&mut self) -> Result<()> {
+        self.state = None;
+
+        Ok(())
+    }
+}
+
+impl EtchingDao for MemoryStore {
+    fn store_etching(&mut self, entity: &EtchingEntity) -> Result<u64> {
+        ensure!(
+            self.etchings
+                .iter()
+                .all(|etching| etching.commit_tx_id != entity.commit_tx_id),
+            "store_etching failed"
+        );
+
+        let id = self.id();
+        self.etchings.push(EtchingEntity {
+            id,
+            ..entity.clone()
+        });
+
+        Ok(id)
+    }
+
+    fn load_etching(&mut self, id: u64) -> Result<Option<EtchingEntity>> {
+        Ok(self
+            .etchings
+            .iter()
+            .find(|etching| etching.id == id)
+            .cloned())
+    }
+
+    fn load_etchings_in_states(&mut self, states: Vec<u8>) -> Result<Vec<EtchingEntity>> {
+        Ok(self
+            .etchings
+            .iter()
+            .filter(|etching| states.contains(&etching.state))
+            .cloned()
+            .collect())
+    }
+
+    fn update_etching(&mut self, entity: &EtchingEntity) -> Result<()> {
+        let etching = self
+            .etchings
+            .iter_mut()
+            .find(|etching| etching.id == entity.id)
+            .ok_or_else(|| anyhow!("update etching {} failed", entity.id))?;
+
+        *etching = entity.clone();
+
+        Ok(())
+    }
+}
+
+impl RunestoneDao for MemoryStore {
+    fn store_runestones(&mut self, entities: &[RunestoneEntity]) -> Result<()> {
+        for entity in entities {
+            ensure!(
+                self.runestones
+                    .iter()
+                    .all(|runestone| runestone.tx_id != entity.tx_id),
+                "store_runestones failed"
+            );
+
+            let id = self.id();
+            self.runestones.push(RunestoneEntity {
+                id,
+                ..entity.clone()
+            });
+        }
+
+        Ok(())
+    }
+
+    fn store_edicts(&mut self, entities: &[RunestoneEdictEntity]) -> Result<()> {
+        for entity in entities {
+            let id = self.id();
+            self.edicts.push(RunestoneEdictEntity {
+                id,
+                ..entity.clone()
+            });
+        }
+
+        Ok(())
+    }
+
+    fn load_runestone(
+        &mut self,
+        txid: &Txid,
+    ) -> Result<Option<(RunestoneEntity, Vec<RunestoneEdictEntity>)>> {
+        let txid = txid.to_string();
+
+        let Some(runestone) = self
+            .runestones
+            .iter()
+            .find(|runestone| runestone.tx_id == txid)
+        else {
+            return Ok(None);
+        };
+
+        let mut edicts = self
+            .edicts
+            .iter()
+            .filter(|edict| edict.tx_id == txid)
+            .cloned()
+            .collect::<Vec<RunestoneEdictEntity>>();
+        edicts.sort_by_key(|edict| edict.edict_index);
+
+        Ok(Some((runestone.clone(), edicts)))
+    }
+
+    fn load_edicts_by_rune(
+        &mut self,
+        id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEdictEntity>> {
+        let id = id.to_string();
+
+        page(
+            self.edicts
+                .iter()
+                .rev()
+                .filter(|edict| edict.rune_id == id)
+                .cloned(),
+            offset,
+            limit,
+        )
+    }
+
+    fn load_cenotaphs(
+        &mut self,
+        flaw: Option<Flaw>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEntity>> {
+        let flaw = flaw.map(runestone::flaw_name).transpose()?;
+
+        page(
+            self.runestones
+                .iter()
+                .rev()
+                .filter(|runestone| {
+                    runestone.cenotaph && (flaw.is_none() || runestone.flaw == flaw)
+                })
+                .cloned(),
+            offset,
+            limit,
+        )
+    }
+
+    fn delete_runestones_after(&mut self, height: u64) -> Result<()> {
+        self.runestones
+            .retain(|runestone| runestone.block <= height);
+        self.edicts.retain(|edict| edict.block <= height);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use {
+    self::{
+        model::{
+            EtchingRow, IndexerStateRow, RuneAddressBalanceRow, RuneBalanceRow, RuneBlockRow,
+            RuneEntryRow, RuneEventRow, RuneTxRow, RunestoneEdictRow, RunestoneRow,
+        },
+        schema::{
+            etching::dsl::etching as EtchingTable,
+            indexer_state::dsl::indexer_state as IndexerStateTable,
+            rune_address_balance::dsl::rune_address_balance as RuneAddressBalanceTable,
+            rune_balance::dsl::rune_balance as RuneBalanceTable,
+            rune_block::dsl::rune_block as RuneBlockTable,
+            rune_entry::dsl::rune_entry as RuneEntryTable,
+            rune_event::dsl::rune_event as RuneEventTable, rune_tx::dsl::rune_tx as RuneTxTable,
+            runestone::dsl::runestone as RunestoneTable,
+            runestone_edict::dsl::runestone_edict as RunestoneEdictTable,
+        },
+    },
+    super::*,
+    diesel::{
+        pg::{upsert::excluded, Pg},
+        sql_types::{BigInt, Text},
+        PgConnection,
+    },
+};
+
+mod etching;
+mod indexer_state;
+mod model;
+mod runes_address_balance;
+mod runes_balance;
+mod runes_block;
+mod runes_entry;
+mod runes_event;
+mod runes_tx;
+mod runestone;
+mod schema;
+
+/// The indexer state table holds a single row.
+const INDEXER_STATE_ID: i32 = 1;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/postgres");
+
+impl RuneStore for PgConnection {
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn RuneStore) -> Result) -> Result {
+        self.transaction(|conn| f(conn))
+    }
+
+    fn migrate(&mut self) -> Result {
+        self.run_pending_migrations(MIGRATIONS)
+            .map_err(|err| anyhow!("failed to migrate database: {err}"))?;
+
+        Ok(())
+    }
+}
+
+fn entities<R, E>(rows: Vec<R>) -> Result<Vec<E>>
+where
+    E: TryFrom<R, Error = Error>,
+{
+    rows.into_iter().map(E::try_from).collect()
+}
+
+fn rows<'a, E: 'a, R>(entities: impl IntoIterator<Item = &'a E>) -> Result<Vec<R>>
+where
+    R: TryFrom<&'a E, Error = Error>,
+{
+    entities.into_iter().map(R::try_from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        diesel::{connection::SimpleConnection, migration::MigrationSource, mysql::Mysql, pg::Pg},
+        ordinals::inscription_id::txid,
+    };
+
+    #[test]
+    fn migrations_match_mysql() {
+        let postgres = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+            .unwrap()
+            .iter()
+            .map(|migration| migration.name().to_string())
+            .collect::<Vec<String>>();
+
+        let mysql = MigrationSource::<Mysql>::migrations(&super::super::MIGRATIONS)
+            .unwrap()
+            .iter()
+            .map(|migration| migration.name().to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(postgres.len(), 11);
+        assert_eq!(postgres, mysql);
+    }
+
+    #[test]
+    fn balances_record_their_spends() {
+        // drops every table of the database, so only runs when given one
+        let Ok(url) = env::var("RUNES_TEST_POSTGRES_URL") else {
+            return;
+        };
+
+        let mut conn = PgConnection::establish(&url).unwrap();
+
+        conn.batch_execute("DROP SCHEMA public CASCADE; CREATE SCHEMA public;")
+            .unwrap();
+
+        conn.migrate().unwrap();
+
+        let first = outpoint(txid(1), 0);
+        conn.store_balances(&[RuneBalanceEntity {
+            block: 840000,
+            ..balance(first, "840000:1", 100)
+        }])
+        .unwrap();
+
+        // scripts of outputs without an address can be over 128 bytes
+        let script = "51".repeat(200);
+
+        conn.store_events(&[RuneEventEntity {
+            block: 840001,
+            event_type: 6,
+            tx_id: txid(2).to_string(),
+            rune_id: "840000:1".into(),
+            pk_script_hex: script.clone(),
+            reason: "mint cap reached".into(),
+            ..default()
+        }])
+        .unwrap();
+
+        let events = conn.load_events_by_tx(&txid(2)).unwrap();
+        assert_eq!(events[0].reason, "mint cap reached");
+        assert_eq!(events[0].pk_script_hex, script);
+
+        let nonstandard = outpoint(txid(2), 0);
+        conn.store_balances(&[RuneBalanceEntity {
+            block: 840001,
+            address: String::new(),
+            pk_script_hex: script.clone(),
+            script_type: "nonstandard".into(),
+            ..balance(nonstandard, "840000:1", 100)
+        }])
+        .unwrap();
+        assert_eq!(
+            conn.load_by_outpoint(&nonstandard).unwrap()[0].pk_script_hex,
+            script
+        );
+
+        // the spends of a block are recorded by a single update
+        conn.updates_spend_out_point(&[
+            spend(first, txid(3), 0, 840002),
+            spend(nonstandard, txid(3), 1, 840002),
+        ])
+        .unwrap();
+
+        let mut spent = conn
+            .load_spent_by_tx(&txid(3))
+            .unwrap()
+            .into_iter()
+            .map(|balance| (balance.out_point, balance.spent_vin, balance.spent_block))
+            .collect::<Vec<(String, Option<u32>, Option<u64>)>>();
+        spent.sort();
+        assert_eq!(
+            spent,
+            [
+                (first.to_string(), Some(0), Some(840002)),
+                (nonstandard.to_string(), Some(1), Some(840002)),
+            ]
+        );
+
+        // spent outpoints and outpoints without runes can't be spent, and
+        // leave the spends already recorded as they are
+        for unspendable in [nonstandard, outpoint(txid(4), 0)] {
+            assert!(conn
+                .updates_spend_out_point(&[spend(unspendable, txid(5), 0, 840003)])
+                .is_err());
+        }
+        assert!(conn.load_spent_by_tx(&txid(5)).unwrap().is_empty());
+        assert_eq!(conn.load_spent_by_tx(&txid(3)).unwrap().len(), 2);
+
+        // only spends recorded without their block are cleared by outpoint
+        conn.batch_execute(&format!(
+            "UPDATE rune_balance SET spent_block = NULL WHERE out_point = '{first}'"
+        ))
+        .unwrap();
+        conn.unspend_unrecorded_out_points(vec![first.to_string(), nonstandard.to_string()])
+            .unwrap();
+        assert!(!conn.load_by_outpoint(&first).unwrap()[0].spent);
+        assert!(conn.load_by_outpoint(&nonstandard).unwrap()[0].spent);
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+
+impl EtchingDao for PgConnection {
+    /// Store a new etching, returning its id.
+    fn store_etching(&mut self, entity: &EtchingEntity) -> Result<u64> {
+        use self::schema::etching::id;
+
+        let etching_id = diesel::insert_into(EtchingTable)
+            .values(&EtchingRow::try_from(entity)?)
+            .returning(id)
+            .get_result::<i64>(self)?;
+
+        Ok(etching_id.try_into()?)
+    }
+
+    fn load_etching(&mut self, _id: u64) -> Result<Option<EtchingEntity>> {
+        use self::schema::etching::id;
+        let result = EtchingTable
+            .filter(id.eq(i64::try_from(_id)?))
+            .select(EtchingRow::as_select())
+            .first(self)
+            .optional()?;
+
+        result.map(EtchingEntity::try_from).transpose()
+    }
+
+    fn load_etchings_in_states(&mut self, states: Vec<u8>) -> Result<Vec<EtchingEntity>> {
+        use self::schema::etching::{id, state};
+
+        let states = states.into_iter().map(i16::from).collect::<Vec<i16>>();
+        let results = EtchingTable
+            .filter(state.eq_any(states))
+            .order(id.asc())
+            .select(EtchingRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn update_etching(&mut self, entity: &EtchingEntity) -> Result<()> {
+        use self::schema::etching::update_at;
+
+        let row = EtchingRow::try_from(entity)?;
+        let effect_rows = diesel::update(EtchingTable.find(row.id))
+            .set((&row, update_at.eq(diesel::dsl::now)))
+            .execute(self)?;
+
+        if effect_rows == 0 {
+            return Err(anyhow!("update etching {} failed", entity.id));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use super::*;
+
+impl IndexerStateDao for PgConnection {
+    fn load_indexer_state(&mut self) -> Result<Option<IndexerStateEntity>> {
+        use self::schema::indexer_state::id;
+        let result = IndexerStateTable
+            .filter(id.eq(INDEXER_STATE_ID))
+            .select(IndexerStateRow::as_select())
+            .first(self)
+            .optional()?;
+
+        result.map(IndexerStateEntity::try_from).transpose()
+    }
+
+    fn store_indexer_state(
+        &mut self,
+        network: Network,
+        height: u64,
+        hash: &BlockHash,
+    ) -> Result<()> {
+        use self::schema::indexer_state::{
+            block_hash, height as height_column, id, network as network_column,
+        };
+
+        let row = IndexerStateRow {
+            id: INDEXER_STATE_ID,
+            network: network.to_string(),
+            height: height.try_into()?,
+            block_hash: hash.to_string(),
+        };
+        let insert_rows = diesel::insert_into(IndexerStateTable)
+            .values(&row)
+            .on_conflict(id)
+            .do_update()
+            .set((
+                network_column.eq(excluded(network_column)),
+                height_column.eq(excluded(height_column)),
+                block_hash.eq(excluded(block_hash)),
+            ))
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_indexer_state failed"));
+        }
+
+        Ok(())
+    }
+
+    fn delete_indexer_state(&mut self) -> Result<()> {
+        diesel::delete(IndexerStateTable).execute(self)?;
+
+        Ok(())
+    }
+}
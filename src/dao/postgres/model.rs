@@ -0,0 +1,567 @@
+//! Rows of the PostgreSQL tables. PostgreSQL has no unsigned integers, so
+//! heights, counts and ids are stored as `BIGINT` and checked when converting
+//! to and from the entities shared by every store.
+
+use {
+    super::*,
+    crate::model::{
+        EtchingEntity, IndexerStateEntity, RuneAddressBalanceEntity, RuneBalanceEntity,
+        RuneBlockEntity, RuneEntryEntity, RuneEventEntity, RuneTxEntity, RunestoneEdictEntity,
+        RunestoneEntity,
+    },
+};
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::rune_entry)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RuneEntryRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub block: i64,
+    pub burned: BigDecimal,
+    pub divisibility: i16,
+    pub etching: String,
+    pub mints: BigDecimal,
+    pub number: i64,
+    pub premine: BigDecimal,
+    pub rune: BigDecimal,
+    pub spaced_rune: String,
+    pub rune_id: String,
+    pub timestamp: i64,
+    pub symbol: String,
+    pub turbo: bool,
+    pub amount: Option<BigDecimal>,
+    pub cap: Option<BigDecimal>,
+    pub height_start: Option<i64>,
+    pub height_end: Option<i64>,
+    pub offset_start: Option<i64>,
+    pub offset_end: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::rune_event)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RuneEventRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub block: i64,
+    pub event_type: i16,
+    pub tx_id: String,
+    pub rune_id: String,
+    pub amount: Option<BigDecimal>,
+    pub address: String,
+    pub pk_script_hex: String,
+    pub script_type: String,
+    pub vout: i64,
+    pub timestamp: i64,
+    pub reason: String,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::rune_tx)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RuneTxRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub block: i64,
+    pub tx_id: String,
+    pub input_value: Option<i64>,
+    pub fee: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::runestone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RunestoneRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub block: i64,
+    pub tx_id: String,
+    pub cenotaph: bool,
+    pub flaw: Option<String>,
+    pub pointer: Option<i64>,
+    pub mint: Option<String>,
+    pub etching: bool,
+    pub rune: Option<BigDecimal>,
+    pub spacers: Option<i64>,
+    pub divisibility: Option<i16>,
+    pub premine: Option<BigDecimal>,
+    pub symbol: Option<String>,
+    pub turbo: bool,
+    pub terms: bool,
+    pub amount: Option<BigDecimal>,
+    pub cap: Option<BigDecimal>,
+    pub height_start: Option<i64>,
+    pub height_end: Option<i64>,
+    pub offset_start: Option<i64>,
+    pub offset_end: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::runestone_edict)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RunestoneEdictRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub block: i64,
+    pub tx_id: String,
+    pub edict_index: i64,
+    pub rune_id: String,
+    pub amount: BigDecimal,
+    pub output: i64,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::rune_balance)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RuneBalanceRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub block: i64,
+    pub rune_id: String,
+    pub amount: BigDecimal,
+    pub address: String,
+    pub pk_script_hex: String,
+    pub script_type: String,
+    pub out_point: String,
+    pub spent: bool,
+    pub spent_txid: Option<String>,
+    pub spent_vin: Option<i64>,
+    pub spent_block: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::rune_address_balance)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RuneAddressBalanceRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub address: String,
+    pub rune_id: String,
+    pub amount: BigDecimal,
+    pub utxo_count: i64,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::rune_block)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct RuneBlockRow {
+    pub height: i64,
+    pub block_hash: String,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = schema::indexer_state)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct IndexerStateRow {
+    pub id: i32,
+    pub network: String,
+    pub height: i64,
+    pub block_hash: String,
+}
+
+#[derive(Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = schema::etching)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub(super) struct EtchingRow {
+    #[diesel(skip_insertion)]
+    pub id: i64,
+    pub rune: BigDecimal,
+    pub state: i16,
+    pub destination: String,
+    pub commit_key: String,
+    pub runestone: String,
+    pub fee_rate: i64,
+    pub postage: i64,
+    pub commit_tx_id: String,
+    pub commit_tx: String,
+    pub reveal_tx_id: String,
+    pub reveal_tx: String,
+    pub inscription_output: String,
+}
+
+// NUMERIC(39,0) columns hold any u128, but a value written by something else
+// may still be out of range, so reads check them like every other column
+fn numeric(value: &BigDecimal) -> Result<BigDecimal> {
+    decimal_to_u128(value)?;
+    Ok(value.clone())
+}
+
+fn optional<T, U>(value: Option<T>) -> Result<Option<U>>
+where
+    U: TryFrom<T>,
+    <U as TryFrom<T>>::Error: std::error::Error + Send + Sync + 'static,
+{
+    Ok(value.map(U::try_from).transpose()?)
+}
+
+impl TryFrom<&RuneEntryEntity> for RuneEntryRow {
+    type Error = Error;
+
+    fn try_from(entity: &RuneEntryEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            block: entity.block.try_into()?,
+            burned: entity.burned.clone(),
+            divisibility: entity.divisibility.into(),
+            etching: entity.etching.clone(),
+            mints: entity.mints.clone(),
+            number: entity.number.try_into()?,
+            premine: entity.premine.clone(),
+            rune: entity.rune.clone(),
+            spaced_rune: entity.spaced_rune.clone(),
+            rune_id: entity.rune_id.clone(),
+            timestamp: entity.timestamp.try_into()?,
+            symbol: entity.symbol.clone(),
+            turbo: entity.turbo,
+            amount: entity.amount.clone(),
+            cap: entity.cap.clone(),
+            height_start: optional(entity.height_start)?,
+            height_end: optional(entity.height_end)?,
+            offset_start: optional(entity.offset_start)?,
+            offset_end: optional(entity.offset_end)?,
+        })
+    }
+}
+
+impl TryFrom<RuneEntryRow> for RuneEntryEntity {
+    type Error = Error;
+
+    fn try_from(row: RuneEntryRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            block: row.block.try_into()?,
+            burned: numeric(&row.burned)?,
+            divisibility: row.divisibility.try_into()?,
+            etching: row.etching,
+            mints: numeric(&row.mints)?,
+            number: row.number.try_into()?,
+            premine: numeric(&row.premine)?,
+            rune: numeric(&row.rune)?,
+            spaced_rune: row.spaced_rune,
+            rune_id: row.rune_id,
+            timestamp: row.timestamp.try_into()?,
+            symbol: row.symbol,
+            turbo: row.turbo,
+            amount: row.amount.as_ref().map(numeric).transpose()?,
+            cap: row.cap.as_ref().map(numeric).transpose()?,
+            height_start: optional(row.height_start)?,
+            height_end: optional(row.height_end)?,
+            offset_start: optional(row.offset_start)?,
+            offset_end: optional(row.offset_end)?,
+        })
+    }
+}
+
+impl TryFrom<&RuneEventEntity> for RuneEventRow {
+    type Error = Error;
+
+    fn try_from(entity: &RuneEventEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            block: entity.block.try_into()?,
+            event_type: entity.event_type.into(),
+            tx_id: entity.tx_id.clone(),
+            rune_id: entity.rune_id.clone(),
+            amount: entity.amount.clone(),
+            address: entity.address.clone(),
+            pk_script_hex: entity.pk_script_hex.clone(),
+            script_type: entity.script_type.clone(),
+            vout: entity.vout.into(),
+            timestamp: entity.timestamp.try_into()?,
+            reason: entity.reason.clone(),
+        })
+    }
+}
+
+impl TryFrom<RuneEventRow> for RuneEventEntity {
+    type Error = Error;
+
+    fn try_from(row: RuneEventRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            block: row.block.try_into()?,
+            event_type: row.event_type.try_into()?,
+            tx_id: row.tx_id,
+            rune_id: row.rune_id,
+            amount: row.amount.as_ref().map(numeric).transpose()?,
+            address: row.address,
+            pk_script_hex: row.pk_script_hex,
+            script_type: row.script_type,
+            vout: row.vout.try_into()?,
+            timestamp: row.timestamp.try_into()?,
+            reason: row.reason,
+        })
+    }
+}
+
+impl TryFrom<&RuneTxEntity> for RuneTxRow {
+    type Error = Error;
+
+    fn try_from(entity: &RuneTxEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            block: entity.block.try_into()?,
+            tx_id: entity.tx_id.clone(),
+            input_value: optional(entity.input_value)?,
+            fee: optional(entity.fee)?,
+        })
+    }
+}
+
+impl TryFrom<RuneTxRow> for RuneTxEntity {
+    type Error = Error;
+
+    fn try_from(row: RuneTxRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            block: row.block.try_into()?,
+            tx_id: row.tx_id,
+            input_value: optional(row.input_value)?,
+            fee: optional(row.fee)?,
+        })
+    }
+}
+
+impl TryFrom<&RunestoneEntity> for RunestoneRow {
+    type Error = Error;
+
+    fn try_from(entity: &RunestoneEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            block: entity.block.try_into()?,
+            tx_id: entity.tx_id.clone(),
+            cenotaph: entity.cenotaph,
+            flaw: entity.flaw.clone(),
+            pointer: entity.pointer.map(i64::from),
+            mint: entity.mint.clone(),
+            etching: entity.etching,
+            rune: entity.rune.clone(),
+            spacers: entity.spacers.map(i64::from),
+            divisibility: entity.divisibility.map(i16::from),
+            premine: entity.premine.clone(),
+            symbol: entity.symbol.clone(),
+            turbo: entity.turbo,
+            terms: entity.terms,
+            amount: entity.amount.clone(),
+            cap: entity.cap.clone(),
+            height_start: optional(entity.height_start)?,
+            height_end: optional(entity.height_end)?,
+            offset_start: optional(entity.offset_start)?,
+            offset_end: optional(entity.offset_end)?,
+        })
+    }
+}
+
+impl TryFrom<RunestoneRow> for RunestoneEntity {
+    type Error = Error;
+
+    fn try_from(row: RunestoneRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            block: row.block.try_into()?,
+            tx_id: row.tx_id,
+            cenotaph: row.cenotaph,
+            flaw: row.flaw,
+            pointer: optional(row.pointer)?,
+            mint: row.mint,
+            etching: row.etching,
+            rune: row.rune.as_ref().map(numeric).transpose()?,
+            spacers: optional(row.spacers)?,
+            divisibility: optional(row.divisibility)?,
+            premine: row.premine.as_ref().map(numeric).transpose()?,
+            symbol: row.symbol,
+            turbo: row.turbo,
+            terms: row.terms,
+            amount: row.amount.as_ref().map(numeric).transpose()?,
+            cap: row.cap.as_ref().map(numeric).transpose()?,
+            height_start: optional(row.height_start)?,
+            height_end: optional(row.height_end)?,
+            offset_start: optional(row.offset_start)?,
+            offset_end: optional(row.offset_end)?,
+        })
+    }
+}
+
+impl TryFrom<&RunestoneEdictEntity> for RunestoneEdictRow {
+    type Error = Error;
+
+    fn try_from(entity: &RunestoneEdictEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            block: entity.block.try_into()?,
+            tx_id: entity.tx_id.clone(),
+            edict_index: entity.edict_index.into(),
+            rune_id: entity.rune_id.clone(),
+            amount: entity.amount.clone(),
+            output: entity.output.into(),
+        })
+    }
+}
+
+impl TryFrom<RunestoneEdictRow> for RunestoneEdictEntity {
+    type Error = Error;
+
+    fn try_from(row: RunestoneEdictRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            block: row.block.try_into()?,
+            tx_id: row.tx_id,
+            edict_index: row.edict_index.try_into()?,
+            rune_id: row.rune_id,
+            amount: numeric(&row.amount)?,
+            output: row.output.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<&RuneBalanceEntity> for RuneBalanceRow {
+    type Error = Error;
+
+    fn try_from(entity: &RuneBalanceEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            block: entity.block.try_into()?,
+            rune_id: entity.rune_id.clone(),
+            amount: entity.amount.clone(),
+            address: entity.address.clone(),
+            pk_script_hex: entity.pk_script_hex.clone(),
+            script_type: entity.script_type.clone(),
+            out_point: entity.out_point.clone(),
+            spent: entity.spent,
+            spent_txid: entity.spent_txid.clone(),
+            spent_vin: entity.spent_vin.map(i64::from),
+            spent_block: optional(entity.spent_block)?,
+        })
+    }
+}
+
+impl TryFrom<RuneBalanceRow> for RuneBalanceEntity {
+    type Error = Error;
+
+    fn try_from(row: RuneBalanceRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            block: row.block.try_into()?,
+            rune_id: row.rune_id,
+            amount: numeric(&row.amount)?,
+            address: row.address,
+            pk_script_hex: row.pk_script_hex,
+            script_type: row.script_type,
+            out_point: row.out_point,
+            spent: row.spent,
+            spent_txid: row.spent_txid,
+            spent_vin: optional(row.spent_vin)?,
+            spent_block: optional(row.spent_block)?,
+        })
+    }
+}
+
+impl TryFrom<&RuneAddressBalanceEntity> for RuneAddressBalanceRow {
+    type Error = Error;
+
+    fn try_from(entity: &RuneAddressBalanceEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            address: entity.address.clone(),
+            rune_id: entity.rune_id.clone(),
+            amount: entity.amount.clone(),
+            utxo_count: entity.utxo_count.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<RuneAddressBalanceRow> for RuneAddressBalanceEntity {
+    type Error = Error;
+
+    fn try_from(row: RuneAddressBalanceRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            address: row.address,
+            rune_id: row.rune_id,
+            amount: numeric(&row.amount)?,
+            utxo_count: row.utxo_count.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<&RuneBlockEntity> for RuneBlockRow {
+    type Error = Error;
+
+    fn try_from(entity: &RuneBlockEntity) -> Result<Self> {
+        Ok(Self {
+            height: entity.height.try_into()?,
+            block_hash: entity.block_hash.clone(),
+        })
+    }
+}
+
+impl TryFrom<&IndexerStateEntity> for IndexerStateRow {
+    type Error = Error;
+
+    fn try_from(entity: &IndexerStateEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            network: entity.network.clone(),
+            height: entity.height.try_into()?,
+            block_hash: entity.block_hash.clone(),
+        })
+    }
+}
+
+impl TryFrom<IndexerStateRow> for IndexerStateEntity {
+    type Error = Error;
+
+    fn try_from(row: IndexerStateRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            network: row.network,
+            height: row.height.try_into()?,
+            block_hash: row.block_hash,
+        })
+    }
+}
+
+impl TryFrom<&EtchingEntity> for EtchingRow {
+    type Error = Error;
+
+    fn try_from(entity: &EtchingEntity) -> Result<Self> {
+        Ok(Self {
+            id: entity.id.try_into()?,
+            rune: entity.rune.clone(),
+            state: entity.state.into(),
+            destination: entity.destination.clone(),
+            commit_key: entity.commit_key.clone(),
+            runestone: entity.runestone.clone(),
+            fee_rate: entity.fee_rate.try_into()?,
+            postage: entity.postage.try_into()?,
+            commit_tx_id: entity.commit_tx_id.clone(),
+            commit_tx: entity.commit_tx.clone(),
+            reveal_tx_id: entity.reveal_tx_id.clone(),
+            reveal_tx: entity.reveal_tx.clone(),
+            inscription_output: entity.inscription_output.clone(),
+        })
+    }
+}
+
+impl TryFrom<EtchingRow> for EtchingEntity {
+    type Error = Error;
+
+    fn try_from(row: EtchingRow) -> Result<Self> {
+        Ok(Self {
+            id: row.id.try_into()?,
+            rune: numeric(&row.rune)?,
+            state: row.state.try_into()?,
+            destination: row.destination,
+            commit_key: row.commit_key,
+            runestone: row.runestone,
+            fee_rate: row.fee_rate.try_into()?,
+            postage: row.postage.try_into()?,
+            commit_tx_id: row.commit_tx_id,
+            commit_tx: row.commit_tx,
+            reveal_tx_id: row.reveal_tx_id,
+            reveal_tx: row.reveal_tx,
+            inscription_output: row.inscription_output,
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use super::*;
+
+impl RuneBlanaceDao for PgConnection {
+    fn load_by_outpoints(&mut self, outpoints: Vec<String>) -> Result<Vec<RuneBalanceEntity>> {
+        use self::schema::rune_balance::out_point;
+        let results = RuneBalanceTable
+            .filter(out_point.eq_any(outpoints))
+            .select(RuneBalanceRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn load_by_outpoint(&mut self, outpoint: &OutPoint) -> Result<Vec<RuneBalanceEntity>> {
+        self.load_by_outpoints(vec![outpoint.to_string()])
+    }
+
+    fn updates_spend_out_point(&mut self, spends: &[Spend]) -> Result {
+        use self::schema::rune_balance::{out_point, spent};
+
+        for chunk in spends.chunks(SPEND_BATCH_SIZE) {
+            let unspent = RuneBalanceTable
+                .filter(out_point.eq_any(chunk.iter().map(|spend| spend.outpoint.to_string())))
+                .filter(spent.eq(false))
+                .select(out_point)
+                .load::<String>(self)?;
+
+            ensure_unspent(chunk, unspent.into_iter().collect())?;
+
+            let mut query = diesel::sql_query(spends_update_sql(chunk.len(), |n| format!("${n}")))
+                .into_boxed::<Pg>();
+
+            for spend in chunk {
+                query = query
+                    .bind::<Text, _>(spend.outpoint.to_string())
+                    .bind::<Text, _>(spend.txid.to_string());
+            }
+            for spend in chunk {
+                query = query
+                    .bind::<Text, _>(spend.outpoint.to_string())
+                    .bind::<BigInt, _>(i64::from(spend.vin));
+            }
+            for spend in chunk {
+                query = query
+                    .bind::<Text, _>(spend.outpoint.to_string())
+                    .bind::<BigInt, _>(i64::try_from(spend.block)?);
+            }
+            for spend in chunk {
+                query = query.bind::<Text, _>(spend.outpoint.to_string());
+            }
+
+            query.execute(self)?;
+        }
+
+        Ok(())
+    }
+
+    fn store_balances(&mut self, entities: &[RuneBalanceEntity]) -> Result<()> {
+        let rows = rows::<_, RuneBalanceRow>(entities)?;
+        let insert_rows = diesel::insert_into(RuneBalanceTable)
+            .values(&rows)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_balances failed"));
+        }
+
+        Ok(())
+    }
+
+    fn unspend_out_points_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_balance::{spent, spent_block, spent_txid, spent_vin};
+
+        diesel::update(RuneBalanceTable.filter(spent_block.gt(i64::try_from(height)?)))
+            .set((
+                spent.eq(false),
+                spent_txid.eq(None::<String>),
+                spent_vin.eq(None::<i64>),
+                spent_block.eq(None::<i64>),
+            ))
+            .execute(self)?;
+
+        Ok(())
+    }
+
+    fn unspend_unrecorded_out_points(&mut self, outpoints: Vec<String>) -> Result<()> {
+        use self::schema::rune_balance::{out_point, spent, spent_block};
+
+        diesel::update(
+            RuneBalanceTable
+                .filter(out_point.eq_any(outpoints))
+                .filter(spent_block.is_null()),
+        )
+        .set(spent.eq(false))
+        .execute(self)?;
+
+        Ok(())
+    }
+
+    fn has_unrecorded_spends(&mut self) -> Result<bool> {
+        use self::schema::rune_balance::{spent, spent_block};
+
+        let exists = diesel::select(diesel::dsl::exists(
+            RuneBalanceTable
+                .filter(spent.eq(true))
+                .filter(spent_block.is_null()),
+        ))
+        .get_result(self)?;
+
+        Ok(exists)
+    }
+
+    fn delete_balances_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_balance::block;
+        diesel::delete(RuneBalanceTable.filter(block.gt(i64::try_from(height)?))).execute(self)?;
+
+        Ok(())
+    }
+
+    fn load_addresses_after(&mut self, height: u64) -> Result<Vec<String>> {
+        use self::schema::rune_balance::{address, block, spent_block};
+        let height = i64::try_from(height)?;
+        let results = RuneBalanceTable
+            .filter(block.gt(height).or(spent_block.gt(height)))
+            .select(address)
+            .distinct()
+            .load::<String>(self)?;
+
+        Ok(results)
+    }
+
+    fn load_unspent_by_address(&mut self, _address: &str) -> Result<Vec<RuneBalanceEntity>> {
+        use self::schema::rune_balance::{address, spent};
+        let results = RuneBalanceTable
+            .filter(address.eq(_address))
+            .filter(spent.eq(false))
+            .select(RuneBalanceRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn load_spent_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneBalanceEntity>> {
+        use self::schema::rune_balance::{spent_txid, spent_vin};
+        let results = RuneBalanceTable
+            .filter(spent_txid.eq(txid.to_string()))
+            .order(spent_vin.asc())
+            .select(RuneBalanceRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+}
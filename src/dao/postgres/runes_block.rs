@@ -0,0 +1,45 @@
+use super::*;
+
+impl RuneBlockDao for PgConnection {
+    fn load_block_hash(&mut self, _height: u64) -> Result<Option<BlockHash>> {
+        use self::schema::rune_block::{block_hash, height};
+        let result = RuneBlockTable
+            .filter(height.eq(i64::try_from(_height)?))
+            .select(block_hash)
+            .first::<String>(self)
+            .optional()?;
+
+        match result {
+            Some(hash) => Ok(Some(BlockHash::from_str(hash.as_str())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn store_block_header(&mut self, height: u64, header: &Header) -> Result<()> {
+        use self::schema::rune_block::{block_hash, height as height_column};
+
+        let row = RuneBlockRow::try_from(&RuneBlockEntity {
+            height,
+            block_hash: header.block_hash().to_string(),
+        })?;
+        let insert_rows = diesel::insert_into(RuneBlockTable)
+            .values(&row)
+            .on_conflict(height_column)
+            .do_update()
+            .set(block_hash.eq(excluded(block_hash)))
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_block_header failed"));
+        }
+
+        Ok(())
+    }
+
+    fn delete_blocks_after(&mut self, _height: u64) -> Result<()> {
+        use self::schema::rune_block::height;
+        diesel::delete(RuneBlockTable.filter(height.gt(i64::try_from(_height)?))).execute(self)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use super::*;
+
+impl RuneEntryDao for PgConnection {
+    fn gets_rune_entry(&mut self, ids: Vec<String>) -> Result<Vec<RuneEntryEntity>> {
+        use self::schema::rune_entry::rune_id;
+
+        let results = RuneEntryTable
+            .filter(rune_id.eq_any(ids))
+            .select(RuneEntryRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn gets_rune_entry_by_runes(&mut self, runes: Vec<Rune>) -> Result<Vec<RuneEntryEntity>> {
+        use self::schema::rune_entry::rune;
+
+        let runes = runes
+            .iter()
+            .map(|r| BigDecimal::from(r.n()))
+            .collect::<Vec<BigDecimal>>();
+
+        let results = RuneEntryTable
+            .filter(rune.eq_any(runes))
+            .select(RuneEntryRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn load_entry_by_rune(&mut self, _rune: &Rune) -> Result<Option<RuneEntry>> {
+        self.gets_rune_entry_by_runes(vec![*_rune])?
+            .first()
+            .map(convert_model_to_rune_entry)
+            .transpose()
+    }
+
+    fn store_rune_entry(&mut self, id: &RuneId, entry: &RuneEntry) -> Result<()> {
+        let row = RuneEntryRow::try_from(&crate::dao::runes_entry::convert_rune_entry_to_model(
+            id, entry,
+        ))?;
+        let insert_rows = diesel::insert_into(RuneEntryTable)
+            .values(&row)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_rune_entry failed"));
+        }
+
+        Ok(())
+    }
+
+    fn update_rune_mints(&mut self, id: &RuneId, _mints: u128) -> Result<()> {
+        use self::schema::rune_entry::{mints, rune_id};
+
+        let effect_rows = diesel::update(RuneEntryTable.filter(rune_id.eq(id.to_string())))
+            .set(mints.eq(BigDecimal::from(_mints)))
+            .execute(self)?;
+
+        if effect_rows == 0 {
+            return Err(anyhow!("update_rune_mints failed"));
+        }
+
+        Ok(())
+    }
+
+    fn update_rune_burned(&mut self, id: &RuneId, _burned: u128) -> Result<()> {
+        use self::schema::rune_entry::{burned, rune_id};
+
+        let effect_rows = diesel::update(RuneEntryTable.filter(rune_id.eq(id.to_string())))
+            .set(burned.eq(BigDecimal::from(_burned)))
+            .execute(self)?;
+
+        if effect_rows == 0 {
+            return Err(anyhow!("update_rune_burned failed"));
+        }
+
+        Ok(())
+    }
+
+    fn delete_rune_entries_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_entry::block;
+        diesel::delete(RuneEntryTable.filter(block.gt(i64::try_from(height)?))).execute(self)?;
+
+        Ok(())
+    }
+
+    fn gets_rune_number(&mut self) -> Option<u64> {
+        use self::schema::rune_entry::number;
+
+        let result = RuneEntryTable
+            .order(number.desc())
+            .select(number)
+            .first::<i64>(self);
+
+        result.ok().and_then(|last| last.try_into().ok())
+    }
+
+    fn load_rune_entries(&mut self, offset: i64, limit: i64) -> Result<Vec<RuneEntryEntity>> {
+        use self::schema::rune_entry::number;
+
+        let results = RuneEntryTable
+            .order(number.asc())
+            .offset(offset)
+            .limit(limit)
+            .select(RuneEntryRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+}
@@ -0,0 +1,80 @@
+use super::*;
+
+impl RuneEventDao for PgConnection {
+    fn store_events(&mut self, entities: &[RuneEventEntity]) -> Result<()> {
+        let rows = rows::<_, RuneEventRow>(entities)?;
+        let insert_rows = diesel::insert_into(RuneEventTable)
+            .values(&rows)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_events failed"));
+        }
+
+        Ok(())
+    }
+
+    fn load_events_after(&mut self, height: u64) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::block;
+        let results = RuneEventTable
+            .filter(block.gt(i64::try_from(height)?))
+            .select(RuneEventRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn delete_events_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_event::block;
+        diesel::delete(RuneEventTable.filter(block.gt(i64::try_from(height)?))).execute(self)?;
+
+        Ok(())
+    }
+
+    fn load_events_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::{id, tx_id};
+        let results = RuneEventTable
+            .filter(tx_id.eq(txid.to_string()))
+            .order(id.asc())
+            .select(RuneEventRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn load_events_by_rune(
+        &mut self,
+        _rune_id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::{id, rune_id};
+        let results = RuneEventTable
+            .filter(rune_id.eq(_rune_id.to_string()))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RuneEventRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn load_events_by_address(
+        &mut self,
+        _address: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::{address, id};
+        let results = RuneEventTable
+            .filter(address.eq(_address))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RuneEventRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+}
@@ -0,0 +1,33 @@
+use super::*;
+
+impl RuneTxDao for PgConnection {
+    fn store_txs(&mut self, entities: &[RuneTxEntity]) -> Result<()> {
+        let rows = rows::<_, RuneTxRow>(entities)?;
+        let insert_rows = diesel::insert_into(RuneTxTable)
+            .values(&rows)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_txs failed"));
+        }
+
+        Ok(())
+    }
+
+    fn load_txs(&mut self, txids: Vec<String>) -> Result<Vec<RuneTxEntity>> {
+        use self::schema::rune_tx::tx_id;
+        let results = RuneTxTable
+            .filter(tx_id.eq_any(txids))
+            .select(RuneTxRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn delete_txs_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_tx::block;
+        diesel::delete(RuneTxTable.filter(block.gt(i64::try_from(height)?))).execute(self)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use super::*;
+
+impl RunestoneDao for PgConnection {
+    fn store_runestones(&mut self, entities: &[RunestoneEntity]) -> Result<()> {
+        let rows = rows::<_, RunestoneRow>(entities)?;
+        let insert_rows = diesel::insert_into(RunestoneTable)
+            .values(&rows)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_runestones failed"));
+        }
+
+        Ok(())
+    }
+
+    fn store_edicts(&mut self, entities: &[RunestoneEdictEntity]) -> Result<()> {
+        let rows = rows::<_, RunestoneEdictRow>(entities)?;
+        let insert_rows = diesel::insert_into(RunestoneEdictTable)
+            .values(&rows)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_edicts failed"));
+        }
+
+        Ok(())
+    }
+
+    fn load_runestone(
+        &mut self,
+        txid: &Txid,
+    ) -> Result<Option<(RunestoneEntity, Vec<RunestoneEdictEntity>)>> {
+        use self::schema::{runestone, runestone_edict};
+
+        let Some(row) = RunestoneTable
+            .filter(runestone::tx_id.eq(txid.to_string()))
+            .select(RunestoneRow::as_select())
+            .first(self)
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let edicts = RunestoneEdictTable
+            .filter(runestone_edict::tx_id.eq(txid.to_string()))
+            .order(runestone_edict::edict_index.asc())
+            .select(RunestoneEdictRow::as_select())
+            .load(self)?;
+
+        Ok(Some((row.try_into()?, entities(edicts)?)))
+    }
+
+    fn load_edicts_by_rune(
+        &mut self,
+        _rune_id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEdictEntity>> {
+        use self::schema::runestone_edict::{id, rune_id};
+        let results = RunestoneEdictTable
+            .filter(rune_id.eq(_rune_id.to_string()))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RunestoneEdictRow::as_select())
+            .load(self)?;
+
+        entities(results)
+    }
+
+    fn load_cenotaphs(
+        &mut self,
+        _flaw: Option<Flaw>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEntity>> {
+        use self::schema::runestone::{cenotaph, flaw, id};
+        let mut query = RunestoneTable
+            .filter(cenotaph.eq(true))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RunestoneRow::as_select())
+            .into_boxed();
+
+        if let Some(_flaw) = _flaw {
+            query = query.filter(flaw.eq(crate::dao::runestone::flaw_name(_flaw)?));
+        }
+
+        entities(query.load(self)?)
+    }
+
+    fn delete_runestones_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::{runestone, runestone_edict};
+
+        let height = i64::try_from(height)?;
+        diesel::delete(RunestoneEdictTable.filter(runestone_edict::block.gt(height)))
+            .execute(self)?;
+        diesel::delete(RunestoneTable.filter(runestone::block.gt(height))).execute(self)?;
+
+        Ok(())
+    }
+}
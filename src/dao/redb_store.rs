@@ -0,0 +1,1885 @@
+use {
+    super::*,
+    crate::{
+        entry::{Entry, HeaderValue, OutPointValue, RuneEntryValue, RuneIdValue},
+        indexer::{decode_rune_balances, encode_rune_balance},
+    },
+    redb::{
+        Database, Key, MultimapTableDefinition, ReadTransaction, ReadableMultimapTable,
+        ReadableTable, Table, TableDefinition, Value, WriteTransaction,
+    },
+};
+
+macro_rules! define_table {
+    ($name:ident, $key:ty, $value:ty) => {
+        const $name: TableDefinition<$key, $value> = TableDefinition::new(stringify!($name));
+    };
+}
+
+macro_rules! define_multimap_table {
+    ($name:ident, $key:ty, $value:ty) => {
+        const $name: MultimapTableDefinition<$key, $value> =
+            MultimapTableDefinition::new(stringify!($name));
+    };
+}
+
+// reads go through the open write transaction, if any, so they see its writes
+macro_rules! read {
+    ($store:expr, |$tx:ident| $body:expr) => {
+        match &$store.wtx {
+            Some($tx) => {
+                let result = $body;
+                result
+            }
+            None => {
+                let rtx = $store.database.begin_read()?;
+                let $tx = &rtx;
+                let result = $body;
+                result
+            }
+        }
+    };
+}
+
+type OwnerValue<'a> = (
+    u64,     // block
+    bool,    // spent
+    &'a str, // address
+    &'a str, // pk script hex
+    &'a str, // script type
+);
+
+type SpendValue<'a> = (
+    &'a str, // txid
+    u32,     // vin
+    u64,     // block
+);
+
+type EventValue<'a> = (
+    u64,                     // block
+    u8,                      // event type
+    &'a str,                 // txid
+    &'a str,                 // rune id
+    Option<u128>,            // amount
+    &'a str,                 // address
+    (&'a str, &'a str, u32), // pk script hex, script type, vout
+    u64,                     // timestamp
+    &'a str,                 // reason
+);
+
+type TxValue<'a> = (
+    u64,         // block
+    &'a str,     // txid
+    Option<u64>, // input value
+    Option<u64>, // fee
+);
+
+type AddressBalanceValue<'a> = (
+    &'a str, // address
+    &'a str, // rune id
+    u128,    // amount
+    u64,     // utxo count
+);
+
+type IndexerStateValue<'a> = (
+    &'a str, // network
+    u64,     // height
+    &'a str, // block hash
+);
+
+type EtchingValue<'a> = (
+    u128,    // rune
+    u8,      // state
+    &'a str, // destination
+    &'a str, // commit key
+    &'a str, // runestone
+    u64,     // fee rate
+    u64,     // postage
+    &'a str, // commit txid
+    &'a str, // commit tx
+    &'a str, // reveal txid
+    &'a str, // reveal tx
+    &'a str, // inscription output
+);
+
+type RunestoneValue<'a> = (
+    u64,             // block
+    &'a str,         // txid
+    bool,            // cenotaph
+    Option<&'a str>, // flaw
+    Option<u32>,     // pointer
+    Option<&'a str>, // mint
+    (
+        bool,            // etching
+        Option<u128>,    // rune
+        Option<u32>,     // spacers
+        Option<u8>,      // divisibility
+        Option<u128>,    // premine
+        Option<&'a str>, // symbol
+        bool,            // turbo
+    ),
+    (
+        bool,                       // terms
+        Option<u128>,               // amount
+        Option<u128>,               // cap
+        (Option<u64>, Option<u64>), // height
+        (Option<u64>, Option<u64>), // offset
+    ),
+);
+
+type EdictValue<'a> = (
+    u64,     // block
+    &'a str, // txid
+    u32,     // edict index
+    &'a str, // rune id
+    u128,    // amount
+    u32,     // output
+);
+
+/// Version of the layout of the tables, bumped whenever a table or the
+/// encoding of its keys or values changes. Files of other versions are refused
+/// rather than migrated, and have to be indexed again.
+const SCHEMA_VERSION: u64 = 1;
+
+define_table! { METADATA, &str, u64 }
+define_table! { RUNE_ID_TO_RUNE_ENTRY, RuneIdValue, RuneEntryValue }
+define_table! { RUNE_TO_RUNE_ID, u128, RuneIdValue }
+define_table! { RUNE_NUMBER_TO_RUNE_ID, u64, RuneIdValue }
+define_table! { OUTPOINT_TO_RUNE_BALANCES, &OutPointValue, &[u8] }
+define_table! { OUTPOINT_TO_OWNER, &OutPointValue, OwnerValue<'static> }
+define_multimap_table! { ADDRESS_TO_OUTPOINT, &str, &OutPointValue }
+define_multimap_table! { HEIGHT_TO_OUTPOINT, u64, &OutPointValue }
+define_table! { OUTPOINT_TO_SPEND, &OutPointValue, SpendValue<'static> }
+define_multimap_table! { TXID_TO_SPENT_OUTPOINT, &str, &OutPointValue }
+define_multimap_table! { SPENT_HEIGHT_TO_OUTPOINT, u64, &OutPointValue }
+define_table! { HEIGHT_TO_BLOCK_HEADER, u64, &HeaderValue }
+define_table! { INDEXER_STATE, u32, IndexerStateValue<'static> }
+define_table! { SEQUENCE_TO_LAST_ID, &str, u64 }
+define_table! { ID_TO_EVENT, u64, EventValue<'static> }
+define_multimap_table! { TXID_TO_EVENT_ID, &str, u64 }
+define_multimap_table! { RUNE_ID_TO_EVENT_ID, &str, u64 }
+define_multimap_table! { ADDRESS_TO_EVENT_ID, &str, u64 }
+define_table! { ID_TO_TX, u64, TxValue<'static> }
+define_table! { TXID_TO_TX_ID, &str, u64 }
+define_table! { ID_TO_ADDRESS_BALANCE, u64, AddressBalanceValue<'static> }
+define_multimap_table! { ADDRESS_TO_ADDRESS_BALANCE_ID, &str, u64 }
+define_multimap_table! { RUNE_ID_TO_ADDRESS_BALANCE_ID, &str, u64 }
+define_table! { ID_TO_ETCHING, u64, EtchingValue<'static> }
+define_table! { COMMIT_TXID_TO_ETCHING_ID, &str, u64 }
+define_table! { ID_TO_RUNESTONE, u64, RunestoneValue<'static> }
+define_table! { TXID_TO_RUNESTONE_ID, &str, u64 }
+define_table! { ID_TO_EDICT, u64, EdictValue<'static> }
+define_multimap_table! { TXID_TO_EDICT_ID, &str, u64 }
+define_multimap_table! { RUNE_ID_TO_EDICT_ID, &str, u64 }
+
+// a file can only be opened once per process, so stores of the same file, like
+// the ones of the api and the indexer, share its database
+static DATABASES: Mutex<BTreeMap<PathBuf, Arc<Database>>> = Mutex::new(BTreeMap::new());
+
+/// Rune store in a redb file, for single node deployments without a database
+/// server. Rune entries, rune ids and balances use the same encodings as
+/// ord's index.
+pub(crate) struct RedbStore {
+    database: Arc<Database>,
+    wtx: Option<WriteTransaction>,
+}
+
+/// Tables of either a read or a write transaction.
+trait Tables {
+    fn table<K: Key + 'static, V: Value + 'static>(
+        &self,
+        definition: TableDefinition<K, V>,
+    ) -> Result<impl ReadableTable<K, V> + '_>;
+
+    fn multimap_table<K: Key + 'static, V: Key + 'static>(
+        &self,
+        definition: MultimapTableDefinition<K, V>,
+    ) -> Result<impl ReadableMultimapTable<K, V> + '_>;
+}
+
+impl Tables for ReadTransaction {
+    fn table<K: Key + 'static, V: Value + 'static>(
+        &self,
+        definition: TableDefinition<K, V>,
+    ) -> Result<impl ReadableTable<K, V> + '_> {
+        Ok(self.open_table(definition)?)
+    }
+
+    fn multimap_table<K: Key + 'static, V: Key + 'static>(
+        &self,
+        definition: MultimapTableDefinition<K, V>,
+    ) -> Result<impl ReadableMultimapTable<K, V> + '_> {
+        Ok(self.open_multimap_table(definition)?)
+    }
+}
+
+impl Tables for WriteTransaction {
+    fn table<K: Key + 'static, V: Value + 'static>(
+        &self,
+        definition: TableDefinition<K, V>,
+    ) -> Result<impl ReadableTable<K, V> + '_> {
+        Ok(self.open_table(definition)?)
+    }
+
+    fn multimap_table<K: Key + 'static, V: Key + 'static>(
+        &self,
+        definition: MultimapTableDefinition<K, V>,
+    ) -> Result<impl ReadableMultimapTable<K, V> + '_> {
+        Ok(self.open_multimap_table(definition)?)
+    }
+}
+
+impl RedbStore {
+    /// Open the store at `path`, creating it if it doesn't exist.
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let mut databases = DATABASES
+            .lock()
+            .map_err(|_| anyhow!("redb databases mutex poisoned"))?;
+
+        if let Some(database) = databases.get(path) {
+            return Ok(Self {
+                database: database.clone(),
+                wtx: None,
+            });
+        }
+
+        let database = Database::create(path)
+            .with_context(|| format!("failed to open redb database at `{}`", path.display()))?;
+
+        // read transactions can't open tables that don't exist yet
+        let wtx = database.begin_write()?;
+        wtx.open_table(METADATA)?;
+        wtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
+        wtx.open_table(RUNE_TO_RUNE_ID)?;
+        wtx.open_table(RUNE_NUMBER_TO_RUNE_ID)?;
+        wtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+        wtx.open_table(OUTPOINT_TO_OWNER)?;
+        wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+        wtx.open_multimap_table(HEIGHT_TO_OUTPOINT)?;
+        wtx.open_table(OUTPOINT_TO_SPEND)?;
+        wtx.open_multimap_table(TXID_TO_SPENT_OUTPOINT)?;
+        wtx.open_multimap_table(SPENT_HEIGHT_TO_OUTPOINT)?;
+        wtx.open_table(HEIGHT_TO_BLOCK_HEADER)?;
+        wtx.open_table(INDEXER_STATE)?;
+        wtx.open_table(SEQUENCE_TO_LAST_ID)?;
+        wtx.open_table(ID_TO_EVENT)?;
+        wtx.open_multimap_table(TXID_TO_EVENT_ID)?;
+        wtx.open_multimap_table(RUNE_ID_TO_EVENT_ID)?;
+        wtx.open_multimap_table(ADDRESS_TO_EVENT_ID)?;
+        wtx.open_table(ID_TO_TX)?;
+        wtx.open_table(TXID_TO_TX_ID)?;
+        wtx.open_table(ID_TO_ADDRESS_BALANCE)?;
+        wtx.open_multimap_table(ADDRESS_TO_ADDRESS_BALANCE_ID)?;
+        wtx.open_multimap_table(RUNE_ID_TO_ADDRESS_BALANCE_ID)?;
+        wtx.open_table(ID_TO_ETCHING)?;
+        wtx.open_table(COMMIT_TXID_TO_ETCHING_ID)?;
+        wtx.open_table(ID_TO_RUNESTONE)?;
+        wtx.open_table(TXID_TO_RUNESTONE_ID)?;
+        wtx.open_table(ID_TO_EDICT)?;
+        wtx.open_multimap_table(TXID_TO_EDICT_ID)?;
+        wtx.open_multimap_table(RUNE_ID_TO_EDICT_ID)?;
+        wtx.commit()?;
+
+        let database = Arc::new(database);
+        databases.insert(path.into(), database.clone());
+
+        Ok(Self {
+            database,
+            wtx: None,
+        })
+    }
+
+    /// Run `f` in the open transaction, or in one of its own.
+    fn write<T>(&mut self, f: impl FnOnce(&WriteTransaction) -> Result<T>) -> Result<T> {
+        if let Some(wtx) = &self.wtx {
+            return f(wtx);
+        }
+
+        let wtx = self.database.begin_write()?;
+        let result = f(&wtx)?;
+        wtx.commit()?;
+
+        Ok(result)
+    }
+
+    fn last_rune_number(&mut self) -> Result<Option<u64>> {
+        read!(self, |tx| Ok(tx
+            .table(RUNE_NUMBER_TO_RUNE_ID)?
+            .last()?
+            .map(|(number, _)| number.value())))
+    }
+
+    fn update_rune_entry(&mut self, id: &RuneId, update: impl FnOnce(&mut RuneEntry)) -> Result {
+        self.write(|wtx| {
+            let mut entries = wtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
+
+            let mut entry = entries
+                .get(id.store())?
+                .map(|entry| RuneEntry::load(entry.value()))
+                .ok_or_else(|| anyhow!("rune {id} not found"))?;
+
+            update(&mut entry);
+
+            entries.insert(id.store(), entry.store())?;
+
+            Ok(())
+        })
+    }
+
+    fn load_events(&mut self, ids: Vec<u64>) -> Result<Vec<RuneEventEntity>> {
+        read!(self, |tx| {
+            let events = tx.table(ID_TO_EVENT)?;
+
+            ids.into_iter()
+                .map(|id| {
+                    let event = events
+                        .get(id)?
+                        .ok_or_else(|| anyhow!("event {id} not found"))?;
+
+                    Ok(event_entity(id, event.value()))
+                })
+                .collect()
+        })
+    }
+
+    fn load_address_balances_by_id(
+        &mut self,
+        ids: Vec<u64>,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        read!(self, |tx| {
+            let balances = tx.table(ID_TO_ADDRESS_BALANCE)?;
+
+            ids.into_iter()
+                .map(|id| {
+                    let balance = balances
+                        .get(id)?
+                        .ok_or_else(|| anyhow!("address balance {id} not found"))?;
+
+                    Ok(address_balance_entity(id, balance.value()))
+                })
+                .collect()
+        })
+    }
+
+    fn load_edicts(&mut self, ids: Vec<u64>) -> Result<Vec<RunestoneEdictEntity>> {
+        read!(self, |tx| {
+            let edicts = tx.table(ID_TO_EDICT)?;
+
+            ids.into_iter()
+                .map(|id| {
+                    let edict = edicts
+                        .get(id)?
+                        .ok_or_else(|| anyhow!("edict {id} not found"))?;
+
+                    Ok(edict_entity(id, edict.value()))
+                })
+                .collect()
+        })
+    }
+}
+
+impl RuneStore for RedbStore {
+    fn with_transaction(&mut self, f: &mut dyn FnMut(&mut dyn RuneStore) -> Result) -> Result {
+        if self.wtx.is_some() {
+            return f(self);
+        }
+
+        self.wtx = Some(self.database.begin_write()?);
+
+        let result = f(self);
+
+        if let Some(wtx) = self.wtx.take() {
+            match result {
+                Ok(()) => wtx.commit()?,
+                Err(_) => wtx.abort()?,
+            }
+        }
+
+        result
+    }
+
+    // tables are created when the store is opened, so this only checks that
+    // they have the layout of this version, recording it in new files
+    fn migrate(&mut self) -> Result {
+        self.write(|wtx| {
+            let mut metadata = wtx.open_table(METADATA)?;
+
+            let version = metadata
+                .get("schema_version")?
+                .map(|version| version.value());
+
+            match version {
+                Some(version) => ensure!(
+                    version == SCHEMA_VERSION,
+                    "redb database has schema version {version}, but this version of runes \
+                     uses schema version {SCHEMA_VERSION}, index into a new file"
+                ),
+                None => {
+                    metadata.insert("schema_version", SCHEMA_VERSION)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+fn next_id(wtx: &WriteTransaction, sequence: &str) -> Result<u64> {
+    let mut sequences = wtx.open_table(SEQUENCE_TO_LAST_ID)?;
+
+    let id = sequences
+        .get(sequence)?
+        .map(|id| id.value())
+        .unwrap_or_default()
+        + 1;
+
+    sequences.insert(sequence, id)?;
+
+    Ok(id)
+}
+
+// the ids of a multimap entry, oldest first
+fn ids(table: &impl ReadableMultimapTable<&'static str, u64>, key: &str) -> Result<Vec<u64>> {
+    table.get(key)?.map(|id| Ok(id?.value())).collect()
+}
+
+// the newest `limit` ids of a multimap entry after skipping `offset`
+fn page_ids(
+    table: &impl ReadableMultimapTable<&'static str, u64>,
+    key: &str,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<u64>> {
+    table
+        .get(key)?
+        .rev()
+        .skip(offset.try_into()?)
+        .take(limit.try_into()?)
+        .map(|id| Ok(id?.value()))
+        .collect()
+}
+
+fn balance_entities(
+    balances: &impl ReadableTable<&'static OutPointValue, &'static [u8]>,
+    owners: &impl ReadableTable<&'static OutPointValue, OwnerValue<'static>>,
+    spends: &impl ReadableTable<&'static OutPointValue, SpendValue<'static>>,
+    outpoint: OutPoint,
+) -> Result<Vec<RuneBalanceEntity>> {
+    let key = outpoint.store();
+
+    let (Some(buffer), Some(owner)) = (balances.get(&key)?, owners.get(&key)?) else {
+        return Ok(Vec::new());
+    };
+
+    let (block, spent, address, pk_script_hex, script_type) = owner.value();
+
+    let spend = spends.get(&key)?.map(|spend| {
+        let (txid, vin, block) = spend.value();
+        (txid.to_owned(), vin, block)
+    });
+
+    Ok(decode_rune_balances(buffer.value())?
+        .into_iter()
+        .map(|(id, balance)| RuneBalanceEntity {
+            id: 0,
+            block,
+            rune_id: id.to_string(),
+            amount: BigDecimal::from(balance),
+            address: address.into(),
+            pk_script_hex: pk_script_hex.into(),
+            script_type: script_type.into(),
+            out_point: outpoint.to_string(),
+            spent,
+            spent_txid: spend.as_ref().map(|(txid, _, _)| txid.clone()),
+            spent_vin: spend.as_ref().map(|(_, vin, _)| *vin),
+            spent_block: spend.as_ref().map(|(_, _, block)| *block),
+        })
+        .collect())
+}
+
+// returns whether the spent flag changed
+fn set_spent(
+    owners: &mut Table<&'static OutPointValue, OwnerValue<'static>>,
+    key: &OutPointValue,
+    spent: bool,
+) -> Result<bool> {
+    let Some((block, was_spent, address, pk_script_hex, script_type)) =
+        owners.get(&key)?.map(|owner| {
+            let (block, spent, address, pk_script_hex, script_type) = owner.value();
+            (
+                block,
+                spent,
+                address.to_owned(),
+                pk_script_hex.to_owned(),
+                script_type.to_owned(),
+            )
+        })
+    else {
+        return Ok(false);
+    };
+
+    if was_spent == spent {
+        return Ok(false);
+    }
+
+    owners.insert(
+        &key,
+        (
+            block,
+            spent,
+            address.as_str(),
+            pk_script_hex.as_str(),
+            script_type.as_str(),
+        ),
+    )?;
+
+    Ok(true)
+}
+
+fn event_entity(
+    id: u64,
+    (
+        block,
+        event_type,
+        tx_id,
+        rune_id,
+        amount,
+        address,
+        (pk_script_hex, script_type, vout),
+        timestamp,
+        reason,
+    ): EventValue,
+) -> RuneEventEntity {
+    RuneEventEntity {
+        id,
+        block,
+        event_type,
+        tx_id: tx_id.into(),
+        rune_id: rune_id.into(),
+        amount: amount.map(BigDecimal::from),
+        address: address.into(),
+        pk_script_hex: pk_script_hex.into(),
+        script_type: script_type.into(),
+        vout,
+        timestamp,
+        reason: reason.into(),
+    }
+}
+
+fn address_balance_entity(
+    id: u64,
+    (address, rune_id, amount, utxo_count): AddressBalanceValue,
+) -> RuneAddressBalanceEntity {
+    RuneAddressBalanceEntity {
+        id,
+        address: address.into(),
+        rune_id: rune_id.into(),
+        amount: BigDecimal::from(amount),
+        utxo_count,
+    }
+}
+
+fn insert_address_balance(wtx: &WriteTransaction, id: u64, value: AddressBalanceValue) -> Result {
+    let (address, rune_id, _, _) = value;
+
+    wtx.open_table(ID_TO_ADDRESS_BALANCE)?.insert(id, value)?;
+    wtx.open_multimap_table(ADDRESS_TO_ADDRESS_BALANCE_ID)?
+        .insert(address, id)?;
+    wtx.open_multimap_table(RUNE_ID_TO_ADDRESS_BALANCE_ID)?
+        .insert(rune_id, id)?;
+
+    Ok(())
+}
+
+fn remove_address_balance(wtx: &WriteTransaction, id: u64) -> Result {
+    let Some((address, rune_id)) =
+        wtx.open_table(ID_TO_ADDRESS_BALANCE)?
+            .remove(id)?
+            .map(|balance| {
+                let (address, rune_id, _, _) = balance.value();
+                (address.to_owned(), rune_id.to_owned())
+            })
+    else {
+        return Ok(());
+    };
+
+    wtx.open_multimap_table(ADDRESS_TO_ADDRESS_BALANCE_ID)?
+        .remove(address.as_str(), id)?;
+    wtx.open_multimap_table(RUNE_ID_TO_ADDRESS_BALANCE_ID)?
+        .remove(rune_id.as_str(), id)?;
+
+    Ok(())
+}
+
+fn etching_entity(
+    id: u64,
+    (
+        rune,
+        state,
+        destination,
+        commit_key,
+        runestone,
+        fee_rate,
+        postage,
+        commit_tx_id,
+        commit_tx,
+        reveal_tx_id,
+        reveal_tx,
+        inscription_output,
+    ): EtchingValue,
+) -> EtchingEntity {
+    EtchingEntity {
+        id,
+        rune: BigDecimal::from(rune),
+        state,
+        destination: destination.into(),
+        commit_key: commit_key.into(),
+        runestone: runestone.into(),
+        fee_rate,
+        postage,
+        commit_tx_id: commit_tx_id.into(),
+        commit_tx: commit_tx.into(),
+        reveal_tx_id: reveal_tx_id.into(),
+        reveal_tx: reveal_tx.into(),
+        inscription_output: inscription_output.into(),
+    }
+}
+
+fn insert_etching(wtx: &WriteTransaction, id: u64, entity: &EtchingEntity) -> Result {
+    wtx.open_table(ID_TO_ETCHING)?.insert(
+        id,
+        (
+            decimal_to_u128(&entity.rune)?,
+            entity.state,
+            entity.destination.as_str(),
+            entity.commit_key.as_str(),
+            entity.runestone.as_str(),
+            entity.fee_rate,
+            entity.postage,
+            entity.commit_tx_id.as_str(),
+            entity.commit_tx.as_str(),
+            entity.reveal_tx_id.as_str(),
+            entity.reveal_tx.as_str(),
+            entity.inscription_output.as_str(),
+        ),
+    )?;
+
+    wtx.open_table(COMMIT_TXID_TO_ETCHING_ID)?
+        .insert(entity.commit_tx_id.as_str(), id)?;
+
+    Ok(())
+}
+
+fn runestone_entity(
+    id: u64,
+    (
+        block,
+        tx_id,
+        cenotaph,
+        flaw,
+        pointer,
+        mint,
+        (etching, rune, spacers, divisibility, premine, symbol, turbo),
+        (terms, amount, cap, (height_start, height_end), (offset_start, offset_end)),
+    ): RunestoneValue,
+) -> RunestoneEntity {
+    RunestoneEntity {
+        id,
+        block,
+        tx_id: tx_id.into(),
+        cenotaph,
+        flaw: flaw.map(Into::into),
+        pointer,
+        mint: mint.map(Into::into),
+        etching,
+        rune: rune.map(BigDecimal::from),
+        spacers,
+        divisibility,
+        premine: premine.map(BigDecimal::from),
+        symbol: symbol.map(Into::into),
+        turbo,
+        terms,
+        amount: amount.map(BigDecimal::from),
+        cap: cap.map(BigDecimal::from),
+        height_start,
+        height_end,
+        offset_start,
+        offset_end,
+    }
+}
+
+fn edict_entity(
+    id: u64,
+    (block, tx_id, edict_index, rune_id, amount, output): EdictValue,
+) -> RunestoneEdictEntity {
+    RunestoneEdictEntity {
+        id,
+        block,
+        tx_id: tx_id.into(),
+        edict_index,
+        rune_id: rune_id.into(),
+        amount: BigDecimal::from(amount),
+        output,
+    }
+}
+
+impl RuneEntryDao for RedbStore {
+    fn gets_rune_entry(&mut self, ids: Vec<String>) -> Result<Vec<RuneEntryEntity>> {
+        let ids = ids
+            .iter()
+            .map(|id| RuneId::from_str(id))
+            .collect::<Result<Vec<RuneId>, _>>()?;
+
+        read!(self, |tx| {
+            let entries = tx.table(RUNE_ID_TO_RUNE_ENTRY)?;
+
+            let mut entities = Vec::new();
+            for id in &ids {
+                if let Some(entry) = entries.get(id.store())? {
+                    entities.push(runes_entry::convert_rune_entry_to_model(
+                        id,
+                        &RuneEntry::load(entry.value()),
+                    ));
+                }
+            }
+
+            Ok(entities)
+        })
+    }
+
+    fn gets_rune_entry_by_runes(&mut self, runes: Vec<Rune>) -> Result<Vec<RuneEntryEntity>> {
+        let ids = read!(self, |tx| {
+            let ids = tx.table(RUNE_TO_RUNE_ID)?;
+
+            let mut rune_ids = Vec::new();
+            for rune in &runes {
+                if let Some(id) = ids.get(rune.store())? {
+                    rune_ids.push(RuneId::load(id.value()).to_string());
+                }
+            }
+
+            Ok::<Vec<String>, Error>(rune_ids)
+        })?;
+
+        self.gets_rune_entry(ids)
+    }
+
+    fn load_entry_by_rune(&mut self, rune: &Rune) -> Result<Option<RuneEntry>> {
+        self.gets_rune_entry_by_runes(vec![*rune])?
+            .first()
+            .map(convert_model_to_rune_entry)
+            .transpose()
+    }
+
+    fn store_rune_entry(&mut self, id: &RuneId, entry: &RuneEntry) -> Result<()> {
+        self.write(|wtx| {
+            let rune = entry.spaced_rune.rune;
+
+            let mut ids = wtx.open_table(RUNE_TO_RUNE_ID)?;
+            ensure!(
+                ids.get(rune.store())?.is_none(),
+                "rune {rune} already exists"
+            );
+            ids.insert(rune.store(), id.store())?;
+
+            wtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?
+                .insert(id.store(), entry.store())?;
+            wtx.open_table(RUNE_NUMBER_TO_RUNE_ID)?
+                .insert(entry.number, id.store())?;
+
+            Ok(())
+        })
+    }
+
+    fn update_rune_mints(&mut self, id: &RuneId, mints: u128) -> Result<()> {
+        self.update_rune_entry(id, |entry| entry.mints = mints)
+    }
+
+    fn update_rune_burned(&mut self, id: &RuneId, burned: u128) -> Result<()> {
+        self.update_rune_entry(id, |entry| entry.burned = burned)
+    }
+
+    // rune ids start with the block of the etching, so they sort by height
+    fn delete_rune_entries_after(&mut self, height: u64) -> Result<()> {
+        self.write(|wtx| {
+            let mut entries = wtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
+
+            let mut deleted = Vec::new();
+            for result in entries.extract_from_if((height + 1, 0u32).., |_, _| true)? {
+                let (_, entry) = result?;
+                deleted.push(RuneEntry::load(entry.value()));
+            }
+
+            let mut ids = wtx.open_table(RUNE_TO_RUNE_ID)?;
+            let mut numbers = wtx.open_table(RUNE_NUMBER_TO_RUNE_ID)?;
+            for entry in deleted {
+                ids.remove(entry.spaced_rune.rune.store())?;
+                numbers.remove(entry.number)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn gets_rune_number(&mut self) -> Option<u64> {
+        self.last_rune_number().ok().flatten()
+    }
+
+    fn load_rune_entries(&mut self, offset: i64, limit: i64) -> Result<Vec<RuneEntryEntity>> {
+        let ids = read!(self, |tx| tx
+            .table(RUNE_NUMBER_TO_RUNE_ID)?
+            .iter()?
+            .skip(offset.try_into()?)
+            .take(limit.try_into()?)
+            .map(|result| Ok(RuneId::load(result?.1.value()).to_string()))
+            .collect::<Result<Vec<String>>>())?;
+
+        self.gets_rune_entry(ids)
+    }
+}
+
+impl RuneEventDao for RedbStore {
+    fn store_events(&mut self, entities: &[RuneEventEntity]) -> Result<()> {
+        self.write(|wtx| {
+            for entity in entities {
+                let id = next_id(wtx, "event")?;
+
+                wtx.open_table(ID_TO_EVENT)?.insert(
+                    id,
+                    (
+                        entity.block,
+                        entity.event_type,
+                        entity.tx_id.as_str(),
+                        entity.rune_id.as_str(),
+                        entity.amount.as_ref().map(decimal_to_u128).transpose()?,
+                        entity.address.as_str(),
+                        (
+                            entity.pk_script_hex.as_str(),
+                            entity.script_type.as_str(),
+                            entity.vout,
+                        ),
+                        entity.timestamp,
+                        entity.reason.as_str(),
+                    ),
+                )?;
+
+                wtx.open_multimap_table(TXID_TO_EVENT_ID)?
+                    .insert(entity.tx_id.as_str(), id)?;
+                wtx.open_multimap_table(RUNE_ID_TO_EVENT_ID)?
+                    .insert(entity.rune_id.as_str(), id)?;
+                wtx.open_multimap_table(ADDRESS_TO_EVENT_ID)?
+                    .insert(entity.address.as_str(), id)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    // events are stored block by block, so the newest ones are those after
+    // the height
+    fn load_events_after(&mut self, height: u64) -> Result<Vec<RuneEventEntity>> {
+        let mut events = read!(self, |tx| {
+            let mut events = Vec::new();
+            for result in tx.table(ID_TO_EVENT)?.iter()?.rev() {
+                let (id, event) = result?;
+                let event = event_entity(id.value(), event.value());
+
+                if event.block <= height {
+                    break;
+                }
+
+                events.push(event);
+            }
+
+            Ok::<Vec<RuneEventEntity>, Error>(events)
+        })?;
+
+        events.reverse();
+
+        Ok(events)
+    }
+
+    fn delete_events_after(&mut self, height: u64) -> Result<()> {
+        let events = self.load_events_after(height)?;
+
+        self.write(|wtx| {
+            let mut table = wtx.open_table(ID_TO_EVENT)?;
+            let mut txids = wtx.open_multimap_table(TXID_TO_EVENT_ID)?;
+            let mut rune_ids = wtx.open_multimap_table(RUNE_ID_TO_EVENT_ID)?;
+            let mut addresses = wtx.open_multimap_table(ADDRESS_TO_EVENT_ID)?;
+
+            for event in &events {
+                table.remove(event.id)?;
+                txids.remove(event.tx_id.as_str(), event.id)?;
+                rune_ids.remove(event.rune_id.as_str(), event.id)?;
+                addresses.remove(event.address.as_str(), event.id)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn load_events_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneEventEntity>> {
+        let ids = read!(self, |tx| ids(
+            &tx.multimap_table(TXID_TO_EVENT_ID)?,
+            &txid.to_string()
+        ))?;
+
+        self.load_events(ids)
+    }
+
+    fn load_events_by_rune(
+        &mut self,
+        id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        let ids = read!(self, |tx| page_ids(
+            &tx.multimap_table(RUNE_ID_TO_EVENT_ID)?,
+            &id.to_string(),
+            offset,
+            limit
+        ))?;
+
+        self.load_events(ids)
+    }
+
+    fn load_events_by_address(
+        &mut self,
+        address: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        let ids = read!(self, |tx| page_ids(
+            &tx.multimap_table(ADDRESS_TO_EVENT_ID)?,
+            address,
+            offset,
+            limit
+        ))?;
+
+        self.load_events(ids)
+    }
+}
+
+impl RuneTxDao for RedbStore {
+    fn store_txs(&mut self, entities: &[RuneTxEntity]) -> Result<()> {
+        self.write(|wtx| {
+            for entity in entities {
+                let mut txids = wtx.open_table(TXID_TO_TX_ID)?;
+
+                ensure!(
+                    txids.get(entity.tx_id.as_str())?.is_none(),
+                    "store_txs failed"
+                );
+
+                let id = next_id(wtx, "tx")?;
+
+                txids.insert(entity.tx_id.as_str(), id)?;
+
+                wtx.open_table(ID_TO_TX)?.insert(
+                    id,
+                    (
+                        entity.block,
+                        entity.tx_id.as_str(),
+                        entity.input_value,
+                        entity.fee,
+                    ),
+                )?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn load_txs(&mut self, txids: Vec<String>) -> Result<Vec<RuneTxEntity>> {
+        read!(self, |tx| {
+            let ids = tx.table(TXID_TO_TX_ID)?;
+            let txs = tx.table(ID_TO_TX)?;
+
+            let mut entities = Vec::new();
+            for txid in &txids {
+                let Some(id) = ids.get(txid.as_str())?.map(|id| id.value()) else {
+                    continue;
+                };
+
+                let tx = txs.get(id)?.ok_or_else(|| anyhow!("tx {id} not found"))?;
+                let (block, tx_id, input_value, fee) = tx.value();
+
+                entities.push(RuneTxEntity {
+                    id,
+                    block,
+                    tx_id: tx_id.into(),
+                    input_value,
+                    fee,
+                });
+            }
+
+            Ok::<Vec<RuneTxEntity>, Error>(entities)
+        })
+    }
+
+    // txs are stored block by block, so the newest ones are those after the
+    // height
+    fn delete_txs_after(&mut self, height: u64) -> Result<()> {
+        self.write(|wtx| {
+            let mut txs = wtx.open_table(ID_TO_TX)?;
+
+            let mut deleted = Vec::new();
+            for result in txs.iter()?.rev() {
+                let (id, tx) = result?;
+                let (block, tx_id, ..) = tx.value();
+
+                if block <= height {
+                    break;
+                }
+
+                deleted.push((id.value(), tx_id.to_owned()));
+            }
+
+            let mut txids = wtx.open_table(TXID_TO_TX_ID)?;
+            for (id, tx_id) in deleted {
+                txs.remove(id)?;
+                txids.remove(tx_id.as_str())?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl RuneBlanaceDao for RedbStore {
+    fn load_by_outpoints(&mut self, outpoints: Vec<String>) -> Result<Vec<RuneBalanceEntity>> {
+        read!(self, |tx| {
+            let balances = tx.table(OUTPOINT_TO_RUNE_BALANCES)?;
+            let owners = tx.table(OUTPOINT_TO_OWNER)?;
+            let spends = tx.table(OUTPOINT_TO_SPEND)?;
+
+            let mut entities = Vec::new();
+            for outpoint in &outpoints {
+                entities.extend(balance_entities(
+                    &balances,
+                    &owners,
+                    &spends,
+                    OutPoint::from_str(outpoint)?,
+                )?);
+            }
+
+            Ok(entities)
+        })
+    }
+
+    fn load_by_outpoint(&mut self, outpoint: &OutPoint) -> Result<Vec<RuneBalanceEntity>> {
+        self.load_by_outpoints(vec![outpoint.to_string()])
+    }
+
+    // like MySQL, only rows that actually change count as updated
+    fn updates_spend_out_point(&mut self, spends: &[Spend]) -> Result {
+        self.write(|wtx| {
+            let mut owners = wtx.open_table(OUTPOINT_TO_OWNER)?;
+            let mut outpoint_to_spend = wtx.open_table(OUTPOINT_TO_SPEND)?;
+            let mut txids = wtx.open_multimap_table(TXID_TO_SPENT_OUTPOINT)?;
+            let mut heights = wtx.open_multimap_table(SPENT_HEIGHT_TO_OUTPOINT)?;
+
+            for spend in spends {
+                let key = spend.outpoint.store();
+
+                ensure!(
+                    set_spent(&mut owners, &key, true)?,
+                    "outpoint {} holds no unspent runes",
+                    spend.outpoint
+                );
+
+                let txid = spend.txid.to_string();
+                outpoint_to_spend.insert(&key, (txid.as_str(), spend.vin, spend.block))?;
+                txids.insert(txid.as_str(), &key)?;
+                heights.insert(spend.block, &key)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn store_balances(&mut self, entities: &[RuneBalanceEntity]) -> Result<()> {
+        self.write(|wtx| {
+            let mut balances = wtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+            let mut owners = wtx.open_table(OUTPOINT_TO_OWNER)?;
+            let mut addresses = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+            let mut heights = wtx.open_multimap_table(HEIGHT_TO_OUTPOINT)?;
+
+            for entity in entities {
+                let key = OutPoint::from_str(&entity.out_point)?.store();
+
+                let mut buffer = balances
+                    .get(&key)?
+                    .map(|buffer| buffer.value().to_vec())
+                    .unwrap_or_default();
+
+                encode_rune_balance(
+                    RuneId::from_str(&entity.rune_id)?,
+                    decimal_to_u128(&entity.amount)?,
+                    &mut buffer,
+                );
+
+                balances.insert(&key, buffer.as_slice())?;
+                owners.insert(
+                    &key,
+                    (
+                        entity.block,
+                        entity.spent,
+                        entity.address.as_str(),
+                        entity.pk_script_hex.as_str(),
+                        entity.script_type.as_str(),
+                    ),
+                )?;
+                addresses.insert(entity.address.as_str(), &key)?;
+                heights.insert(entity.block, &key)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn unspend_out_points_after(&mut self, height: u64) -> Result<()> {
+        self.write(|wtx| {
+            let mut heights = wtx.open_multimap_table(SPENT_HEIGHT_TO_OUTPOINT)?;
+
+            let mut unspent = Vec::new();
+            for result in heights.range(height + 1..)? {
+                let (block, outpoints) = result?;
+                for outpoint in outpoints {
+                    unspent.push((block.value(), *outpoint?.value()));
+                }
+            }
+
+            let mut owners = wtx.open_table(OUTPOINT_TO_OWNER)?;
+            let mut spends = wtx.open_table(OUTPOINT_TO_SPEND)?;
+            let mut txids = wtx.open_multimap_table(TXID_TO_SPENT_OUTPOINT)?;
+
+            for (block, key) in unspent {
+                // balances created after `height` may already be deleted
+                set_spent(&mut owners, &key, false)?;
+
+                let txid = spends.remove(&key)?.map(|spend| spend.value().0.to_owned());
+
+                if let Some(txid) = txid {
+                    txids.remove(txid.as_str(), &key)?;
+                }
+
+                heights.remove_all(block)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn unspend_unrecorded_out_points(&mut self, outpoints: Vec<String>) -> Result<()> {
+        self.write(|wtx| {
+            let mut owners = wtx.open_table(OUTPOINT_TO_OWNER)?;
+            let spends = wtx.open_table(OUTPOINT_TO_SPEND)?;
+
+            for outpoint in &outpoints {
+                let key = OutPoint::from_str(outpoint)?.store();
+
+                if spends.get(&key)?.is_none() {
+                    set_spent(&mut owners, &key, false)?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    fn has_unrecorded_spends(&mut self) -> Result<bool> {
+        read!(self, |tx| {
+            let spends = tx.table(OUTPOINT_TO_SPEND)?;
+
+            for result in tx.table(OUTPOINT_TO_OWNER)?.iter()? {
+                let (key, owner) = result?;
+                let (_, spent, _, _, _) = owner.value();
+
+                if spent && spends.get(key.value())?.is_none() {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        })
+    }
+
+    fn delete_balances_after(&mut self, height: u64) -> Result<()> {
+        self.write(|wtx| {
+            let mut heights = wtx.open_multimap_table(HEIGHT_TO_OUTPOINT)?;
+
+            let mut deleted = Vec::new();
+            for result in heights.range(height + 1..)? {
+                let (block, outpoints) = result?;
+                for outpoint in outpoints {
+                    deleted.push((block.value(), *outpoint?.value()));
+                }
+            }
+
+            let mut balances = wtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+            let mut owners = wtx.open_table(OUTPOINT_TO_OWNER)?;
+            let mut addresses = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+
+            for (block, key) in deleted {
+                balances.remove(&key)?;
+
+                let address = owners.remove(&key)?.map(|owner| owner.value().2.to_owned());
+
+                if let Some(address) = address {
+                    addresses.remove(address.as_str(), &key)?;
+                }
+
+                heights.remove_all(block)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn load_addresses_after(&mut self, height: u64) -> Result<Vec<String>> {
+        read!(self, |tx| {
+            let owners = tx.table(OUTPOINT_TO_OWNER)?;
+
+            let created = tx.multimap_table(HEIGHT_TO_OUTPOINT)?;
+            let spent = tx.multimap_table(SPENT_HEIGHT_TO_OUTPOINT)?;
+
+            let mut addresses = BTreeSet::new();
+            for result in created
+                .range(height + 1..)?
+                .chain(spent.range(height + 1..)?)
+            {
+                let (_, outpoints) = result?;
+                for outpoint in outpoints {
+                    if let Some(owner) = owners.get(outpoint?.value())? {
+                        addresses.insert(owner.value().2.to_owned());
+                    }
+                }
+            }
+
+            Ok(addresses.into_iter().collect())
+        })
+    }
+
+    fn load_unspent_by_address(&mut self, address: &str) -> Result<Vec<RuneBalanceEntity>> {
+        read!(self, |tx| {
+            let balances = tx.table(OUTPOINT_TO_RUNE_BALANCES)?;
+            let owners = tx.table(OUTPOINT_TO_OWNER)?;
+            let spends = tx.table(OUTPOINT_TO_SPEND)?;
+
+            let mut entities = Vec::new();
+            for outpoint in tx.multimap_table(ADDRESS_TO_OUTPOINT)?.get(address)? {
+                let outpoint = OutPoint::load(*outpoint?.value());
+                entities.extend(
+                    balance_entities(&balances, &owners, &spends, outpoint)?
+                        .into_iter()
+                        .filter(|balance| !balance.spent),
+                );
+            }
+
+            Ok(entities)
+        })
+    }
+
+    fn load_spent_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneBalanceEntity>> {
+        read!(self, |tx| {
+            let balances = tx.table(OUTPOINT_TO_RUNE_BALANCES)?;
+            let owners = tx.table(OUTPOINT_TO_OWNER)?;
+            let spends = tx.table(OUTPOINT_TO_SPEND)?;
+
+            let mut entities = Vec::new();
+            for outpoint in tx
+                .multimap_table(TXID_TO_SPENT_OUTPOINT)?
+                .get(txid.to_string().as_str())?
+            {
+                let outpoint = OutPoint::load(*outpoint?.value());
+                entities.extend(balance_entities(&balances, &owners, &spends, outpoint)?);
+            }
+
+            entities.sort_by_key(|balance| balance.spent_vin);
+
+            Ok(entities)
+        })
+    }
+}
+
+impl RuneAddressBalanceDao for RedbStore {
+    fn load_address_balances(
+        &mut self,
+        addresses: Vec<String>,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        let ids = read!(self, |tx| {
+            let table = tx.multimap_table(ADDRESS_TO_ADDRESS_BALANCE_ID)?;
+
+            let mut ids = Vec::new();
+            for address in &addresses {
+                ids.extend(self::ids(&table, address)?);
+            }
+
+            Ok::<Vec<u64>, Error>(ids)
+        })?;
+
+        self.load_address_balances_by_id(ids)
+    }
+
+    fn load_balances_by_address(&mut self, address: &str) -> Result<Vec<RuneAddressBalanceEntity>> {
+        let mut balances = self.load_address_balances(vec![address.into()])?;
+        balances.sort_by(|a, b| a.rune_id.cmp(&b.rune_id));
+
+        Ok(balances)
+    }
+
+    fn load_top_holders(
+        &mut self,
+        id: &RuneId,
+        limit: i64,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        let ids = read!(self, |tx| ids(
+            &tx.multimap_table(RUNE_ID_TO_ADDRESS_BALANCE_ID)?,
+            &id.to_string()
+        ))?;
+
+        let mut holders = self.load_address_balances_by_id(ids)?;
+        holders.sort_by(|a, b| b.amount.cmp(&a.amount));
+        holders.truncate(limit.try_into()?);
+
+        Ok(holders)
+    }
+
+    fn count_holders(&mut self, id: &RuneId) -> Result<u64> {
+        read!(self, |tx| Ok(tx
+            .multimap_table(RUNE_ID_TO_ADDRESS_BALANCE_ID)?
+            .get(id.to_string().as_str())?
+            .len()))
+    }
+
+    // replaces balances with the same id or the same address and rune
+    fn store_address_balances(&mut self, entities: &[RuneAddressBalanceEntity]) -> Result<()> {
+        for entity in entities {
+            let replaced = self
+                .load_address_balances(vec![entity.address.clone()])?
+                .into_iter()
+                .filter(|balance| balance.rune_id == entity.rune_id)
+                .map(|balance| balance.id)
+                .collect::<Vec<u64>>();
+
+            self.write(|wtx| {
+                remove_address_balance(wtx, entity.id)?;
+
+                for id in &replaced {
+                    remove_address_balance(wtx, *id)?;
+                }
+
+                let id = if entity.id == 0 {
+                    next_id(wtx, "address_balance")?
+                } else {
+                    entity.id
+                };
+
+                insert_address_balance(
+                    wtx,
+                    id,
+                    (
+                        entity.address.as_str(),
+                        entity.rune_id.as_str(),
+                        decimal_to_u128(&entity.amount)?,
+                        entity.utxo_count,
+                    ),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn delete_address_balances(&mut self, ids: Vec<u64>) -> Result<()> {
+        self.write(|wtx| {
+            for id in &ids {
+                remove_address_balance(wtx, *id)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn rebuild_address_balances(&mut self, addresses: Vec<String>) -> Result<()> {
+        for address in addresses {
+            let mut rebuilt: BTreeMap<String, (u128, u64)> = BTreeMap::new();
+            for balance in self.load_unspent_by_address(&address)? {
+                let (amount, utxo_count) = rebuilt.entry(balance.rune_id.clone()).or_default();
+                *amount += decimal_to_u128(&balance.amount)?;
+                *utxo_count += 1;
+            }
+
+            let current = self
+                .load_address_balances(vec![address.clone()])?
+                .into_iter()
+                .map(|balance| balance.id)
+                .collect::<Vec<u64>>();
+
+            self.write(|wtx| {
+                for id in &current {
+                    remove_address_balance(wtx, *id)?;
+                }
+
+                for (rune_id, (amount, utxo_count)) in &rebuilt {
+                    let id = next_id(wtx, "address_balance")?;
+                    insert_address_balance(
+                        wtx,
+                        id,
+                        (address.as_str(), rune_id.as_str(), *amount, *utxo_count),
+                    )?;
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RuneBlockDao for RedbStore {
+    fn load_block_hash(&mut self, height: u64) -> Result<Option<BlockHash>> {
+        read!(self, |tx| Ok(tx
+            .table(HEIGHT_TO_BLOCK_HEADER)?
+            .get(height)?
+            .map(|header| Header::load(*header.value()).block_hash())))
+    }
+
+    fn store_block_header(&mut self, height: u64, header: &Header) -> Result<()> {
+        self.write(|wtx| {
+            wtx.open_table(HEIGHT_TO_BLOCK_HEADER)?
+                .insert(height, &header.store())?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_blocks_after(&mut self, height: u64) -> Result<()> {
+        self.write(|wtx| {
+            wtx.open_table(HEIGHT_TO_BLOCK_HEADER)?
+                .retain_in(height + 1.., |_, _| false)?;
+
+            Ok(())
+        })
+    }
+}
+
+impl IndexerStateDao for RedbStore {
+    fn load_indexer_state(&mut self) -> Result<Option<IndexerStateEntity>> {
+        read!(self, |tx| Ok(tx.table(INDEXER_STATE)?.get(1)?.map(
+            |state| {
+                let (network, height, block_hash) = state.value();
+                IndexerStateEntity {
+                    id: 1,
+                    network: network.into(),
+                    height,
+                    block_hash: block_hash.into(),
+                }
+            }
+        )))
+    }
+
+    fn store_indexer_state(
+        &mut self,
+        network: Network,
+        height: u64,
+        hash: &BlockHash,
+    ) -> Result<()> {
+        self.write(|wtx| {
+            wtx.open_table(INDEXER_STATE)?.insert(
+                1,
+                (
+                    network.to_string().as_str(),
+                    height,
+                    hash.to_string().as_str(),
+                ),
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_indexer_state(&mut self) -> Result<()> {
+        self.write(|wtx| {
+            wtx.open_table(INDEXER_STATE)?.remove(1)?;
+
+            Ok(())
+        })
+    }
+}
+
+impl EtchingDao for RedbStore {
+    fn store_etching(&mut self, entity: &EtchingEntity) -> Result<u64> {
+        self.write(|wtx| {
+            ensure!(
+                wtx.open_table(COMMIT_TXID_TO_ETCHING_ID)?
+                    .get(entity.commit_tx_id.as_str())?
+                    .is_none(),
+                "store_etching failed"
+            );
+
+            let id = next_id(wtx, "etching")?;
+
+            insert_etching(wtx, id, entity)?;
+
+            Ok(id)
+        })
+    }
+
+    fn load_etching(&mut self, id: u64) -> Result<Option<EtchingEntity>> {
+        read!(self, |tx| Ok(tx
+            .table(ID_TO_ETCHING)?
+            .get(id)?
+            .map(|etching| etching_entity(id, etching.value()))))
+    }
+
+    fn load_etchings_in_states(&mut self, states: Vec<u8>) -> Result<Vec<EtchingEntity>> {
+        read!(self, |tx| {
+            let mut etchings = Vec::new();
+            for result in tx.table(ID_TO_ETCHING)?.iter()? {
+                let (id, etching) = result?;
+                let etching = etching_entity(id.value(), etching.value());
+
+                if states.contains(&etching.state) {
+                    etchings.push(etching);
+                }
+            }
+
+            Ok(etchings)
+        })
+    }
+
+    fn update_etching(&mut self, entity: &EtchingEntity) -> Result<()> {
+        let current = self
+            .load_etching(entity.id)?
+            .ok_or_else(|| anyhow!("update etching {} failed", entity.id))?;
+
+        self.write(|wtx| {
+            wtx.open_table(COMMIT_TXID_TO_ETCHING_ID)?
+                .remove(current.commit_tx_id.as_str())?;
+
+            insert_etching(wtx, entity.id, entity)
+        })
+    }
+}
+
+impl RunestoneDao for RedbStore {
+    fn store_runestones(&mut self, entities: &[RunestoneEntity]) -> Result<()> {
+        self.write(|wtx| {
+            for entity in entities {
+                let mut txids = wtx.open_table(TXID_TO_RUNESTONE_ID)?;
+
+                ensure!(
+                    txids.get(entity.tx_id.as_str())?.is_none(),
+                    "store_runestones failed"
+                );
+
+                let id = next_id(wtx, "runestone")?;
+
+                txids.insert(entity.tx_id.as_str(), id)?;
+
+                wtx.open_table(ID_TO_RUNESTONE)?.insert(
+                    id,
+                    (
+                        entity.block,
+                        entity.tx_id.as_str(),
+                        entity.cenotaph,
+                        entity.flaw.as_deref(),
+                        entity.pointer,
+                        entity.mint.as_deref(),
+                        (
+                            entity.etching,
+                            entity.rune.as_ref().map(decimal_to_u128).transpose()?,
+                            entity.spacers,
+                            entity.divisibility,
+                            entity.premine.as_ref().map(decimal_to_u128).transpose()?,
+                            entity.symbol.as_deref(),
+                            entity.turbo,
+                        ),
+                        (
+                            entity.terms,
+                            entity.amount.as_ref().map(decimal_to_u128).transpose()?,
+                            entity.cap.as_ref().map(decimal_to_u128).transpose()?,
+                            (entity.height_start, entity.height_end),
+                            (entity.offset_start, entity.offset_end),
+                        ),
+                    ),
+                )?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn store_edicts(&mut self, entities: &[RunestoneEdictEntity]) -> Result<()> {
+        for entity in entities {
+            let exists = self
+                .load_edicts(read!(self, |tx| ids(
+                    &tx.multimap_table(TXID_TO_EDICT_ID)?,
+                    &entity.tx_id
+                ))?)?
+                .iter()
+                .any(|edict| edict.edict_index == entity.edict_index);
+
+            ensure!(!exists, "store_edicts failed");
+
+            self.write(|wtx| {
+                let id = next_id(wtx, "edict")?;
+
+                wtx.open_table(ID_TO_EDICT)?.insert(
+                    id,
+                    (
+                        entity.block,
+                        entity.tx_id.as_str(),
+                        entity.edict_index,
+                        entity.rune_id.as_str(),
+                        decimal_to_u128(&entity.amount)?,
+                        entity.output,
+                    ),
+                )?;
+
+                wtx.open_multimap_table(TXID_TO_EDICT_ID)?
+                    .insert(entity.tx_id.as_str(), id)?;
+                wtx.open_multimap_table(RUNE_ID_TO_EDICT_ID)?
+                    .insert(entity.rune_id.as_str(), id)?;
+
+                Ok(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn load_runestone(
+        &mut self,
+        txid: &Txid,
+    ) -> Result<Option<(RunestoneEntity, Vec<RunestoneEdictEntity>)>> {
+        let txid = txid.to_string();
+
+        let Some(id) = read!(self, |tx| Ok::<Option<u64>, Error>(
+            tx.table(TXID_TO_RUNESTONE_ID)?
+                .get(txid.as_str())?
+                .map(|id| id.value())
+        ))?
+        else {
+            return Ok(None);
+        };
+
+        let runestone = read!(self, |tx| tx
+            .table(ID_TO_RUNESTONE)?
+            .get(id)?
+            .map(|runestone| runestone_entity(id, runestone.value()))
+            .ok_or_else(|| anyhow!("runestone {id} not found")))?;
+
+        let ids = read!(self, |tx| ids(&tx.multimap_table(TXID_TO_EDICT_ID)?, &txid))?;
+
+        let mut edicts = self.load_edicts(ids)?;
+        edicts.sort_by_key(|edict| edict.edict_index);
+
+        Ok(Some((runestone, edicts)))
+    }
+
+    fn load_edicts_by_rune(
+        &mut self,
+        id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEdictEntity>> {
+        let ids = read!(self, |tx| page_ids(
+            &tx.multimap_table(RUNE_ID_TO_EDICT_ID)?,
+            &id.to_string(),
+            offset,
+            limit
+        ))?;
+
+        self.load_edicts(ids)
+    }
+
+    fn load_cenotaphs(
+        &mut self,
+        flaw: Option<Flaw>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEntity>> {
+        let flaw = flaw.map(runestone::flaw_name).transpose()?;
+        let offset = usize::try_from(offset)?;
+        let limit = usize::try_from(limit)?;
+
+        read!(self, |tx| {
+            let mut cenotaphs = Vec::new();
+            let mut skipped = 0;
+            for result in tx.table(ID_TO_RUNESTONE)?.iter()?.rev() {
+                if cenotaphs.len() == limit {
+                    break;
+                }
+
+                let (id, runestone) = result?;
+                let runestone = runestone_entity(id.value(), runestone.value());
+
+                if !runestone.cenotaph || (flaw.is_some() && runestone.flaw != flaw) {
+                    continue;
+                }
+
+                if skipped < offset {
+                    skipped += 1;
+                    continue;
+                }
+
+                cenotaphs.push(runestone);
+            }
+
+            Ok(cenotaphs)
+        })
+    }
+
+    fn delete_runestones_after(&mut self, height: u64) -> Result<()> {
+        self.write(|wtx| {
+            let mut runestones = wtx.open_table(ID_TO_RUNESTONE)?;
+
+            let mut deleted = Vec::new();
+            for result in runestones.iter()?.rev() {
+                let (id, runestone) = result?;
+                let (block, tx_id, ..) = runestone.value();
+
+                if block <= height {
+                    break;
+                }
+
+                deleted.push((id.value(), tx_id.to_owned()));
+            }
+
+            let mut txids = wtx.open_table(TXID_TO_RUNESTONE_ID)?;
+            for (id, tx_id) in deleted {
+                runestones.remove(id)?;
+                txids.remove(tx_id.as_str())?;
+            }
+
+            let mut edicts = wtx.open_table(ID_TO_EDICT)?;
+
+            let mut deleted = Vec::new();
+            for result in edicts.iter()?.rev() {
+                let (id, edict) = result?;
+                let (block, tx_id, _, rune_id, ..) = edict.value();
+
+                if block <= height {
+                    break;
+                }
+
+                deleted.push((id.value(), tx_id.to_owned(), rune_id.to_owned()));
+            }
+
+            let mut txids = wtx.open_multimap_table(TXID_TO_EDICT_ID)?;
+            let mut rune_ids = wtx.open_multimap_table(RUNE_ID_TO_EDICT_ID)?;
+            for (id, tx_id, rune_id) in deleted {
+                edicts.remove(id)?;
+                txids.remove(tx_id.as_str(), id)?;
+                rune_ids.remove(rune_id.as_str(), id)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::dao::{balance, outpoint, spend},
+        ordinals::inscription_id::txid,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn balances_of_an_outpoint_are_packed_together() {
+        let dir = TempDir::new().unwrap();
+        let mut store = RedbStore::open(&dir.path().join("index.redb")).unwrap();
+
+        let a = outpoint(Txid::all_zeros(), 0);
+        let b = outpoint(Txid::all_zeros(), 1);
+
+        store
+            .store_balances(&[
+                RuneBalanceEntity {
+                    block: 840_000,
+                    ..balance(a, "840000:1", 100)
+                },
+                RuneBalanceEntity {
+                    block: 840_000,
+                    ..balance(a, "840000:2", u128::MAX)
+                },
+                RuneBalanceEntity {
+                    block: 840_001,
+                    ..balance(b, "840000:1", 5)
+                },
+            ])
+            .unwrap();
+
+        let a = a.to_string();
+        let b = b.to_string();
+
+        let amounts = |store: &mut RedbStore| {
+            store
+                .load_unspent_by_address("bc1qfoo")
+                .unwrap()
+                .into_iter()
+                .map(|balance| (balance.out_point, balance.rune_id, balance.amount))
+                .collect::<Vec<(String, String, BigDecimal)>>()
+        };
+
+        assert_eq!(
+            amounts(&mut store),
+            [
+                (a.clone(), "840000:1".into(), BigDecimal::from(100)),
+                (a.clone(), "840000:2".into(), BigDecimal::from(u128::MAX)),
+                (b.clone(), "840000:1".into(), BigDecimal::from(5)),
+            ]
+        );
+
+        let spend = spend(outpoint(Txid::all_zeros(), 0), txid(1), 1, 840_002);
+
+        store.updates_spend_out_point(&[spend]).unwrap();
+        assert!(store.updates_spend_out_point(&[spend]).is_err());
+        assert_eq!(amounts(&mut store).len(), 1);
+
+        let spent = store.load_spent_by_tx(&spend.txid).unwrap();
+        assert_eq!(spent.len(), 2);
+        for balance in spent {
+            assert_eq!(balance.out_point, a);
+            assert_eq!(balance.spent_txid, Some(spend.txid.to_string()));
+            assert_eq!(balance.spent_vin, Some(1));
+            assert_eq!(balance.spent_block, Some(840_002));
+        }
+
+        assert_eq!(store.load_addresses_after(840_001).unwrap(), ["bc1qfoo"]);
+        store.delete_balances_after(840_000).unwrap();
+        assert!(store.load_by_outpoints(vec![b]).unwrap().is_empty());
+
+        store.unspend_out_points_after(840_000).unwrap();
+        assert!(store.load_addresses_after(840_000).unwrap().is_empty());
+        assert!(store.load_spent_by_tx(&spend.txid).unwrap().is_empty());
+        assert_eq!(amounts(&mut store).len(), 2);
+    }
+
+    #[test]
+    fn migrate_refuses_other_schema_versions() {
+        let dir = TempDir::new().unwrap();
+        let mut store = RedbStore::open(&dir.path().join("index.redb")).unwrap();
+
+        store.migrate().unwrap();
+        store.migrate().unwrap();
+
+        store
+            .write(|wtx| {
+                wtx.open_table(METADATA)?
+                    .insert("schema_version", SCHEMA_VERSION + 1)?;
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(store.migrate().is_err());
+    }
+}
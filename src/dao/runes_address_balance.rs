@@ -0,0 +1,125 @@
+use super::*;
+
+impl RuneAddressBalanceDao for MysqlConnection {
+    fn load_address_balances(
+        &mut self,
+        addresses: Vec<String>,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        use self::schema::rune_address_balance::address;
+        let results = RuneAddressBalanceTable
+            .filter(address.eq_any(addresses))
+            .select(RuneAddressBalanceEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(entities) => Ok(entities),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_balances_by_address(
+        &mut self,
+        _address: &str,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        use self::schema::rune_address_balance::{address, rune_id};
+        let results = RuneAddressBalanceTable
+            .filter(address.eq(_address))
+            .order(rune_id.asc())
+            .select(RuneAddressBalanceEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(entities) => Ok(entities),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_top_holders(
+        &mut self,
+        id: &RuneId,
+        limit: i64,
+    ) -> Result<Vec<RuneAddressBalanceEntity>> {
+        use self::schema::rune_address_balance::{amount, rune_id};
+        let results = RuneAddressBalanceTable
+            .filter(rune_id.eq(id.to_string()))
+            .order(amount.desc())
+            .limit(limit)
+            .select(RuneAddressBalanceEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(entities) => Ok(entities),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn count_holders(&mut self, id: &RuneId) -> Result<u64> {
+        use self::schema::rune_address_balance::rune_id;
+        let count = RuneAddressBalanceTable
+            .filter(rune_id.eq(id.to_string()))
+            .count()
+            .get_result::<i64>(self)?;
+
+        Ok(count.try_into()?)
+    }
+
+    fn store_address_balances(&mut self, entities: &[RuneAddressBalanceEntity]) -> Result<()> {
+        let insert_rows = diesel::replace_into(RuneAddressBalanceTable)
+            .values(entities)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_address_balances failed"));
+        }
+
+        Ok(())
+    }
+
+    fn delete_address_balances(&mut self, ids: Vec<u64>) -> Result<()> {
+        use self::schema::rune_address_balance::id;
+        diesel::delete(RuneAddressBalanceTable.filter(id.eq_any(ids))).execute(self)?;
+
+        Ok(())
+    }
+
+    // recompute the holdings of `addresses` from their unspent outputs, used
+    // when a rollback touches balances out of order
+    fn rebuild_address_balances(&mut self, addresses: Vec<String>) -> Result<()> {
+        use self::schema::rune_balance::{address, amount, rune_id, spent};
+        use diesel::dsl::{count_star, sum};
+
+        let rows = RuneBalanceTable
+            .filter(spent.eq(false))
+            .filter(address.eq_any(&addresses))
+            .group_by((address, rune_id))
+            .select((address, rune_id, sum(amount), count_star()))
+            .load::<(String, String, Option<BigDecimal>, i64)>(self)?;
+
+        diesel::delete(
+            RuneAddressBalanceTable
+                .filter(self::schema::rune_address_balance::address.eq_any(&addresses)),
+        )
+        .execute(self)?;
+
+        let entities = rows
+            .into_iter()
+            .map(|(_address, _rune_id, _amount, utxo_count)| {
+                Ok(RuneAddressBalanceEntity {
+                    id: 0,
+                    address: _address,
+                    rune_id: _rune_id,
+                    amount: _amount.unwrap_or_default(),
+                    utxo_count: utxo_count.try_into()?,
+                })
+            })
+            .collect::<Result<Vec<RuneAddressBalanceEntity>>>()?;
+
+        if !entities.is_empty() {
+            diesel::insert_into(RuneAddressBalanceTable)
+                .values(&entities)
+                .execute(self)?;
+        }
+
+        Ok(())
+    }
+}
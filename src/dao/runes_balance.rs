@@ -1,15 +1,18 @@
-use super::*;
+use {
+    super::*,
+    diesel::{
+        mysql::Mysql,
+        sql_types::{BigInt, Integer, Text, Unsigned},
+    },
+};
 
-impl RuneBlanaceDao for RuneMysqlDao {
-    fn load_by_outpoints(
-        conn: &mut MysqlConnection,
-        outpoints: Vec<String>,
-    ) -> Result<Vec<RuneBalanceEntity>> {
+impl RuneBlanaceDao for MysqlConnection {
+    fn load_by_outpoints(&mut self, outpoints: Vec<String>) -> Result<Vec<RuneBalanceEntity>> {
         use self::schema::rune_balance::out_point;
         let results = RuneBalanceTable
             .filter(out_point.eq_any(outpoints))
             .select(RuneBalanceEntity::as_select())
-            .load(conn);
+            .load(self);
 
         match results {
             Ok(events) => Ok(events),
@@ -17,15 +20,12 @@ impl RuneBlanaceDao for RuneMysqlDao {
         }
     }
 
-    fn load_by_outpoint(
-        conn: &mut MysqlConnection,
-        outpoint: &OutPoint,
-    ) -> Result<Vec<RuneBalanceEntity>> {
+    fn load_by_outpoint(&mut self, outpoint: &OutPoint) -> Result<Vec<RuneBalanceEntity>> {
         use self::schema::rune_balance::out_point;
         let results = RuneBalanceTable
             .filter(out_point.eq(outpoint.to_string()))
             .select(RuneBalanceEntity::as_select())
-            .load(conn);
+            .load(self);
 
         match results {
             Ok(events) => Ok(events),
@@ -33,43 +33,52 @@ impl RuneBlanaceDao for RuneMysqlDao {
         }
     }
 
-    fn updates_spend_out_point(conn: &mut MysqlConnection, outpoints: Vec<String>) -> Result {
+    fn updates_spend_out_point(&mut self, spends: &[Spend]) -> Result {
         use self::schema::rune_balance::{out_point, spent};
 
-        let effect_rows =
-            diesel::update(RuneBalanceTable.filter(out_point.eq_any(outpoints)))
-                .set(spent.eq(true))
-                .execute(conn)
-                .expect("Error update rune entry");
+        // every spend has its own input, so their values are picked per
+        // outpoint by a single update
+        for chunk in spends.chunks(SPEND_BATCH_SIZE) {
+            let unspent = RuneBalanceTable
+                .filter(out_point.eq_any(chunk.iter().map(|spend| spend.outpoint.to_string())))
+                .filter(spent.eq(false))
+                .select(out_point)
+                .load::<String>(self)?;
 
-        if effect_rows == 0 {
-            return Err(anyhow!("update_spend_out_point failed"));
-        }
-
-        Ok(())
-    }
+            ensure_unspent(chunk, unspent.into_iter().collect())?;
 
-    fn update_spend_out_point(conn: &mut MysqlConnection, outpoint: &OutPoint) -> Result {
-        use self::schema::rune_balance::{out_point, spent};
+            let mut query = diesel::sql_query(spends_update_sql(chunk.len(), |_| "?".into()))
+                .into_boxed::<Mysql>();
 
-        let effect_rows =
-            diesel::update(RuneBalanceTable.filter(out_point.eq(outpoint.to_string())))
-                .set(spent.eq(true))
-                .execute(conn)
-                .expect("Error update rune entry");
+            for spend in chunk {
+                query = query
+                    .bind::<Text, _>(spend.outpoint.to_string())
+                    .bind::<Text, _>(spend.txid.to_string());
+            }
+            for spend in chunk {
+                query = query
+                    .bind::<Text, _>(spend.outpoint.to_string())
+                    .bind::<Unsigned<Integer>, _>(spend.vin);
+            }
+            for spend in chunk {
+                query = query
+                    .bind::<Text, _>(spend.outpoint.to_string())
+                    .bind::<Unsigned<BigInt>, _>(spend.block);
+            }
+            for spend in chunk {
+                query = query.bind::<Text, _>(spend.outpoint.to_string());
+            }
 
-        if effect_rows == 0 {
-            return Err(anyhow!("update_spend_out_point failed"));
+            query.execute(self)?;
         }
 
         Ok(())
     }
 
-    fn store_balances(conn: &mut MysqlConnection, entry: &Vec<RuneBalanceEntity>) -> Result<()> {
+    fn store_balances(&mut self, entry: &[RuneBalanceEntity]) -> Result<()> {
         let insert_rows = diesel::insert_into(RuneBalanceTable)
             .values(entry)
-            .execute(conn)
-            .unwrap();
+            .execute(self)?;
 
         if insert_rows == 0 {
             return Err(anyhow!("store_balances failed"));
@@ -77,4 +86,92 @@ impl RuneBlanaceDao for RuneMysqlDao {
 
         Ok(())
     }
+
+    fn unspend_out_points_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_balance::{spent, spent_block, spent_txid, spent_vin};
+
+        diesel::update(RuneBalanceTable.filter(spent_block.gt(height)))
+            .set((
+                spent.eq(false),
+                spent_txid.eq(None::<String>),
+                spent_vin.eq(None::<u32>),
+                spent_block.eq(None::<u64>),
+            ))
+            .execute(self)?;
+
+        Ok(())
+    }
+
+    fn unspend_unrecorded_out_points(&mut self, outpoints: Vec<String>) -> Result<()> {
+        use self::schema::rune_balance::{out_point, spent, spent_block};
+
+        diesel::update(
+            RuneBalanceTable
+                .filter(out_point.eq_any(outpoints))
+                .filter(spent_block.is_null()),
+        )
+        .set(spent.eq(false))
+        .execute(self)?;
+
+        Ok(())
+    }
+
+    fn has_unrecorded_spends(&mut self) -> Result<bool> {
+        use self::schema::rune_balance::{spent, spent_block};
+
+        let exists = diesel::select(diesel::dsl::exists(
+            RuneBalanceTable
+                .filter(spent.eq(true))
+                .filter(spent_block.is_null()),
+        ))
+        .get_result(self)?;
+
+        Ok(exists)
+    }
+
+    fn delete_balances_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_balance::block;
+        diesel::delete(RuneBalanceTable.filter(block.gt(height))).execute(self)?;
+
+        Ok(())
+    }
+
+    fn load_addresses_after(&mut self, height: u64) -> Result<Vec<String>> {
+        use self::schema::rune_balance::{address, block, spent_block};
+        let results = RuneBalanceTable
+            .filter(block.gt(height).or(spent_block.gt(height)))
+            .select(address)
+            .distinct()
+            .load::<String>(self)?;
+
+        Ok(results)
+    }
+
+    fn load_unspent_by_address(&mut self, _address: &str) -> Result<Vec<RuneBalanceEntity>> {
+        use self::schema::rune_balance::{address, spent};
+        let results = RuneBalanceTable
+            .filter(address.eq(_address))
+            .filter(spent.eq(false))
+            .select(RuneBalanceEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(balances) => Ok(balances),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_spent_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneBalanceEntity>> {
+        use self::schema::rune_balance::{spent_txid, spent_vin};
+        let results = RuneBalanceTable
+            .filter(spent_txid.eq(txid.to_string()))
+            .order(spent_vin.asc())
+            .select(RuneBalanceEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(balances) => Ok(balances),
+            Err(e) => Err(e.into()),
+        }
+    }
 }
@@ -0,0 +1,40 @@
+use super::*;
+
+impl RuneBlockDao for MysqlConnection {
+    fn load_block_hash(&mut self, _height: u64) -> Result<Option<BlockHash>> {
+        use self::schema::rune_block::{block_hash, height};
+        let result = RuneBlockTable
+            .filter(height.eq(_height))
+            .select(block_hash)
+            .first::<String>(self)
+            .optional()?;
+
+        match result {
+            Some(hash) => Ok(Some(BlockHash::from_str(hash.as_str())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn store_block_header(&mut self, height: u64, header: &Header) -> Result<()> {
+        let entity = RuneBlockEntity {
+            height,
+            block_hash: header.block_hash().to_string(),
+        };
+        let insert_rows = diesel::replace_into(RuneBlockTable)
+            .values(&entity)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_block_header failed"));
+        }
+
+        Ok(())
+    }
+
+    fn delete_blocks_after(&mut self, _height: u64) -> Result<()> {
+        use self::schema::rune_block::height;
+        diesel::delete(RuneBlockTable.filter(height.gt(_height))).execute(self)?;
+
+        Ok(())
+    }
+}
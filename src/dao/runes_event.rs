@@ -1,24 +1,10 @@
 use super::*;
 
-impl RuneEventDao for RuneMysqlDao {
-    fn load(conn: &mut MysqlConnection, _id: u64) -> Result<RuneEventEntity> {
-        use self::schema::rune_event::id;
-        let result = RuneEventTable
-            .filter(id.eq(_id))
-            .select(RuneEventEntity::as_select())
-            .first(conn);
-
-        match result {
-            Ok(entity) => Ok(entity),
-            Err(e) => Err(e.into()),
-        }
-    }
-
-    fn store_events(conn: &mut MysqlConnection, entity: &Vec<RuneEventEntity>) -> Result<()> {
+impl RuneEventDao for MysqlConnection {
+    fn store_events(&mut self, entity: &[RuneEventEntity]) -> Result<()> {
         let insert_rows = diesel::insert_into(RuneEventTable)
             .values(entity)
-            .execute(conn)
-            .unwrap();
+            .execute(self)?;
 
         if insert_rows == 0 {
             return Err(anyhow!("store_events failed"));
@@ -27,16 +13,79 @@ impl RuneEventDao for RuneMysqlDao {
         Ok(())
     }
 
-    fn delete(conn: &mut MysqlConnection, id: &Txid) -> Result<()> {
-        use crate::schema::rune_event::tx_id;
-        let effect_rows = diesel::delete(RuneEventTable.filter(tx_id.eq(id.to_string())))
-            .execute(conn)
-            .expect("Error deleting rune entry");
+    fn load_events_after(&mut self, height: u64) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::block;
+        let results = RuneEventTable
+            .filter(block.gt(height))
+            .select(RuneEventEntity::as_select())
+            .load(self);
 
-        if effect_rows == 0 {
-            return Err(anyhow!("delete event failed"));
+        match results {
+            Ok(events) => Ok(events),
+            Err(e) => Err(e.into()),
         }
+    }
+
+    fn delete_events_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_event::block;
+        diesel::delete(RuneEventTable.filter(block.gt(height))).execute(self)?;
 
         Ok(())
     }
+
+    fn load_events_by_tx(&mut self, txid: &Txid) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::{id, tx_id};
+        let results = RuneEventTable
+            .filter(tx_id.eq(txid.to_string()))
+            .order(id.asc())
+            .select(RuneEventEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(events) => Ok(events),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_events_by_rune(
+        &mut self,
+        _rune_id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::{id, rune_id};
+        let results = RuneEventTable
+            .filter(rune_id.eq(_rune_id.to_string()))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RuneEventEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(events) => Ok(events),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_events_by_address(
+        &mut self,
+        _address: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RuneEventEntity>> {
+        use self::schema::rune_event::{address, id};
+        let results = RuneEventTable
+            .filter(address.eq(_address))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RuneEventEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(events) => Ok(events),
+            Err(e) => Err(e.into()),
+        }
+    }
 }
@@ -0,0 +1,35 @@
+use super::*;
+
+impl RuneTxDao for MysqlConnection {
+    fn store_txs(&mut self, entities: &[RuneTxEntity]) -> Result<()> {
+        let insert_rows = diesel::insert_into(RuneTxTable)
+            .values(entities)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_txs failed"));
+        }
+
+        Ok(())
+    }
+
+    fn load_txs(&mut self, txids: Vec<String>) -> Result<Vec<RuneTxEntity>> {
+        use self::schema::rune_tx::tx_id;
+        let results = RuneTxTable
+            .filter(tx_id.eq_any(txids))
+            .select(RuneTxEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(entities) => Ok(entities),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete_txs_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::rune_tx::block;
+        diesel::delete(RuneTxTable.filter(block.gt(height))).execute(self)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use {super::*, ordinals::Cenotaph};
+
+/// Flatten the artifact of `txid` into its runestone row and one row per
+/// edict. Edicts of `0:0` are stored with the ID of the rune `etched` by the
+/// transaction, so they are found with the other edicts of that rune, and
+/// keep `0:0` if the etching failed.
+pub(crate) fn convert_artifact_to_model(
+    block: u64,
+    txid: &Txid,
+    artifact: &Artifact,
+    etched: Option<RuneId>,
+) -> Result<(RunestoneEntity, Vec<RunestoneEdictEntity>)> {
+    let mut entity = RunestoneEntity {
+        id: 0,
+        block,
+        tx_id: txid.to_string(),
+        cenotaph: false,
+        flaw: None,
+        pointer: None,
+        mint: artifact.mint().map(|id| id.to_string()),
+        etching: false,
+        rune: None,
+        spacers: None,
+        divisibility: None,
+        premine: None,
+        symbol: None,
+        turbo: false,
+        terms: false,
+        amount: None,
+        cap: None,
+        height_start: None,
+        height_end: None,
+        offset_start: None,
+        offset_end: None,
+    };
+
+    let runestone = match artifact {
+        Artifact::Cenotaph(cenotaph) => {
+            entity.cenotaph = true;
+            entity.flaw = cenotaph.flaw.map(flaw_name).transpose()?;
+            entity.etching = cenotaph.etching.is_some();
+            entity.rune = cenotaph.etching.map(|rune| BigDecimal::from(rune.0));
+            return Ok((entity, Vec::new()));
+        }
+        Artifact::Runestone(runestone) => runestone,
+    };
+
+    entity.pointer = runestone.pointer;
+
+    if let Some(etching) = runestone.etching {
+        entity.etching = true;
+        entity.rune = etching.rune.map(|rune| BigDecimal::from(rune.0));
+        entity.spacers = etching.spacers;
+        entity.divisibility = etching.divisibility;
+        entity.premine = etching.premine.map(BigDecimal::from);
+        entity.symbol = etching.symbol.map(|symbol| symbol.to_string());
+        entity.turbo = etching.turbo;
+
+        if let Some(terms) = etching.terms {
+            entity.terms = true;
+            entity.amount = terms.amount.map(BigDecimal::from);
+            entity.cap = terms.cap.map(BigDecimal::from);
+            entity.height_start = terms.height.0;
+            entity.height_end = terms.height.1;
+            entity.offset_start = terms.offset.0;
+            entity.offset_end = terms.offset.1;
+        }
+    }
+
+    let edicts = runestone
+        .edicts
+        .iter()
+        .zip(0..)
+        .map(|(edict, edict_index)| RunestoneEdictEntity {
+            id: 0,
+            block,
+            tx_id: entity.tx_id.clone(),
+            edict_index,
+            rune_id: if edict.id == RuneId::default() {
+                etched.unwrap_or_default()
+            } else {
+                edict.id
+            }
+            .to_string(),
+            amount: BigDecimal::from(edict.amount),
+            output: edict.output,
+        })
+        .collect();
+
+    Ok((entity, edicts))
+}
+
+/// Rebuild the artifact stored by `convert_artifact_to_model`. `edicts` must
+/// be ordered by `edict_index`, and edicts of the rune `etched` by the
+/// transaction are rebuilt as `0:0`.
+pub(crate) fn convert_model_to_artifact(
+    entity: &RunestoneEntity,
+    edicts: &[RunestoneEdictEntity],
+    etched: Option<RuneId>,
+) -> Result<Artifact> {
+    let rune = entity
+        .rune
+        .as_ref()
+        .map(|rune| {
+            decimal_to_u128(rune)
+                .map(Rune)
+                .with_context(|| format!("invalid rune in runestone of {}", entity.tx_id))
+        })
+        .transpose()?;
+    let mint = entity.mint.as_deref().map(RuneId::from_str).transpose()?;
+
+    if entity.cenotaph {
+        return Ok(Artifact::Cenotaph(Cenotaph {
+            etching: rune,
+            flaw: entity.flaw.as_deref().map(parse_flaw).transpose()?,
+            mint,
+        }));
+    }
+
+    let u128_column = |value: &Option<BigDecimal>, column: &str| {
+        value
+            .as_ref()
+            .map(|value| {
+                decimal_to_u128(value)
+                    .with_context(|| format!("invalid {column} in runestone of {}", entity.tx_id))
+            })
+            .transpose()
+    };
+
+    let etching = if entity.etching {
+        Some(Etching {
+            divisibility: entity.divisibility,
+            premine: u128_column(&entity.premine, "premine")?,
+            rune,
+            spacers: entity.spacers,
+            symbol: entity
+                .symbol
+                .as_deref()
+                .and_then(|symbol| symbol.chars().next()),
+            terms: if entity.terms {
+                Some(Terms {
+                    amount: u128_column(&entity.amount, "amount")?,
+                    cap: u128_column(&entity.cap, "cap")?,
+                    height: (entity.height_start, entity.height_end),
+                    offset: (entity.offset_start, entity.offset_end),
+                })
+            } else {
+                None
+            },
+            turbo: entity.turbo,
+        })
+    } else {
+        None
+    };
+
+    let edicts = edicts
+        .iter()
+        .map(|edict| {
+            let id = RuneId::from_str(&edict.rune_id)?;
+
+            Ok(Edict {
+                id: if Some(id) == etched {
+                    RuneId::default()
+                } else {
+                    id
+                },
+                amount: decimal_to_u128(&edict.amount)
+                    .with_context(|| format!("invalid edict amount in {}", edict.tx_id))?,
+                output: edict.output,
+            })
+        })
+        .collect::<Result<Vec<Edict>>>()?;
+
+    Ok(Artifact::Runestone(Runestone {
+        edicts,
+        etching,
+        mint,
+        pointer: entity.pointer,
+    }))
+}
+
+/// Flaws are stored by their kebab-case serde name, e.g. `edict-output`.
+pub(crate) fn flaw_name(flaw: Flaw) -> Result<String> {
+    match serde_json::to_value(flaw)? {
+        serde_json::Value::String(name) => Ok(name),
+        value => Err(anyhow!("unexpected flaw encoding {value}")),
+    }
+}
+
+pub(crate) fn parse_flaw(name: &str) -> Result<Flaw> {
+    Ok(serde_json::from_value(serde_json::Value::String(
+        name.into(),
+    ))?)
+}
+
+impl RunestoneDao for MysqlConnection {
+    fn store_runestones(&mut self, entities: &[RunestoneEntity]) -> Result<()> {
+        let insert_rows = diesel::insert_into(RunestoneTable)
+            .values(entities)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_runestones failed"));
+        }
+
+        Ok(())
+    }
+
+    fn store_edicts(&mut self, entities: &[RunestoneEdictEntity]) -> Result<()> {
+        let insert_rows = diesel::insert_into(RunestoneEdictTable)
+            .values(entities)
+            .execute(self)?;
+
+        if insert_rows == 0 {
+            return Err(anyhow!("store_edicts failed"));
+        }
+
+        Ok(())
+    }
+
+    fn load_runestone(
+        &mut self,
+        txid: &Txid,
+    ) -> Result<Option<(RunestoneEntity, Vec<RunestoneEdictEntity>)>> {
+        use self::schema::{runestone, runestone_edict};
+
+        let Some(entity) = RunestoneTable
+            .filter(runestone::tx_id.eq(txid.to_string()))
+            .select(RunestoneEntity::as_select())
+            .first(self)
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let results = RunestoneEdictTable
+            .filter(runestone_edict::tx_id.eq(txid.to_string()))
+            .order(runestone_edict::edict_index.asc())
+            .select(RunestoneEdictEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(edicts) => Ok(Some((entity, edicts))),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_edicts_by_rune(
+        &mut self,
+        _rune_id: &RuneId,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEdictEntity>> {
+        use self::schema::runestone_edict::{id, rune_id};
+        let results = RunestoneEdictTable
+            .filter(rune_id.eq(_rune_id.to_string()))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RunestoneEdictEntity::as_select())
+            .load(self);
+
+        match results {
+            Ok(edicts) => Ok(edicts),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn load_cenotaphs(
+        &mut self,
+        _flaw: Option<Flaw>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<RunestoneEntity>> {
+        use self::schema::runestone::{cenotaph, flaw, id};
+        let mut query = RunestoneTable
+            .filter(cenotaph.eq(true))
+            .order(id.desc())
+            .offset(offset)
+            .limit(limit)
+            .select(RunestoneEntity::as_select())
+            .into_boxed();
+
+        if let Some(_flaw) = _flaw {
+            query = query.filter(flaw.eq(flaw_name(_flaw)?));
+        }
+
+        match query.load(self) {
+            Ok(entities) => Ok(entities),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn delete_runestones_after(&mut self, height: u64) -> Result<()> {
+        use self::schema::{runestone, runestone_edict};
+        diesel::delete(RunestoneEdictTable.filter(runestone_edict::block.gt(height)))
+            .execute(self)?;
+        diesel::delete(RunestoneTable.filter(runestone::block.gt(height))).execute(self)?;
+
+        Ok(())
+    }
+}
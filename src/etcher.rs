@@ -0,0 +1,896 @@
+use {
+    super::*,
+    crate::{
+        dao::{decimal_to_u128, RuneStore},
+        model::EtchingEntity,
+    },
+    bitcoin::{
+        key::{KeyPair, TapTweak, XOnlyPublicKey},
+        opcodes,
+        secp256k1::{self, Message, Secp256k1},
+        sighash::{Prevouts, SighashCache, TapSighashType},
+        taproot::{self, ControlBlock, LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo},
+    },
+    bitcoincore_rpc::json::FundRawTransactionOptions,
+};
+
+/// Largest OP_RETURN output relayed by default.
+const MAX_RUNESTONE_SIZE: usize = 83;
+
+/// Step of an etching. Each transaction is stored before it is broadcast, so
+/// an interrupted etching resumes by broadcasting it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum EtchingState {
+    /// commit transaction signed
+    Commit = 0,
+    /// commit transaction broadcast, waiting for it to mature
+    Committed = 1,
+    /// reveal transaction signed
+    Reveal = 2,
+    /// reveal transaction broadcast, waiting for it to confirm
+    Revealed = 3,
+    /// reveal transaction confirmed
+    Etched = 4,
+    /// commit output sent back to the destination instead of revealed
+    Recovered = 5,
+}
+
+impl EtchingState {
+    pub(crate) const UNFINISHED: [Self; 4] =
+        [Self::Commit, Self::Committed, Self::Reveal, Self::Revealed];
+
+    fn is_finished(self) -> bool {
+        !Self::UNFINISHED.contains(&self)
+    }
+}
+
+impl TryFrom<u8> for EtchingState {
+    type Error = Error;
+
+    fn try_from(state: u8) -> Result<Self> {
+        Ok(match state {
+            0 => Self::Commit,
+            1 => Self::Committed,
+            2 => Self::Reveal,
+            3 => Self::Revealed,
+            4 => Self::Etched,
+            5 => Self::Recovered,
+            _ => bail!("invalid etching state {state}"),
+        })
+    }
+}
+
+/// Etching as shown to users, without its commit key.
+#[derive(Debug, Serialize)]
+pub(crate) struct EtchingOutput {
+    pub(crate) id: u64,
+    pub(crate) rune: SpacedRune,
+    pub(crate) state: EtchingState,
+    pub(crate) destination: String,
+    pub(crate) commit: Txid,
+    pub(crate) reveal: Option<Txid>,
+    /// output of the reveal transaction receiving the premine
+    pub(crate) output: Option<OutPoint>,
+}
+
+/// Rune and parameters of a new etching.
+#[derive(Debug, Clone)]
+pub(crate) struct EtchingRequest {
+    pub(crate) etching: Etching,
+    pub(crate) destination: Address,
+    /// in sats per vbyte
+    pub(crate) fee_rate: u64,
+    /// value of the output receiving the premine
+    pub(crate) postage: Amount,
+}
+
+/// Taproot output committing to a rune, spendable by revealing the
+/// commitment in a script path spend, or by the key path to recover it.
+struct Commitment {
+    key: KeyPair,
+    script: ScriptBuf,
+    spend_info: TaprootSpendInfo,
+}
+
+impl Commitment {
+    fn new(key: KeyPair, rune: Rune) -> Result<Self> {
+        let secp = Secp256k1::new();
+        let (public_key, _parity) = XOnlyPublicKey::from_keypair(&key);
+
+        let commitment = rune.commitment();
+
+        let script = script::Builder::new()
+            .push_slice(public_key.serialize())
+            .push_opcode(opcodes::all::OP_CHECKSIG)
+            .push_opcode(opcodes::OP_FALSE)
+            .push_opcode(opcodes::all::OP_IF)
+            .push_slice::<&script::PushBytes>(commitment.as_slice().try_into()?)
+            .push_opcode(opcodes::all::OP_ENDIF)
+            .into_script();
+
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, script.clone())
+            .map_err(|err| anyhow!("failed to build commitment: {err}"))?
+            .finalize(&secp, public_key)
+            .map_err(|_| anyhow!("failed to finalize commitment"))?;
+
+        Ok(Self {
+            key,
+            script,
+            spend_info,
+        })
+    }
+
+    fn script_pubkey(&self) -> ScriptBuf {
+        ScriptBuf::new_v1_p2tr_tweaked(self.spend_info.output_key())
+    }
+
+    fn control_block(&self) -> Result<ControlBlock> {
+        self.spend_info
+            .control_block(&(self.script.clone(), LeafVersion::TapScript))
+            .ok_or_else(|| anyhow!("commitment script missing from taproot tree"))
+    }
+
+    /// Transaction spending `commit` to reveal `runestone`, sending
+    /// `postage` to `destination`. It can't be mined before the commit
+    /// output has the confirmations etchings require.
+    fn reveal(
+        &self,
+        commit: OutPoint,
+        output: &TxOut,
+        runestone: &Runestone,
+        destination: ScriptBuf,
+        postage: Amount,
+    ) -> Result<Transaction> {
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: commit,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::from_height(Runestone::COMMIT_CONFIRMATIONS - 1),
+                witness: Witness::new(),
+            }],
+            output: vec![
+                TxOut {
+                    value: postage.to_sat(),
+                    script_pubkey: destination,
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey: runestone.encipher(),
+                },
+            ],
+        };
+
+        let sighash = SighashCache::new(&tx).taproot_script_spend_signature_hash(
+            0,
+            &Prevouts::All(&[output]),
+            TapLeafHash::from_script(&self.script, LeafVersion::TapScript),
+            TapSighashType::Default,
+        )?;
+
+        let signature = Secp256k1::new()
+            .sign_schnorr(&Message::from_slice(sighash.as_byte_array())?, &self.key);
+
+        let mut witness = Witness::new();
+        witness.push(
+            taproot::Signature {
+                sig: signature,
+                hash_ty: TapSighashType::Default,
+            }
+            .to_vec(),
+        );
+        witness.push(self.script.as_bytes());
+        witness.push(self.control_block()?.serialize());
+        tx.input[0].witness = witness;
+
+        Ok(tx)
+    }
+
+    /// Transaction sending the value of `commit`, less the fee, to
+    /// `destination` with a key path spend.
+    fn recover(
+        &self,
+        commit: OutPoint,
+        output: &TxOut,
+        destination: ScriptBuf,
+        fee_rate: u64,
+    ) -> Result<Transaction> {
+        let secp = Secp256k1::new();
+
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: commit,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::from_slice(&[[0; 64]]),
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: destination,
+            }],
+        };
+
+        let fee = u64::try_from(tx.vsize())? * fee_rate;
+        tx.output[0].value = output
+            .value
+            .checked_sub(fee)
+            .filter(|value| *value >= tx.output[0].script_pubkey.dust_value().to_sat())
+            .ok_or_else(|| anyhow!("commit output is too small to recover"))?;
+
+        let sighash = SighashCache::new(&tx).taproot_key_spend_signature_hash(
+            0,
+            &Prevouts::All(&[output]),
+            TapSighashType::Default,
+        )?;
+
+        let key = self
+            .key
+            .tap_tweak(&secp, self.spend_info.merkle_root())
+            .to_inner();
+
+        let signature = secp.sign_schnorr(&Message::from_slice(sighash.as_byte_array())?, &key);
+
+        tx.input[0].witness = Witness::from_slice(&[signature.as_ref()]);
+
+        Ok(tx)
+    }
+}
+
+/// Etches runes with a commit transaction funded and signed by the node's
+/// wallet, and a reveal transaction signed with a key generated for each
+/// etching. Every step is stored in the `etching` table before it is taken.
+pub(crate) struct Etcher<'a> {
+    client: &'a Client,
+    store: &'a mut dyn RuneStore,
+    network: Network,
+}
+
+impl<'a> Etcher<'a> {
+    pub(crate) fn new(client: &'a Client, store: &'a mut dyn RuneStore, network: Network) -> Self {
+        Self {
+            client,
+            store,
+            network,
+        }
+    }
+
+    /// Sign and broadcast the commit transaction of a new etching.
+    pub(crate) fn etch(&mut self, request: EtchingRequest) -> Result<EtchingOutput> {
+        let rune = request
+            .etching
+            .rune
+            .ok_or_else(|| anyhow!("etching has no rune"))?;
+
+        ensure!(!rune.is_reserved(), "rune {rune} is reserved");
+        ensure!(request.fee_rate > 0, "fee rate must be at least 1 sat/vB");
+        // an etching whose supply overflows is a cenotaph, burning the premine
+        ensure!(
+            request.etching.supply().is_some(),
+            "supply of rune {rune} overflows"
+        );
+        ensure!(
+            request.postage >= request.destination.script_pubkey().dust_value(),
+            "postage is below the dust limit"
+        );
+
+        // the reveal is mined once the commit output matures, at the earliest
+        let height = self.client.get_block_count()? + u64::from(Runestone::COMMIT_CONFIRMATIONS);
+        let minimum = Rune::minimum_at_height(self.network, Height(height.try_into()?));
+        ensure!(
+            rune >= minimum,
+            "rune {rune} is not unlocked, the shortest rune at height {height} is {minimum}"
+        );
+
+        ensure!(
+            self.store.gets_rune_entry_by_runes(vec![rune])?.is_empty(),
+            "rune {rune} has already been etched"
+        );
+
+        let runestone = Runestone {
+            etching: Some(request.etching),
+            pointer: Some(0),
+            ..default()
+        };
+
+        ensure!(
+            runestone.encipher().len() <= MAX_RUNESTONE_SIZE,
+            "runestone is larger than {MAX_RUNESTONE_SIZE} bytes"
+        );
+
+        let commitment = Commitment::new(
+            KeyPair::new(&Secp256k1::new(), &mut secp256k1::rand::thread_rng()),
+            rune,
+        )?;
+
+        // sign a placeholder reveal to size it
+        let placeholder = commitment.reveal(
+            OutPoint::null(),
+            &TxOut {
+                value: 0,
+                script_pubkey: commitment.script_pubkey(),
+            },
+            &runestone,
+            request.destination.script_pubkey(),
+            request.postage,
+        )?;
+        let reveal_fee = u64::try_from(placeholder.vsize())? * request.fee_rate;
+
+        ensure!(
+            Runestone::decipher(&placeholder) == Some(Artifact::Runestone(runestone.clone())),
+            "reveal transaction does not decipher to the etching runestone"
+        );
+
+        let commit = self.commit(
+            &commitment,
+            request.postage.to_sat() + reveal_fee,
+            request.fee_rate,
+        )?;
+
+        let mut entity = EtchingEntity {
+            id: 0,
+            rune: BigDecimal::from(rune.n()),
+            state: EtchingState::Commit as u8,
+            destination: request.destination.to_string(),
+            commit_key: commitment.key.display_secret().to_string(),
+            runestone: serde_json::to_string(&runestone)?,
+            fee_rate: request.fee_rate,
+            postage: request.postage.to_sat(),
+            commit_tx_id: commit.txid().to_string(),
+            commit_tx: consensus::encode::serialize_hex(&commit),
+            reveal_tx_id: String::new(),
+            reveal_tx: String::new(),
+            inscription_output: String::new(),
+        };
+
+        entity.id = self.store.store_etching(&entity)?;
+
+        self.advance(entity)
+    }
+
+    /// Take every step of unfinished etchings that can be taken now.
+    pub(crate) fn resume(&mut self) -> Result<Vec<EtchingOutput>> {
+        let states = EtchingState::UNFINISHED
+            .iter()
+            .map(|state| *state as u8)
+            .collect();
+
+        self.store
+            .load_etchings_in_states(states)?
+            .into_iter()
+            .map(|entity| {
+                let id = entity.id;
+                self.advance(entity)
+                    .with_context(|| format!("failed to resume etching {id}"))
+            })
+            .collect()
+    }
+
+    /// Send the commit output of an etching that has not been revealed back
+    /// to its destination, for when the rune can no longer be etched.
+    pub(crate) fn recover(&mut self, id: u64) -> Result<EtchingOutput> {
+        let mut entity = self.load(id)?;
+
+        let state = EtchingState::try_from(entity.state)?;
+        ensure!(
+            matches!(state, EtchingState::Commit | EtchingState::Committed),
+            "etching {id} can't be recovered once revealed"
+        );
+
+        let commit = decode_tx(&entity.commit_tx)?;
+        let commitment = self.commitment(&entity)?;
+        let (outpoint, output) = Self::commit_output(&commit, &commitment)?;
+
+        ensure!(
+            self.client
+                .get_tx_out(&outpoint.txid, outpoint.vout, Some(true))?
+                .is_some(),
+            "commit output {outpoint} of etching {id} is spent or unknown"
+        );
+
+        let tx = commitment.recover(
+            outpoint,
+            &output,
+            self.destination(&entity)?.script_pubkey(),
+            entity.fee_rate,
+        )?;
+
+        entity.reveal_tx_id = tx.txid().to_string();
+        entity.reveal_tx = consensus::encode::serialize_hex(&tx);
+        entity.state = EtchingState::Recovered as u8;
+        self.store.update_etching(&entity)?;
+
+        self.broadcast(&tx)?;
+
+        self.output(&entity)
+    }
+
+    pub(crate) fn load(&mut self, id: u64) -> Result<EtchingEntity> {
+        self.store
+            .load_etching(id)?
+            .ok_or_else(|| anyhow!("etching {id} not found"))
+    }
+
+    pub(crate) fn output(&self, entity: &EtchingEntity) -> Result<EtchingOutput> {
+        let runestone = serde_json::from_str::<Runestone>(&entity.runestone)?;
+        let etching = runestone
+            .etching
+            .ok_or_else(|| anyhow!("etching {} has no etching", entity.id))?;
+
+        Ok(EtchingOutput {
+            id: entity.id,
+            rune: SpacedRune {
+                rune: etching
+                    .rune
+                    .ok_or_else(|| anyhow!("etching {} has no rune", entity.id))?,
+                spacers: etching.spacers.unwrap_or_default(),
+            },
+            state: entity.state.try_into()?,
+            destination: entity.destination.clone(),
+            commit: entity.commit_tx_id.parse()?,
+            reveal: (!entity.reveal_tx_id.is_empty())
+                .then(|| entity.reveal_tx_id.parse())
+                .transpose()?,
+            output: (!entity.inscription_output.is_empty())
+                .then(|| entity.inscription_output.parse())
+                .transpose()?,
+        })
+    }
+
+    // take steps until the etching finishes or has to wait for the chain
+    fn advance(&mut self, mut entity: EtchingEntity) -> Result<EtchingOutput> {
+        loop {
+            let state = EtchingState::try_from(entity.state)?;
+
+            let next = match state {
+                EtchingState::Commit => {
+                    let commit = decode_tx(&entity.commit_tx)?;
+                    self.broadcast(&commit)?;
+                    EtchingState::Committed
+                }
+                EtchingState::Committed => {
+                    if !self.reveal(&mut entity)? {
+                        break;
+                    }
+                    EtchingState::Reveal
+                }
+                EtchingState::Reveal => {
+                    let reveal = decode_tx(&entity.reveal_tx)?;
+                    self.broadcast(&reveal)?;
+                    EtchingState::Revealed
+                }
+                EtchingState::Revealed => {
+                    if !self.revealed(&entity)? {
+                        break;
+                    }
+                    EtchingState::Etched
+                }
+                EtchingState::Etched | EtchingState::Recovered => break,
+            };
+
+            entity.state = next as u8;
+
+            // the commit output is spent by the reveal, so its key is no
+            // longer needed
+            if next == EtchingState::Etched {
+                entity.commit_key.clear();
+            }
+
+            self.store.update_etching(&entity)?;
+
+            if next.is_finished() {
+                break;
+            }
+        }
+
+        self.output(&entity)
+    }
+
+    // fund, sign and return a transaction paying `value` to the commitment
+    fn commit(&self, commitment: &Commitment, value: u64, fee_rate: u64) -> Result<Transaction> {
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: vec![TxOut {
+                value,
+                script_pubkey: commitment.script_pubkey(),
+            }],
+        };
+
+        let funded = self.client.fund_raw_transaction(
+            &tx,
+            Some(&FundRawTransactionOptions {
+                fee_rate: Some(Amount::from_sat(fee_rate * 1000)),
+                ..default()
+            }),
+            Some(true),
+        )?;
+
+        let signed = self
+            .client
+            .sign_raw_transaction_with_wallet(&funded.hex, None, None)?;
+
+        ensure!(
+            signed.complete,
+            "failed to sign commit transaction: {:?}",
+            signed.errors
+        );
+
+        Ok(signed.transaction()?)
+    }
+
+    // sign the reveal once the commit output is mature, returning whether
+    // it was
+    fn reveal(&mut self, entity: &mut EtchingEntity) -> Result<bool> {
+        let commit = decode_tx(&entity.commit_tx)?;
+        let commitment = self.commitment(entity)?;
+        let (outpoint, output) = Self::commit_output(&commit, &commitment)?;
+
+        let Some(tx_out) = self
+            .client
+            .get_tx_out(&outpoint.txid, outpoint.vout, Some(true))?
+        else {
+            bail!(
+                "commit output {outpoint} of etching {} is spent or unknown",
+                entity.id
+            );
+        };
+
+        // the reveal is mined in a later block, in which the commit output
+        // has one more confirmation
+        if tx_out.confirmations + 1 < u32::from(Runestone::COMMIT_CONFIRMATIONS) {
+            return Ok(false);
+        }
+
+        let runestone = serde_json::from_str::<Runestone>(&entity.runestone)?;
+        let rune = runestone.etching.and_then(|etching| etching.rune);
+
+        // another etching of the rune may have been mined while the commit
+        // output matured, which would make the reveal etch nothing
+        if let Some(rune) = rune {
+            ensure!(
+                self.store.gets_rune_entry_by_runes(vec![rune])?.is_empty(),
+                "rune {rune} has been etched by another transaction, recover etching {} instead",
+                entity.id
+            );
+        }
+
+        let reveal = commitment.reveal(
+            outpoint,
+            &output,
+            &runestone,
+            self.destination(entity)?.script_pubkey(),
+            Amount::from_sat(entity.postage),
+        )?;
+
+        entity.reveal_tx_id = reveal.txid().to_string();
+        entity.reveal_tx = consensus::encode::serialize_hex(&reveal);
+        entity.inscription_output = OutPoint {
+            txid: reveal.txid(),
+            vout: 0,
+        }
+        .to_string();
+
+        Ok(true)
+    }
+
+    // whether the reveal has confirmed, broadcasting it again if it has
+    // dropped out of the mempool
+    fn revealed(&mut self, entity: &EtchingEntity) -> Result<bool> {
+        let reveal = decode_tx(&entity.reveal_tx)?;
+        let txid = reveal.txid();
+
+        if let Some(output) = self.client.get_tx_out(&txid, 0, Some(true))? {
+            return Ok(output.confirmations > 0);
+        }
+
+        // the output receiving the premine is spent or was never created, in
+        // which case the reveal is either mined or can be broadcast again
+        let runestone = serde_json::from_str::<Runestone>(&entity.runestone)?;
+        if let Some(rune) = runestone.etching.and_then(|etching| etching.rune) {
+            if self
+                .store
+                .gets_rune_entry_by_runes(vec![rune])?
+                .iter()
+                .any(|entry| entry.etching == txid.to_string())
+            {
+                return Ok(true);
+            }
+        }
+
+        self.broadcast(&reveal)?;
+
+        Ok(false)
+    }
+
+    fn commitment(&self, entity: &EtchingEntity) -> Result<Commitment> {
+        let rune = Rune(
+            decimal_to_u128(&entity.rune)
+                .with_context(|| format!("invalid rune of etching {}", entity.id))?,
+        );
+
+        let key = KeyPair::from_seckey_str(&Secp256k1::new(), &entity.commit_key)?;
+
+        Commitment::new(key, rune)
+    }
+
+    fn commit_output(commit: &Transaction, commitment: &Commitment) -> Result<(OutPoint, TxOut)> {
+        let script_pubkey = commitment.script_pubkey();
+
+        commit
+            .output
+            .iter()
+            .enumerate()
+            .find(|(_, output)| output.script_pubkey == script_pubkey)
+            .map(|(vout, output)| {
+                Ok((
+                    OutPoint {
+                        txid: commit.txid(),
+                        vout: vout.try_into()?,
+                    },
+                    output.clone(),
+                ))
+            })
+            .ok_or_else(|| anyhow!("commit transaction {} has no commit output", commit.txid()))?
+    }
+
+    fn destination(&self, entity: &EtchingEntity) -> Result<Address> {
+        Ok(entity
+            .destination
+            .parse::<Address<NetworkUnchecked>>()?
+            .require_network(self.network)?)
+    }
+
+    // broadcasting a transaction the node already has is not an error, since
+    // an interrupted etching broadcasts its last transaction again
+    fn broadcast(&self, tx: &Transaction) -> Result {
+        match self.client.send_raw_transaction(tx) {
+            Ok(_) => Ok(()),
+            Err(err) if already_broadcast(&err) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Whether `sendrawtransaction` failed because the node already has the
+/// transaction, in a block (`RPC_VERIFY_ALREADY_IN_CHAIN`) or in its mempool
+/// (`RPC_VERIFY_REJECTED` with a duplicate reason).
+fn already_broadcast(err: &bitcoincore_rpc::Error) -> bool {
+    match err {
+        bitcoincore_rpc::Error::JsonRpc(bitcoincore_rpc::jsonrpc::error::Error::Rpc(
+            bitcoincore_rpc::jsonrpc::error::RpcError { code, message, .. },
+        )) => {
+            *code == -27
+                || (*code == -26
+                    && (message.starts_with("txn-already-in-mempool")
+                        || message.starts_with("txn-already-known")))
+        }
+        _ => false,
+    }
+}
+
+fn decode_tx(hex: &str) -> Result<Transaction> {
+    Ok(consensus::deserialize(&hex::decode(hex)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::indexer::{Prevout, RuneIndexer},
+        bitcoincore_rpc::Auth,
+        secp256k1::schnorr,
+    };
+
+    const HEIGHT: u32 = 100;
+
+    fn commitment(rune: Rune) -> Commitment {
+        Commitment::new(
+            KeyPair::from_seckey_slice(&Secp256k1::new(), &[1; 32]).unwrap(),
+            rune,
+        )
+        .unwrap()
+    }
+
+    fn commit_output(commitment: &Commitment) -> (OutPoint, TxOut) {
+        (
+            OutPoint {
+                txid: Txid::from_byte_array([1; 32]),
+                vout: 0,
+            },
+            TxOut {
+                value: 20_000,
+                script_pubkey: commitment.script_pubkey(),
+            },
+        )
+    }
+
+    fn destination() -> ScriptBuf {
+        ScriptBuf::new_v0_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([2; 20]))
+    }
+
+    fn runestone(rune: Rune) -> Runestone {
+        Runestone {
+            etching: Some(Etching {
+                rune: Some(rune),
+                premine: Some(1000),
+                ..default()
+            }),
+            pointer: Some(0),
+            ..default()
+        }
+    }
+
+    // whether indexing `reveal` at `HEIGHT` etches its rune, with the commit
+    // output created at `commit_height`
+    fn etches(reveal: &Transaction, output: &TxOut, commit_height: u32) -> bool {
+        let client = Client::new("http://127.0.0.1:1", Auth::None).unwrap();
+        let mut indexer = RuneIndexer {
+            block_hash: BlockHash::all_zeros(),
+            block_time: 0,
+            client: &client,
+            height: HEIGHT,
+            minimum: Rune::minimum_at_height(Network::Regtest, Height(HEIGHT)),
+            network: Network::Regtest,
+            runes: 0,
+            cache: default(),
+        };
+
+        indexer.cache.prevouts = Some(
+            [(
+                reveal.input[0].previous_output,
+                Prevout {
+                    height: commit_height,
+                    script_pubkey: output.script_pubkey.clone(),
+                    value: output.value,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        indexer.parse_tx(1, reveal, reveal.txid()).unwrap();
+
+        !indexer.cache.etched.is_empty()
+    }
+
+    #[test]
+    fn reveal_etches_once_commit_matures() {
+        let rune = Rune::minimum_at_height(Network::Regtest, Height(HEIGHT));
+        let commitment = commitment(rune);
+        let (outpoint, output) = commit_output(&commitment);
+
+        let reveal = commitment
+            .reveal(
+                outpoint,
+                &output,
+                &runestone(rune),
+                destination(),
+                Amount::from_sat(10_000),
+            )
+            .unwrap();
+
+        assert_eq!(
+            Runestone::decipher(&reveal),
+            Some(Artifact::Runestone(runestone(rune)))
+        );
+        assert_eq!(reveal.output[0].script_pubkey, destination());
+        assert_eq!(reveal.input[0].sequence, Sequence::from_height(5));
+
+        assert!(etches(&reveal, &output, HEIGHT - 5));
+        assert!(!etches(&reveal, &output, HEIGHT - 4));
+
+        // the script path signature is valid for the commitment key
+        let sighash = SighashCache::new(&reveal)
+            .taproot_script_spend_signature_hash(
+                0,
+                &Prevouts::All(&[&output]),
+                TapLeafHash::from_script(&commitment.script, LeafVersion::TapScript),
+                TapSighashType::Default,
+            )
+            .unwrap();
+        Secp256k1::new()
+            .verify_schnorr(
+                &schnorr::Signature::from_slice(&reveal.input[0].witness[0]).unwrap(),
+                &Message::from_slice(sighash.as_byte_array()).unwrap(),
+                &XOnlyPublicKey::from_keypair(&commitment.key).0,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn reveal_of_other_rune_etches_nothing() {
+        let rune = Rune::minimum_at_height(Network::Regtest, Height(HEIGHT));
+        let commitment = commitment(Rune(rune.n() + 1));
+        let (outpoint, output) = commit_output(&commitment);
+
+        let reveal = commitment
+            .reveal(
+                outpoint,
+                &output,
+                &runestone(rune),
+                destination(),
+                Amount::from_sat(10_000),
+            )
+            .unwrap();
+
+        assert!(!etches(&reveal, &output, HEIGHT - 10));
+    }
+
+    #[test]
+    fn recover_spends_key_path() {
+        let commitment = commitment(Rune(1000));
+        let (outpoint, output) = commit_output(&commitment);
+
+        let tx = commitment
+            .recover(outpoint, &output, destination(), 10)
+            .unwrap();
+
+        assert_eq!(tx.output[0].script_pubkey, destination());
+        assert_eq!(
+            tx.output[0].value,
+            20_000 - u64::try_from(tx.vsize()).unwrap() * 10
+        );
+
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(
+                0,
+                &Prevouts::All(&[&output]),
+                TapSighashType::Default,
+            )
+            .unwrap();
+        Secp256k1::new()
+            .verify_schnorr(
+                &schnorr::Signature::from_slice(&tx.input[0].witness[0]).unwrap(),
+                &Message::from_slice(sighash.as_byte_array()).unwrap(),
+                &commitment.spend_info.output_key().to_inner(),
+            )
+            .unwrap();
+
+        assert!(commitment
+            .recover(outpoint, &output, destination(), 1000)
+            .is_err());
+    }
+
+    #[test]
+    fn already_broadcast_matches_error_codes() {
+        let rpc = |code, message: &str| {
+            bitcoincore_rpc::Error::JsonRpc(bitcoincore_rpc::jsonrpc::error::Error::Rpc(
+                bitcoincore_rpc::jsonrpc::error::RpcError {
+                    code,
+                    message: message.into(),
+                    data: None,
+                },
+            ))
+        };
+
+        assert!(already_broadcast(&rpc(
+            -27,
+            "Transaction already in block chain"
+        )));
+        assert!(already_broadcast(&rpc(-26, "txn-already-in-mempool")));
+        assert!(already_broadcast(&rpc(-26, "txn-already-known")));
+        assert!(!already_broadcast(&rpc(-26, "min relay fee not met")));
+        assert!(!already_broadcast(&rpc(
+            -25,
+            "bad-txns-inputs-missingorspent, already spent"
+        )));
+    }
+
+    #[test]
+    fn etching_state() {
+        for state in 0..=5 {
+            assert_eq!(EtchingState::try_from(state).unwrap() as u8, state);
+        }
+        assert!(EtchingState::try_from(6).is_err());
+
+        assert!(EtchingState::Etched.is_finished());
+        assert!(EtchingState::Recovered.is_finished());
+        assert!(!EtchingState::Revealed.is_finished());
+    }
+}
@@ -42,39 +42,89 @@ impl Fetcher {
     Ok(Fetcher { client, url, auth })
   }
 
-  pub(crate) async fn get_transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>> {
-    if txids.is_empty() {
+  /// Fetch transactions and the hash of the block confirming them, in one
+  /// batched request.
+  pub(crate) async fn get_transactions(
+    &self,
+    txids: Vec<Txid>,
+  ) -> Result<Vec<(Transaction, Option<BlockHash>)>> {
+    #[derive(Deserialize)]
+    struct TransactionInfo {
+      hex: String,
+      blockhash: Option<BlockHash>,
+    }
+
+    let params = txids.iter().map(|txid| json!([txid, true])).collect();
+
+    self
+      .batch::<TransactionInfo>("getrawtransaction", params)
+      .await?
+      .into_iter()
+      .map(|info| {
+        let hex = hex::decode(info.hex)
+          .map_err(|e| anyhow!("Result for batched JSON-RPC response not valid hex: {e}"))?;
+
+        let tx = consensus::deserialize(&hex).map_err(|e| {
+          anyhow!("Result for batched JSON-RPC response not valid bitcoin tx: {e}")
+        })?;
+
+        Ok((tx, info.blockhash))
+      })
+      .collect()
+  }
+
+  pub(crate) async fn get_block_heights(&self, hashes: Vec<BlockHash>) -> Result<Vec<u32>> {
+    #[derive(Deserialize)]
+    struct HeaderInfo {
+      height: u32,
+    }
+
+    let params = hashes.iter().map(|hash| json!([hash])).collect();
+
+    Ok(
+      self
+        .batch::<HeaderInfo>("getblockheader", params)
+        .await?
+        .into_iter()
+        .map(|header| header.height)
+        .collect(),
+    )
+  }
+
+  async fn batch<T: for<'de> Deserialize<'de>>(
+    &self,
+    method: &str,
+    params: Vec<Value>,
+  ) -> Result<Vec<T>> {
+    if params.is_empty() {
       return Ok(Vec::new());
     }
 
-    let mut reqs = Vec::with_capacity(txids.len());
-    for (i, txid) in txids.iter().enumerate() {
+    let mut reqs = Vec::with_capacity(params.len());
+    for (i, params) in params.into_iter().enumerate() {
       let req = json!({
         "jsonrpc": "2.0",
         "id": i, // Use the index as id, so we can quickly sort the response
-        "method": "getrawtransaction",
-        "params": [ txid ]
+        "method": method,
+        "params": params
       });
       reqs.push(req);
     }
 
     let body = Value::Array(reqs).to_string();
 
-    let mut results: Vec<JsonResponse<String>>;
+    let mut results: Vec<JsonResponse<T>>;
     let mut retries = 0;
 
     loop {
-      results = match self.try_get_transactions(body.clone()).await {
+      results = match self.try_batch(body.clone()).await {
         Ok(results) => results,
         Err(error) => {
           if retries >= 5 {
-            return Err(anyhow!(
-              "failed to fetch raw transactions after 5 retries: {}",
-              error
-            ));
+            return Err(anyhow!("failed to {method} after 5 retries: {}", error));
           }
 
-          log::info!("failed to fetch raw transactions, retrying: {}", error);
+          log::info!("failed to {method}, retrying: {}", error);
 
           tokio::time::sleep(Duration::from_millis(100 * u64::pow(2, retries))).await;
           retries += 1;
@@ -87,36 +137,29 @@ impl Fetcher {
     // Return early on any error, because we need all results to proceed
     if let Some(err) = results.iter().find_map(|res| res.error.as_ref()) {
       return Err(anyhow!(
-        "failed to fetch raw transaction: code {} message {}",
+        "failed to {method}: code {} message {}",
         err.code,
         err.message
       ));
     }
 
     // Results from batched JSON-RPC requests can come back in any order, so we must sort them by id
-    results.sort_by(|a, b| a.id.cmp(&b.id));
+    results.sort_by_key(|res| res.id);
 
-    let txs = results
+    results
       .into_iter()
       .map(|res| {
         res
           .result
           .ok_or_else(|| anyhow!("Missing result for batched JSON-RPC response"))
-          .and_then(|str| {
-            hex::decode(str)
-              .map_err(|e| anyhow!("Result for batched JSON-RPC response not valid hex: {e}"))
-          })
-          .and_then(|hex| {
-            consensus::deserialize(&hex).map_err(|e| {
-              anyhow!("Result for batched JSON-RPC response not valid bitcoin tx: {e}")
-            })
-          })
       })
-      .collect::<Result<Vec<Transaction>>>()?;
-    Ok(txs)
+      .collect()
   }
 
-  async fn try_get_transactions(&self, body: String) -> Result<Vec<JsonResponse<String>>> {
+  async fn try_batch<T: for<'de> Deserialize<'de>>(
+    &self,
+    body: String,
+  ) -> Result<Vec<JsonResponse<T>>> {
     let req = Request::builder()
       .method(Method::POST)
       .uri(&self.url)
@@ -128,7 +171,7 @@ impl Fetcher {
 
     let buf = hyper::body::to_bytes(response).await?;
 
-    let results: Vec<JsonResponse<String>> = match serde_json::from_slice(&buf) {
+    let results: Vec<JsonResponse<T>> = match serde_json::from_slice(&buf) {
       Ok(results) => results,
       Err(e) => {
         return Err(anyhow!(
@@ -1,10 +1,18 @@
+mod cache;
 mod event;
 mod into_usize;
 mod lot;
+mod prevout;
 mod rune_indexer;
 mod runes;
+mod script_type;
 
 use super::*;
+pub use event::{EtchError, EventType};
+pub(crate) use into_usize::IntoUsize;
 pub use lot::Lot;
+pub(crate) use prevout::Prevout;
 pub use rune_indexer::RuneIndexer;
 pub use runes::MintError;
+pub(crate) use runes::{decode_rune_balances, encode_rune_balance};
+pub use script_type::ScriptType;
@@ -0,0 +1,285 @@
+use {
+    super::*,
+    crate::{
+        dao::Spend,
+        model::{
+            RuneBalanceEntity, RuneEventEntity, RuneTxEntity, RunestoneEdictEntity, RunestoneEntity,
+        },
+    },
+};
+
+/// Rune state touched by a single block. It is batch loaded before the
+/// block's transactions are parsed and flushed to the database once they have
+/// all been processed, so parsing a transaction does not hit the database.
+#[derive(Default)]
+pub(crate) struct BlockCache {
+    /// unspent rune balances of outpoints spent by the block, and of outputs
+    /// created by the block's transactions indexed so far
+    pub(crate) balances: HashMap<OutPoint, Vec<(RuneId, Lot)>>,
+    /// addresses holding the outpoints in `balances`, if they have one
+    pub(crate) owners: HashMap<OutPoint, String>,
+    /// changes to the holdings of addresses
+    pub(crate) address_deltas: HashMap<(String, RuneId), AddressDelta>,
+    /// balances created by the block
+    pub(crate) created: Vec<RuneBalanceEntity>,
+    /// inputs of the block spending outpoints that hold runes
+    pub(crate) spent: Vec<Spend>,
+    pub(crate) entries: HashMap<RuneId, RuneEntry>,
+    pub(crate) rune_to_id: BTreeMap<Rune, RuneId>,
+    /// runes etched in the block, in etching order
+    pub(crate) etched: Vec<RuneId>,
+    /// mints and burned of entries as they were loaded from the database
+    pub(crate) loaded: HashMap<RuneId, (u128, u128)>,
+    pub(crate) events: Vec<RuneEventEntity>,
+    /// transactions of the block with events, with their input value and fee
+    pub(crate) txs: Vec<RuneTxEntity>,
+    /// artifacts of the block's transactions, one per transaction that has one
+    pub(crate) runestones: Vec<RunestoneEntity>,
+    pub(crate) edicts: Vec<RunestoneEdictEntity>,
+    /// outputs spent by the block, loaded on first use
+    pub(crate) prevouts: Option<HashMap<OutPoint, Prevout>>,
+}
+
+/// Net change of the holdings of an address in a rune. Sends and receives
+/// are applied as they happen, so the change never exceeds the supply of the
+/// rune, even if the address receives all of it more than once in a block.
+#[derive(Default, Debug, PartialEq)]
+pub(crate) struct AddressDelta {
+    /// size of the change of the amount held
+    pub(crate) amount: u128,
+    /// whether the amount held decreases by `amount`, rather than increases
+    pub(crate) decrease: bool,
+    pub(crate) created_utxos: u64,
+    pub(crate) spent_utxos: u64,
+}
+
+impl AddressDelta {
+    fn change(&mut self, amount: u128, decrease: bool) -> Option<()> {
+        if self.decrease == decrease {
+            self.amount = self.amount.checked_add(amount)?;
+        } else if amount > self.amount {
+            self.amount = amount - self.amount;
+            self.decrease = decrease;
+        } else {
+            self.amount -= amount;
+        }
+
+        Some(())
+    }
+
+    /// The amount held once the change is applied to `amount`, or `None` if
+    /// it would be negative or overflow.
+    pub(crate) fn apply(&self, amount: u128) -> Option<u128> {
+        if self.decrease {
+            amount.checked_sub(self.amount)
+        } else {
+            amount.checked_add(self.amount)
+        }
+    }
+}
+
+impl BlockCache {
+    pub(crate) fn insert_loaded(&mut self, id: RuneId, entry: RuneEntry) {
+        self.loaded.insert(id, (entry.mints, entry.burned));
+        self.rune_to_id.insert(entry.spaced_rune.rune, id);
+        self.entries.insert(id, entry);
+    }
+
+    pub(crate) fn insert_etched(&mut self, id: RuneId, entry: RuneEntry) {
+        self.rune_to_id.insert(entry.spaced_rune.rune, id);
+        self.entries.insert(id, entry);
+        self.etched.push(id);
+    }
+
+    /// Record balances created at `outpoint`, which later transactions of the
+    /// block may spend.
+    pub(crate) fn create(
+        &mut self,
+        outpoint: OutPoint,
+        address: String,
+        balances: Vec<(RuneId, Lot)>,
+    ) -> Result {
+        // outputs without an address are not attributed to a holder
+        if !address.is_empty() {
+            for (id, amount) in &balances {
+                let delta = self
+                    .address_deltas
+                    .entry((address.clone(), *id))
+                    .or_default();
+                delta
+                    .change(amount.n(), false)
+                    .ok_or_else(|| anyhow!("balance of {id} at {address} overflows"))?;
+                delta.created_utxos += 1;
+            }
+
+            self.owners.insert(outpoint, address);
+        }
+
+        self.balances.insert(outpoint, balances);
+
+        Ok(())
+    }
+
+    /// Take the balances held by the outpoint `spend` consumes, recording the
+    /// spend. Returns `None` if the outpoint holds no runes.
+    pub(crate) fn spend(&mut self, spend: Spend) -> Result<Option<Vec<(RuneId, Lot)>>> {
+        let Some(balances) = self.balances.remove(&spend.outpoint) else {
+            return Ok(None);
+        };
+        self.spent.push(spend);
+
+        if let Some(address) = self.owners.remove(&spend.outpoint) {
+            for (id, amount) in &balances {
+                let delta = self
+                    .address_deltas
+                    .entry((address.clone(), *id))
+                    .or_default();
+                delta
+                    .change(amount.n(), true)
+                    .ok_or_else(|| anyhow!("balance of {id} at {address} overflows"))?;
+                delta.spent_utxos += 1;
+            }
+        }
+
+        Ok(Some(balances))
+    }
+
+    pub(crate) fn burn(&mut self, id: RuneId, amount: Lot) -> Result {
+        let entry = self
+            .entries
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("burned rune {id} has no entry"))?;
+
+        entry.burned = entry
+            .burned
+            .checked_add(amount.n())
+            .ok_or_else(|| anyhow!("burned overflow for rune {id}"))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, ordinals::inscription_id::txid};
+
+    fn entry(rune: u128) -> RuneEntry {
+        RuneEntry {
+            block: 1,
+            burned: 0,
+            divisibility: 0,
+            etching: txid(1),
+            mints: 0,
+            number: 0,
+            premine: 0,
+            spaced_rune: SpacedRune {
+                rune: Rune(rune),
+                spacers: 0,
+            },
+            symbol: None,
+            terms: None,
+            timestamp: 0,
+            turbo: false,
+        }
+    }
+
+    #[test]
+    fn spend_takes_balances_once() {
+        let outpoint = OutPoint {
+            txid: txid(2),
+            vout: 0,
+        };
+        let id = RuneId { block: 1, tx: 1 };
+        let spend = Spend {
+            outpoint,
+            txid: txid(3),
+            vin: 0,
+            block: 2,
+        };
+
+        let mut cache = BlockCache::default();
+        cache.balances.insert(outpoint, vec![(id, Lot(100))]);
+
+        assert_eq!(cache.spend(spend).unwrap(), Some(vec![(id, Lot(100))]));
+        assert_eq!(cache.spend(spend).unwrap(), None);
+        assert_eq!(cache.spent, vec![spend]);
+    }
+
+    #[test]
+    fn burn_updates_entry() {
+        let id = RuneId { block: 1, tx: 1 };
+
+        let mut cache = BlockCache::default();
+        cache.insert_loaded(id, entry(1000));
+        cache.burn(id, Lot(7)).unwrap();
+
+        assert_eq!(cache.entries[&id].burned, 7);
+        assert_eq!(cache.loaded[&id], (0, 0));
+        assert_eq!(cache.rune_to_id[&Rune(1000)], id);
+        assert!(cache.burn(RuneId { block: 2, tx: 1 }, Lot(1)).is_err());
+    }
+
+    #[test]
+    fn address_deltas() {
+        let id = RuneId { block: 1, tx: 1 };
+        let outpoint = |vout| OutPoint {
+            txid: txid(2),
+            vout,
+        };
+        let spend = |vout| Spend {
+            outpoint: outpoint(vout),
+            txid: txid(3),
+            vin: vout,
+            block: 2,
+        };
+
+        let mut cache = BlockCache::default();
+        // held by foo before the block
+        cache.balances.insert(outpoint(5), vec![(id, Lot(200))]);
+        cache.owners.insert(outpoint(5), "foo".into());
+        cache
+            .create(outpoint(0), "foo".into(), vec![(id, Lot(100))])
+            .unwrap();
+        cache
+            .create(outpoint(1), "foo".into(), vec![(id, Lot(50))])
+            .unwrap();
+        cache
+            .create(outpoint(2), "bar".into(), vec![(id, Lot(10))])
+            .unwrap();
+        cache
+            .create(outpoint(3), "".into(), vec![(id, Lot(10))])
+            .unwrap();
+        cache.spend(spend(0)).unwrap();
+        cache.spend(spend(5)).unwrap();
+        cache.spend(spend(3)).unwrap();
+        cache
+            .create(outpoint(4), "foo".into(), vec![(id, Lot(20))])
+            .unwrap();
+
+        assert_eq!(
+            cache.address_deltas,
+            [
+                (
+                    ("foo".into(), id),
+                    AddressDelta {
+                        amount: 130,
+                        decrease: true,
+                        created_utxos: 3,
+                        spent_utxos: 2,
+                    }
+                ),
+                (
+                    ("bar".into(), id),
+                    AddressDelta {
+                        amount: 10,
+                        decrease: false,
+                        created_utxos: 1,
+                        spent_utxos: 0,
+                    }
+                ),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+}
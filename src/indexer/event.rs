@@ -1,7 +1,99 @@
 use super::*;
 
+/// Kind of an event, stored in the `event_type` column of `rune_event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+  Etch = 1,
+  Mint = 2,
+  Transfer = 3,
+  Burn = 4,
+  Cenotaph = 5,
+  MintRejected = 6,
+  EtchRejected = 7,
+}
+
+impl EventType {
+  pub fn name(self) -> &'static str {
+    match self {
+      Self::Etch => "etch",
+      Self::Mint => "mint",
+      Self::Transfer => "transfer",
+      Self::Burn => "burn",
+      Self::Cenotaph => "cenotaph",
+      Self::MintRejected => "mint_rejected",
+      Self::EtchRejected => "etch_rejected",
+    }
+  }
+}
+
+impl From<EventType> for u8 {
+  fn from(event_type: EventType) -> Self {
+    event_type as u8
+  }
+}
+
+impl TryFrom<u8> for EventType {
+  type Error = Error;
+
+  fn try_from(event_type: u8) -> Result<Self> {
+    Ok(match event_type {
+      1 => Self::Etch,
+      2 => Self::Mint,
+      3 => Self::Transfer,
+      4 => Self::Burn,
+      5 => Self::Cenotaph,
+      6 => Self::MintRejected,
+      7 => Self::EtchRejected,
+      _ => bail!("unknown event type {event_type}"),
+    })
+  }
+}
+
+impl Display for EventType {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+/// Why a runestone's etching created no rune.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EtchError {
+  Locked(Rune),
+  Reserved,
+  Taken,
+  Uncommitted,
+}
+
+impl Display for EtchError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Locked(minimum) => write!(f, "rune is not unlocked, the minimum is {minimum}"),
+      Self::Reserved => write!(f, "rune is reserved"),
+      Self::Taken => write!(f, "rune has already been etched"),
+      Self::Uncommitted => write!(f, "no mature commitment to rune in inputs"),
+    }
+  }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Event {
+  Cenotaph {
+    block_height: u32,
+    flaw: Option<Flaw>,
+    txid: Txid,
+  },
+  EtchRejected {
+    block_height: u32,
+    reason: EtchError,
+    rune: Rune,
+    txid: Txid,
+  },
+  MintRejected {
+    block_height: u32,
+    reason: MintError,
+    rune_id: RuneId,
+    txid: Txid,
+  },
   RuneBurned {
     amount: u128,
     block_height: u32,
@@ -27,3 +119,22 @@ pub enum Event {
     txid: Txid,
   },
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn event_type_round_trips() {
+    for event_type in 1..=7 {
+      assert_eq!(
+        u8::from(EventType::try_from(event_type).unwrap()),
+        event_type
+      );
+    }
+
+    assert!(EventType::try_from(0).is_err());
+    assert!(EventType::try_from(8).is_err());
+    assert_eq!(EventType::MintRejected.to_string(), "mint_rejected");
+  }
+}
@@ -0,0 +1,140 @@
+use super::*;
+
+/// Output spent by a transaction input.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Prevout {
+    pub(crate) height: u32,
+    pub(crate) script_pubkey: ScriptBuf,
+    pub(crate) value: u64,
+}
+
+#[derive(Deserialize)]
+struct BlockJson {
+    tx: Vec<TransactionJson>,
+}
+
+#[derive(Deserialize)]
+struct TransactionJson {
+    vin: Vec<InputJson>,
+}
+
+#[derive(Deserialize)]
+struct InputJson {
+    txid: Option<Txid>,
+    vout: Option<u32>,
+    prevout: Option<PrevoutJson>,
+}
+
+#[derive(Deserialize)]
+struct PrevoutJson {
+    height: u32,
+    #[serde(rename = "scriptPubKey")]
+    script_pub_key: ScriptPubKeyJson,
+    #[serde(with = "bitcoin::amount::serde::as_btc")]
+    value: Amount,
+}
+
+#[derive(Deserialize)]
+struct ScriptPubKeyJson {
+    hex: String,
+}
+
+impl Prevout {
+    /// Load the outputs spent by every input of block `hash`. The node serves
+    /// them from its undo data in a single call, so `-txindex` is not needed.
+    pub(crate) fn load_block(client: &Client, hash: &BlockHash) -> Result<HashMap<OutPoint, Self>> {
+        let block: serde_json::Value = client
+            .call("getblock", &[hash.to_string().into(), 3.into()])
+            .with_context(|| format!("failed to fetch prevouts of block {hash}"))?;
+
+        Self::from_block_json(block)
+    }
+
+    fn from_block_json(block: serde_json::Value) -> Result<HashMap<OutPoint, Self>> {
+        let block: BlockJson = serde_json::from_value(block)?;
+
+        let mut prevouts = HashMap::new();
+        for input in block.tx.into_iter().flat_map(|tx| tx.vin) {
+            // coinbase inputs spend nothing
+            let (Some(txid), Some(vout)) = (input.txid, input.vout) else {
+                continue;
+            };
+
+            let prevout = input.prevout.ok_or_else(|| {
+                anyhow!("no prevout for {txid}:{vout}, bitcoin core 23.0 or later is required")
+            })?;
+
+            prevouts.insert(
+                OutPoint { txid, vout },
+                Self {
+                    height: prevout.height,
+                    script_pubkey: ScriptBuf::from_hex(&prevout.script_pub_key.hex)?,
+                    value: prevout.value.to_sat(),
+                },
+            );
+        }
+
+        Ok(prevouts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn from_block_json() {
+        let txid = "b5a2af5845a8d3796308ff9840e567b14cf6bb158ff26c999e6f9a1f5448f9aa";
+        let script = "5120cb03645b892a156641d1bc09ad63aeb33dd7ab070e1a70ab798bf7e5a52f14ee";
+
+        let prevouts = Prevout::from_block_json(json!({
+            "hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "tx": [
+                { "vin": [{ "coinbase": "03a0bb0d", "sequence": 4294967295u32 }] },
+                {
+                    "vin": [{
+                        "txid": txid,
+                        "vout": 1,
+                        "prevout": {
+                            "generated": false,
+                            "height": 840000,
+                            "value": 0.0001,
+                            "scriptPubKey": { "hex": script, "type": "witness_v1_taproot" }
+                        }
+                    }]
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            prevouts,
+            [(
+                OutPoint {
+                    txid: txid.parse().unwrap(),
+                    vout: 1,
+                },
+                Prevout {
+                    height: 840000,
+                    script_pubkey: ScriptBuf::from_hex(script).unwrap(),
+                    value: 10_000,
+                },
+            )]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn missing_prevout_is_an_error() {
+        assert!(Prevout::from_block_json(json!({
+            "tx": [{
+                "vin": [{
+                    "txid": "b5a2af5845a8d3796308ff9840e567b14cf6bb158ff26c999e6f9a1f5448f9aa",
+                    "vout": 0
+                }]
+            }]
+        }))
+        .is_err());
+    }
+}
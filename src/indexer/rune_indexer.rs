@@ -1,48 +1,282 @@
-use diesel::MysqlConnection;
-
 use self::{
-    dao::{RuneBlanaceDao, RuneEntryDao, RuneEventDao, RuneMysqlDao},
+    dao::{
+        convert_artifact_to_model, convert_model_to_rune_entry, decimal_to_u128, RuneStore, Spend,
+    },
     entry::RuneEntry,
     event::Event,
+    indexer::{
+        cache::{AddressDelta, BlockCache},
+        EtchError, EventType, Prevout, ScriptType,
+    },
     into_usize::IntoUsize,
-    model::{RuneBalanceEntity, RuneEventEntity},
+    model::{RuneAddressBalanceEntity, RuneBalanceEntity, RuneEventEntity, RuneTxEntity},
 };
 
 use super::*;
 
-pub struct RuneIndexer<'client, 'conn> {
+/// Number of outpoints or runes per batched query.
+const BATCH_SIZE: usize = 5_000;
+
+pub struct RuneIndexer<'client> {
+    pub block_hash: BlockHash,
     pub block_time: u32,
-    pub burned: HashMap<RuneId, Lot>,
     pub client: &'client Client,
     pub height: u32,
     pub minimum: Rune,
+    pub network: Network,
     pub runes: u64,
-    pub conn: &'conn mut MysqlConnection,
+    pub(crate) cache: BlockCache,
 }
 
-impl<'client, 'conn> RuneIndexer<'client, 'conn> {
+impl<'client> RuneIndexer<'client> {
+    /// Batch load the balances of every outpoint spent by the block, and the
+    /// entries of runes those balances hold or the block mints or etches.
+    pub(crate) fn preload(
+        &mut self,
+        store: &mut dyn RuneStore,
+        txdata: &[(Transaction, Txid)],
+    ) -> Result {
+        let txids = txdata
+            .iter()
+            .map(|(_, txid)| *txid)
+            .collect::<HashSet<Txid>>();
+        let mut outpoints = Vec::new();
+        let mut rune_ids = HashSet::new();
+        let mut runes = BTreeSet::new();
+        for (tx, _) in txdata {
+            if !tx.is_coin_base() {
+                outpoints.extend(tx.input.iter().map(|input| input.previous_output));
+            }
+
+            match Runestone::decipher(tx) {
+                Some(Artifact::Runestone(runestone)) => {
+                    rune_ids.extend(runestone.mint);
+                    runes.extend(runestone.etching.and_then(|etching| etching.rune));
+                }
+                Some(Artifact::Cenotaph(cenotaph)) => {
+                    rune_ids.extend(cenotaph.mint);
+                    runes.extend(cenotaph.etching);
+                }
+                None => {}
+            }
+        }
+
+        // outputs created by the block are tracked by the cache as they are
+        // indexed, and can't be in the database yet
+        outpoints.retain(|outpoint| !txids.contains(&outpoint.txid));
+
+        for chunk in outpoints.chunks(BATCH_SIZE) {
+            let outpoints = chunk.iter().map(ToString::to_string).collect();
+            for balance in store.load_by_outpoints(outpoints)? {
+                let outpoint = OutPoint::from_str(&balance.out_point)?;
+                let rune_id = RuneId::from_str(&balance.rune_id)?;
+                let amount = decimal_to_u128(&balance.amount)
+                    .with_context(|| format!("invalid balance amount of {outpoint}"))?;
+
+                rune_ids.insert(rune_id);
+                self.cache
+                    .balances
+                    .entry(outpoint)
+                    .or_default()
+                    .push((rune_id, Lot(amount)));
+
+                if !balance.address.is_empty() {
+                    self.cache.owners.insert(outpoint, balance.address);
+                }
+            }
+        }
+
+        let rune_ids = rune_ids.into_iter().collect::<Vec<RuneId>>();
+        for chunk in rune_ids.chunks(BATCH_SIZE) {
+            let ids = chunk.iter().map(ToString::to_string).collect();
+            for entity in store.gets_rune_entry(ids)? {
+                let id = RuneId::from_str(&entity.rune_id)?;
+                self.cache
+                    .insert_loaded(id, convert_model_to_rune_entry(&entity)?);
+            }
+        }
+
+        let runes = runes.into_iter().collect::<Vec<Rune>>();
+        for chunk in runes.chunks(BATCH_SIZE) {
+            for entity in store.gets_rune_entry_by_runes(chunk.to_vec())? {
+                let id = RuneId::from_str(&entity.rune_id)?;
+                if !self.cache.entries.contains_key(&id) {
+                    self.cache
+                        .insert_loaded(id, convert_model_to_rune_entry(&entity)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the rune state changed by the block. Like the rest of the block's
+    /// writes this runs inside the block's db transaction.
+    pub(crate) fn flush(&mut self, store: &mut dyn RuneStore) -> Result {
+        let cache = mem::take(&mut self.cache);
+
+        for id in &cache.etched {
+            store.store_rune_entry(id, &cache.entries[id])?;
+        }
+
+        // updates fail when no row changes, so only write counters that moved
+        for (id, (mints, burned)) in &cache.loaded {
+            let entry = &cache.entries[id];
+
+            if entry.mints != *mints {
+                store.update_rune_mints(id, entry.mints)?;
+            }
+
+            if entry.burned != *burned {
+                store.update_rune_burned(id, entry.burned)?;
+            }
+        }
+
+        // balances created by the block go in before outpoints are marked as
+        // spent, since some of them may already have been spent again
+        for chunk in cache.created.chunks(BATCH_SIZE) {
+            store.store_balances(chunk)?;
+        }
+
+        for chunk in cache.spent.chunks(BATCH_SIZE) {
+            store.updates_spend_out_point(chunk)?;
+        }
+
+        if !cache.events.is_empty() {
+            store.store_events(&cache.events)?;
+        }
+
+        for chunk in cache.txs.chunks(BATCH_SIZE) {
+            store.store_txs(chunk)?;
+        }
+
+        for chunk in cache.runestones.chunks(BATCH_SIZE) {
+            store.store_runestones(chunk)?;
+        }
+
+        for chunk in cache.edicts.chunks(BATCH_SIZE) {
+            store.store_edicts(chunk)?;
+        }
+
+        Self::flush_address_balances(store, cache.address_deltas)
+    }
+
+    fn flush_address_balances(
+        store: &mut dyn RuneStore,
+        deltas: HashMap<(String, RuneId), AddressDelta>,
+    ) -> Result {
+        let addresses = deltas
+            .keys()
+            .map(|(address, _)| address.clone())
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect::<Vec<String>>();
+
+        let mut current = HashMap::new();
+        for chunk in addresses.chunks(BATCH_SIZE) {
+            for entity in store.load_address_balances(chunk.to_vec())? {
+                current.insert((entity.address.clone(), entity.rune_id.clone()), entity);
+            }
+        }
+
+        let mut stores = Vec::new();
+        let mut deletes = Vec::new();
+        for ((address, rune_id), delta) in deltas {
+            let rune_id = rune_id.to_string();
+
+            let (id, amount, utxo_count) = match current.remove(&(address.clone(), rune_id.clone()))
+            {
+                Some(entity) => (
+                    entity.id,
+                    decimal_to_u128(&entity.amount)
+                        .with_context(|| format!("invalid balance of {rune_id} at {address}"))?,
+                    entity.utxo_count,
+                ),
+                None => (0, 0, 0),
+            };
+
+            let (Some(amount), Some(utxo_count)) = (
+                delta.apply(amount),
+                (utxo_count + delta.created_utxos).checked_sub(delta.spent_utxos),
+            ) else {
+                bail!("balance of {rune_id} at {address} would become negative or overflow");
+            };
+
+            if utxo_count == 0 {
+                if id != 0 {
+                    deletes.push(id);
+                }
+                continue;
+            }
+
+            stores.push(RuneAddressBalanceEntity {
+                id,
+                address,
+                rune_id,
+                amount: BigDecimal::from(amount),
+                utxo_count,
+            });
+        }
+
+        for chunk in stores.chunks(BATCH_SIZE) {
+            store.store_address_balances(chunk)?;
+        }
+
+        if !deletes.is_empty() {
+            store.delete_address_balances(deletes)?;
+        }
+
+        Ok(())
+    }
+
     pub fn parse_tx(&mut self, tx_index: u32, tx: &Transaction, txid: Txid) -> Result<()> {
         let artifact = Runestone::decipher(tx);
-        let mut unallocated = self.unallocated(tx)?;
+        let mut unallocated = self.unallocated(tx, txid)?;
         let mut allocated: Vec<HashMap<RuneId, Lot>> = vec![HashMap::new(); tx.output.len()];
         let mut outpoint_to_balances: HashMap<OutPoint, Vec<(RuneId, Lot)>> = HashMap::new();
         let mut created_rune_entry: Option<(Txid, Artifact, RuneId, Rune)> = None;
-        let mut runes_mints: Option<(RuneId, Lot)> = None;
         let mut events: Vec<Event> = Vec::new();
         if let Some(art) = &artifact {
+            if let Artifact::Cenotaph(cenotaph) = art {
+                events.push(Event::Cenotaph {
+                    block_height: self.height,
+                    flaw: cenotaph.flaw,
+                    txid,
+                });
+            }
+
             if let Some(id) = art.mint() {
-                if let Some(amount) = self.mint(id, &mut runes_mints)? {
-                    *unallocated.entry(id).or_default() += amount;
-                    events.push(Event::RuneMinted {
-                        amount: amount.n(),
+                match self.mint(id) {
+                    Ok(amount) => {
+                        *unallocated.entry(id).or_default() += amount;
+                        events.push(Event::RuneMinted {
+                            amount: amount.n(),
+                            block_height: self.height,
+                            txid,
+                            rune_id: id,
+                        })
+                    }
+                    Err(reason) => events.push(Event::MintRejected {
                         block_height: self.height,
-                        txid,
+                        reason,
                         rune_id: id,
-                    })
+                        txid,
+                    }),
                 }
             }
 
-            let etched = self.etched(tx_index, tx, art)?;
+            let etched = match self.etched(tx_index, tx, art)? {
+                Ok(etched) => etched,
+                Err((rune, reason)) => {
+                    events.push(Event::EtchRejected {
+                        block_height: self.height,
+                        reason,
+                        rune,
+                        txid,
+                    });
+                    None
+                }
+            };
 
             if let Artifact::Runestone(runestone) = art {
                 if let Some((id, ..)) = etched {
@@ -229,187 +463,142 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
             })
         }
 
-        let mut rune_entity: Option<(RuneId, RuneEntry)> = None;
+        let etched = created_rune_entry.as_ref().map(|(_, _, id, _)| *id);
+
         if let Some((txid, art, rune_id, rune)) = created_rune_entry {
-            rune_entity = Some((rune_id, self.build_rune_entry(txid, art, rune_id, rune)?));
+            let entry = self.build_rune_entry(txid, art, rune_id, rune)?;
+            self.cache.insert_etched(rune_id, entry);
         };
 
-        // TODO remove all this function have return's db opr
-        let burned = self.build_all_burned_rune(&burned)?;
-        // self.update(&burned, runes_mints)?;
-        let event_entities = self.build_rune_event(events, tx, &artifact)?;
-        let balance_entities = self.build_rune_balance(outpoint_to_balances, tx)?;
-        self.store_all_at_once(
-            rune_entity,
-            event_entities,
-            balance_entities,
-            burned,
-            runes_mints,
-        )?;
+        for (id, amount) in burned {
+            self.cache.burn(id, amount)?;
+        }
 
-        Ok(())
-    }
+        // the tx's input value and fee are stored once, not on each event
+        if !events.is_empty() {
+            let (input_value, fee) = self.input_value_and_fee(tx);
+            self.cache.txs.push(RuneTxEntity {
+                id: 0,
+                block: self.height.into(),
+                tx_id: txid.to_string(),
+                input_value,
+                fee,
+            });
+        }
 
-    fn mint(&mut self, id: RuneId, mints: &mut Option<(RuneId, Lot)>) -> Result<Option<Lot>> {
-        let mut rune_entry = match RuneMysqlDao::load_rune_entry(&mut self.conn, &id) {
-            Ok(entry) => entry,
-            Err(_) => return Ok(None),
-        };
+        let event_entities = self.build_rune_event(events, tx)?;
+        self.cache.events.extend(event_entities);
 
-        let Ok(amount) = rune_entry.mintable(self.height.into()) else {
-            return Ok(None);
-        };
+        // events of the tx share one copy of its artifact
+        if let Some(artifact) = &artifact {
+            let (runestone, edicts) =
+                convert_artifact_to_model(self.height.into(), &txid, artifact, etched)?;
+            self.cache.runestones.push(runestone);
+            self.cache.edicts.extend(edicts);
+        }
 
-        rune_entry.mints += 1;
+        let balance_entities = self.build_rune_balance(&outpoint_to_balances, tx)?;
+        self.cache.created.extend(balance_entities);
 
-        *mints = Some((id, lot::Lot(rune_entry.mints)));
-        // RuneMysqlDao::update_rune_mints(&mut self.conn, &id, rune_entry.mints)?;
+        for (outpoint, balances) in outpoint_to_balances {
+            let address = self.script_address(&tx.output[outpoint.vout.into_usize()].script_pubkey);
+            self.cache.create(outpoint, address, balances)?;
+        }
 
-        Ok(Some(Lot(amount)))
+        Ok(())
     }
 
-    fn unallocated(&mut self, tx: &Transaction) -> Result<HashMap<RuneId, Lot>> {
-        // map of rune ID to un-allocated balance of that rune
-        let mut unallocated: HashMap<RuneId, Lot> = HashMap::new();
+    fn mint(&mut self, id: RuneId) -> Result<Lot, MintError> {
+        let rune_entry = self.cache.entries.get_mut(&id).ok_or(MintError::Unknown)?;
 
-        // increment unallocated runes with the runes in tx inputs
-        for input in &tx.input {
-            match RuneMysqlDao::load_by_outpoint(&mut self.conn, &input.previous_output) {
-                Ok(entry) => {
-                    for event in entry.iter() {
-                        let rune_id = RuneId::from_str(event.rune_id.as_str()).unwrap();
-                        let a = BigDecimal::to_u128(&event.amount).unwrap();
-                        *unallocated.entry(rune_id).or_default() += a;
-                    }
-                    if !entry.is_empty() {
-                        RuneMysqlDao::update_spend_out_point(
-                            &mut self.conn,
-                            &input.previous_output,
-                        )?;
-                    }
-                }
-                Err(_) => {}
-            }
-        }
+        let amount = rune_entry.mintable(self.height.into())?;
 
-        Ok(unallocated)
+        rune_entry.mints += 1;
+
+        Ok(Lot(amount))
     }
 
-    // TODO use for calc_unallocated
-    fn fast_unallocated(
-        &mut self,
-        tx: &Transaction,
-    ) -> Result<(HashMap<RuneId, Lot>, HashMap<String, bool>)> {
+    fn unallocated(&mut self, tx: &Transaction, txid: Txid) -> Result<HashMap<RuneId, Lot>> {
         // map of rune ID to un-allocated balance of that rune
         let mut unallocated: HashMap<RuneId, Lot> = HashMap::new();
-        let mut spent_outpoints = HashMap::new();
-        let mut outpoints = Vec::new();
-        for input in &tx.input {
-            outpoints.push(input.previous_output.to_string());
-        }
-
-        match RuneMysqlDao::load_by_outpoints(&mut self.conn, outpoints) {
-            Ok(entry) => {
-                // increment unallocated runes with the runes in tx inputs
-                for event in entry.iter() {
-                    let rune_id = RuneId::from_str(event.rune_id.as_str()).unwrap();
-                    let a = BigDecimal::to_u128(&event.amount).unwrap();
-                    *unallocated.entry(rune_id).or_default() += a;
-                    spent_outpoints.insert(event.out_point.clone(), true);
-                }
-            }
-            Err(_) => {}
-        }
-
-        Ok((unallocated, spent_outpoints))
-    }
-
-    // TODO is not implemented
-    pub fn write_all_todb_once(&mut self, spent_outpoints: HashMap<String, bool>) -> Result {
-        let mut outpoints = Vec::new();
-        for (k, v) in spent_outpoints.iter() {
-            if *v {
-                outpoints.push(k.clone());
-            }
-        }
 
-        RuneMysqlDao::updates_spend_out_point(&mut self.conn, outpoints)
-    }
-
-    pub fn update(
-        &mut self,
-        burned: &HashMap<RuneId, Lot>,
-        mints: Option<(RuneId, Lot)>,
-    ) -> Result {
-        for (rune_id, burn) in burned {
-            let rune_entry = match RuneMysqlDao::load_rune_entry(&mut self.conn, &rune_id) {
-                Ok(entry) => entry,
-                Err(e) => return Err(e),
+        // increment unallocated runes with the runes in tx inputs
+        for (vin, input) in tx.input.iter().enumerate() {
+            let Some(balances) = self.cache.spend(Spend {
+                outpoint: input.previous_output,
+                txid,
+                vin: vin.try_into()?,
+                block: self.height.into(),
+            })?
+            else {
+                continue;
             };
 
-            let bruned_value = rune_entry.burned.checked_add(burn.n()).unwrap();
-
-            RuneMysqlDao::update_rune_burned(&mut self.conn, &rune_id, bruned_value)?;
-        }
-
-        if let Some((rune_id, mint)) = mints {
-            RuneMysqlDao::update_rune_mints(&mut self.conn, &rune_id, mint.n())?;
+            for (rune_id, amount) in balances {
+                *unallocated.entry(rune_id).or_default() += amount;
+            }
         }
 
-        Ok(())
+        Ok(unallocated)
     }
 
+    // the rune etched by `artifact`, or the rune it names and why it was not
+    // etched
     fn etched(
         &mut self,
         tx_index: u32,
         tx: &Transaction,
         artifact: &Artifact,
-    ) -> Result<Option<(RuneId, Rune)>> {
+    ) -> Result<Result<Option<(RuneId, Rune)>, (Rune, EtchError)>> {
         let rune = match artifact {
             Artifact::Runestone(runestone) => match runestone.etching {
                 Some(etching) => etching.rune,
-                None => return Ok(None),
+                None => return Ok(Ok(None)),
             },
             Artifact::Cenotaph(cenotaph) => match cenotaph.etching {
                 Some(rune) => Some(rune),
-                None => return Ok(None),
+                None => return Ok(Ok(None)),
             },
         };
 
         let rune = if let Some(rune) = rune {
-            let entry = match RuneMysqlDao::load_entry_by_rune(&mut self.conn, &rune) {
-                Ok(entry) => Some(entry),
-                Err(_) => None,
+            let error = if rune < self.minimum {
+                Some(EtchError::Locked(self.minimum))
+            } else if rune.is_reserved() {
+                Some(EtchError::Reserved)
+            } else if self.cache.rune_to_id.contains_key(&rune) {
+                Some(EtchError::Taken)
+            } else if !self.tx_commits_to_rune(tx, rune)? {
+                Some(EtchError::Uncommitted)
+            } else {
+                None
             };
 
-            if rune < self.minimum
-                || rune.is_reserved()
-                || entry.is_some()
-                || !self.tx_commits_to_rune(tx, rune)?
-            {
-                return Ok(None);
+            if let Some(error) = error {
+                return Ok(Err((rune, error)));
             }
+
             rune
         } else {
             Rune::reserved(self.height.into(), tx_index)
         };
 
-        Ok(Some((
+        Ok(Ok(Some((
             RuneId {
                 block: self.height.into(),
                 tx: tx_index,
             },
             rune,
-        )))
+        ))))
     }
 
-    fn tx_commits_to_rune(&self, tx: &Transaction, rune: Rune) -> Result<bool> {
+    fn tx_commits_to_rune(&mut self, tx: &Transaction, rune: Rune) -> Result<bool> {
         let commitment = rune.commitment();
 
         for input in &tx.input {
             // extracting a tapscript does not indicate that the input being spent
-            // was actually a taproot output. this is checked below, when we load the
-            // output's entry from the database
+            // was actually a taproot output. this is checked below, when we look
+            // up the output being spent
             let Some(tapscript) = input.witness.tapscript() else {
                 continue;
             };
@@ -428,38 +617,20 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
                     continue;
                 }
 
-                let Some(tx_info) = self
-                    .client
-                    .get_raw_transaction_info(&input.previous_output.txid, None)
-                    .into_option()?
-                else {
-                    panic!(
-                        "can't get input transaction: {}",
-                        input.previous_output.txid
-                    );
-                };
-
-                let taproot = tx_info.vout[input.previous_output.vout.into_usize()]
-                    .script_pub_key
-                    .script()?
-                    .is_v1_p2tr();
+                let prevout = self.prevout(&input.previous_output)?;
 
-                if !taproot {
+                if !prevout.script_pubkey.is_v1_p2tr() {
                     continue;
                 }
 
-                let commit_tx_height = self
-                    .client
-                    .get_block_header_info(&tx_info.blockhash.unwrap())
-                    .into_option()?
-                    .unwrap()
-                    .height;
-
-                let confirmations = self
-                    .height
-                    .checked_sub(commit_tx_height.try_into().unwrap())
-                    .unwrap()
-                    + 1;
+                let confirmations = self.height.checked_sub(prevout.height).ok_or_else(|| {
+                    anyhow!(
+                        "output {} spent at height {} was created at later height {}",
+                        input.previous_output,
+                        self.height,
+                        prevout.height
+                    )
+                })? + 1;
 
                 if confirmations >= Runestone::COMMIT_CONFIRMATIONS.into() {
                     return Ok(true);
@@ -470,6 +641,21 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
         Ok(false)
     }
 
+    // the updater provides the block's prevouts up front, otherwise they are
+    // loaded for the whole block the first time a commitment is checked
+    fn prevout(&mut self, outpoint: &OutPoint) -> Result<Prevout> {
+        if self.cache.prevouts.is_none() {
+            self.cache.prevouts = Some(Prevout::load_block(self.client, &self.block_hash)?);
+        }
+
+        self.cache
+            .prevouts
+            .as_ref()
+            .and_then(|prevouts| prevouts.get(outpoint))
+            .cloned()
+            .ok_or_else(|| anyhow!("missing prevout for input {outpoint}"))
+    }
+
     fn build_rune_entry(
         &mut self,
         txid: Txid,
@@ -477,10 +663,6 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
         id: RuneId,
         rune: Rune,
     ) -> Result<RuneEntry> {
-        // self.rune_to_id.insert(rune.store(), id.store())?;
-        // self.transaction_id_to_rune
-        //     .insert(&txid.store(), rune.store())?;
-
         let number = self.runes;
         self.runes += 1;
 
@@ -530,8 +712,6 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
             }
         };
 
-        // RuneMysqlDao::store_rune_entry(&mut self.conn, &id, &entry)?;
-
         Ok(entry)
     }
 
@@ -539,14 +719,75 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
         &mut self,
         events: Vec<Event>,
         tx: &Transaction,
-        art: &Option<Artifact>,
     ) -> Result<Vec<RuneEventEntity>> {
         let mut entities: Vec<RuneEventEntity> = Vec::new();
-        let art = art
-            .as_ref()
-            .map_or("".to_string(), |art| serde_json::to_string(art).unwrap());
         for event in events.iter() {
             match event {
+                Event::Cenotaph {
+                    block_height,
+                    flaw,
+                    txid,
+                } => {
+                    let entity = RuneEventEntity {
+                        id: 0,
+                        block: u64::from(*block_height),
+                        event_type: EventType::Cenotaph.into(),
+                        tx_id: txid.to_string(),
+                        rune_id: "".to_string(),
+                        amount: None,
+                        address: "".to_string(),
+                        pk_script_hex: "".to_string(),
+                        script_type: "".to_string(),
+                        vout: 0,
+                        timestamp: u64::from(self.block_time),
+                        reason: flaw.map(|flaw| flaw.to_string()).unwrap_or_default(),
+                    };
+                    entities.push(entity);
+                }
+                Event::EtchRejected {
+                    block_height,
+                    reason,
+                    rune,
+                    txid,
+                } => {
+                    let entity = RuneEventEntity {
+                        id: 0,
+                        block: u64::from(*block_height),
+                        event_type: EventType::EtchRejected.into(),
+                        tx_id: txid.to_string(),
+                        rune_id: "".to_string(),
+                        amount: None,
+                        address: "".to_string(),
+                        pk_script_hex: "".to_string(),
+                        script_type: "".to_string(),
+                        vout: 0,
+                        timestamp: u64::from(self.block_time),
+                        reason: format!("{rune}: {reason}"),
+                    };
+                    entities.push(entity);
+                }
+                Event::MintRejected {
+                    block_height,
+                    reason,
+                    rune_id,
+                    txid,
+                } => {
+                    let entity = RuneEventEntity {
+                        id: 0,
+                        block: u64::from(*block_height),
+                        event_type: EventType::MintRejected.into(),
+                        tx_id: txid.to_string(),
+                        rune_id: rune_id.to_string(),
+                        amount: None,
+                        address: "".to_string(),
+                        pk_script_hex: "".to_string(),
+                        script_type: "".to_string(),
+                        vout: 0,
+                        timestamp: u64::from(self.block_time),
+                        reason: reason.to_string(),
+                    };
+                    entities.push(entity);
+                }
                 Event::RuneBurned {
                     amount,
                     block_height,
@@ -555,16 +796,17 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
                 } => {
                     let entity = RuneEventEntity {
                         id: 0,
-                        block: *block_height as u64,
-                        event_type: 4,
+                        block: u64::from(*block_height),
+                        event_type: EventType::Burn.into(),
                         tx_id: txid.to_string(),
                         rune_id: rune_id.to_string(),
                         amount: BigDecimal::from_u128(*amount),
                         address: "".to_string(),
                         pk_script_hex: "".to_string(),
+                        script_type: "".to_string(),
                         vout: 0,
-                        rune_stone: art.clone(),
-                        timestamp: self.block_time as u64,
+                        timestamp: u64::from(self.block_time),
+                        reason: String::new(),
                     };
                     entities.push(entity);
                 }
@@ -575,16 +817,17 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
                 } => {
                     let entity = RuneEventEntity {
                         id: 0,
-                        block: *block_height as u64,
-                        event_type: 1,
+                        block: u64::from(*block_height),
+                        event_type: EventType::Etch.into(),
                         tx_id: txid.to_string(),
                         rune_id: rune_id.to_string(),
                         amount: None,
                         address: "".to_string(),
                         pk_script_hex: "".to_string(),
+                        script_type: "".to_string(),
                         vout: 0,
-                        rune_stone: art.clone(),
-                        timestamp: self.block_time as u64,
+                        timestamp: u64::from(self.block_time),
+                        reason: String::new(),
                     };
                     entities.push(entity);
                 }
@@ -596,16 +839,17 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
                 } => {
                     let entity = RuneEventEntity {
                         id: 0,
-                        block: *block_height as u64,
-                        event_type: 2,
+                        block: u64::from(*block_height),
+                        event_type: EventType::Mint.into(),
                         tx_id: txid.to_string(),
                         rune_id: rune_id.to_string(),
                         amount: BigDecimal::from_u128(*amount),
                         address: "".to_string(),
                         pk_script_hex: "".to_string(),
+                        script_type: "".to_string(),
                         vout: 0,
-                        rune_stone: art.clone(),
-                        timestamp: self.block_time as u64,
+                        timestamp: u64::from(self.block_time),
+                        reason: String::new(),
                     };
                     entities.push(entity);
                 }
@@ -616,130 +860,842 @@ impl<'client, 'conn> RuneIndexer<'client, 'conn> {
                     rune_id,
                     txid,
                 } => {
-                    let addr = Address::from_script(
-                        tx.output[outpoint.vout as usize].script_pubkey.as_script(),
-                        Network::Bitcoin,
-                    )
-                    .unwrap();
+                    let script_pubkey = &tx.output[outpoint.vout.into_usize()].script_pubkey;
                     let entity = RuneEventEntity {
                         id: 0,
-                        block: *block_height as u64,
-                        event_type: 3,
+                        block: u64::from(*block_height),
+                        event_type: EventType::Transfer.into(),
                         tx_id: txid.to_string(),
                         rune_id: rune_id.to_string(),
                         amount: BigDecimal::from_u128(*amount),
-                        address: addr.to_string(),
-                        pk_script_hex: tx.output[outpoint.vout as usize]
-                            .script_pubkey
-                            .to_hex_string(),
+                        address: self.script_address(script_pubkey),
+                        pk_script_hex: script_pubkey.to_hex_string(),
+                        script_type: ScriptType::from_script(script_pubkey).to_string(),
                         vout: outpoint.vout,
-                        rune_stone: art.clone(),
-                        timestamp: self.block_time as u64,
+                        timestamp: u64::from(self.block_time),
+                        reason: String::new(),
                     };
                     entities.push(entity);
                 }
             }
         }
+        Ok(entities)
+    }
 
-        // if entities.is_empty() {
-        //     return Ok(entities);
-        // }
+    // value spent by a transaction and the fee it pays, unknown for coinbase
+    // transactions or if the block's prevouts are not loaded
+    fn input_value_and_fee(&self, tx: &Transaction) -> (Option<u64>, Option<u64>) {
+        let Some(prevouts) = self.cache.prevouts.as_ref() else {
+            return (None, None);
+        };
 
-        // RuneMysqlDao::store_events(&mut self.conn, &entities)?;
-        Ok(entities)
+        if tx.is_coin_base() {
+            return (None, None);
+        }
+
+        let input_value = tx
+            .input
+            .iter()
+            .map(|input| {
+                prevouts
+                    .get(&input.previous_output)
+                    .map(|prevout| prevout.value)
+            })
+            .sum::<Option<u64>>();
+
+        let fee = input_value
+            .and_then(|value| value.checked_sub(tx.output.iter().map(|output| output.value).sum()));
+
+        (input_value, fee)
+    }
+
+    // scripts without an address encoding, e.g. bare multisig, P2PK or unknown
+    // witness versions, can still hold runes and are stored with no address
+    fn script_address(&self, script: &Script) -> String {
+        Address::from_script(script, self.network)
+            .map(|address| address.to_string())
+            .unwrap_or_default()
     }
 
     fn build_rune_balance(
         &mut self,
-        balances: HashMap<OutPoint, Vec<(RuneId, Lot)>>,
+        balances: &HashMap<OutPoint, Vec<(RuneId, Lot)>>,
         tx: &Transaction,
     ) -> Result<Vec<RuneBalanceEntity>> {
         let mut entities: Vec<RuneBalanceEntity> = Vec::new();
         for (key, val) in balances.iter() {
             for (rune_id, lot) in val.iter() {
-                let addr = Address::from_script(
-                    tx.output[key.vout as usize].script_pubkey.as_script(),
-                    Network::Bitcoin,
-                )
-                .unwrap();
+                let script_pubkey = &tx.output[key.vout.into_usize()].script_pubkey;
                 let entity = RuneBalanceEntity {
                     id: 0,
-                    block: self.height as u64,
+                    block: u64::from(self.height),
                     rune_id: rune_id.to_string(),
                     amount: BigDecimal::from_u128(lot.n()).unwrap(),
-                    address: addr.to_string(),
-                    pk_script_hex: tx.output[key.vout as usize].script_pubkey.to_hex_string(),
+                    address: self.script_address(script_pubkey),
+                    pk_script_hex: script_pubkey.to_hex_string(),
+                    script_type: ScriptType::from_script(script_pubkey).to_string(),
                     out_point: key.to_string(),
                     spent: false,
+                    spent_txid: None,
+                    spent_vin: None,
+                    spent_block: None,
                 };
                 entities.push(entity);
             }
         }
+        Ok(entities)
+    }
+}
 
-        // if entities.is_empty() {
-        //     return Ok(entities);
-        // }
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            dao::{convert_model_to_artifact, for_each_store, outpoint, spend},
+            model::RunestoneEdictEntity,
+        },
+        bitcoincore_rpc::Auth,
+    };
+
+    const HEIGHT: u32 = 100;
+
+    fn script(n: u8) -> ScriptBuf {
+        ScriptBuf::new_v0_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([n; 20]))
+    }
 
-        // RuneMysqlDao::store_balances(&mut self.conn, &entities)?;
-        Ok(entities)
+    fn tx(inputs: &[OutPoint], outputs: usize, runestone: Option<Runestone>) -> Transaction {
+        let mut output = (0..outputs)
+            .map(|i| TxOut {
+                value: 10_000,
+                script_pubkey: script(i.try_into().unwrap()),
+            })
+            .collect::<Vec<TxOut>>();
+
+        if let Some(runestone) = runestone {
+            output.push(TxOut {
+                value: 0,
+                script_pubkey: runestone.encipher(),
+            });
+        }
+
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|previous_output| TxIn {
+                    previous_output: *previous_output,
+                    ..Default::default()
+                })
+                .collect(),
+            output,
+        }
     }
 
-    // TODO use db transaction
-    fn store_all_at_once(
-        &mut self,
-        rune_entity: Option<(RuneId, RuneEntry)>,
-        event_entities: Vec<RuneEventEntity>,
-        balance_entities: Vec<RuneBalanceEntity>,
-        burned: Vec<RuneEntryEntity>,
-        mints: Option<(RuneId, Lot)>,
-    ) -> Result {
-        match rune_entity {
-            Some((id, entity)) => RuneMysqlDao::store_rune_entry(&mut self.conn, &id, &entity)?,
-            None => {}
+    fn etch(premine: u128, terms: Option<Terms>) -> Transaction {
+        tx(
+            &[OutPoint::null()],
+            1,
+            Some(Runestone {
+                etching: Some(Etching {
+                    premine: Some(premine),
+                    terms,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        )
+    }
+
+    fn index(block: &[Transaction]) -> BlockCache {
+        index_with_prevouts(block, HashMap::new()).unwrap()
+    }
+
+    // prevouts are provided up front, so the client is never asked for anything
+    fn index_with_prevouts(
+        block: &[Transaction],
+        prevouts: HashMap<OutPoint, Prevout>,
+    ) -> Result<BlockCache> {
+        let client = Client::new("http://127.0.0.1:1", Auth::None).unwrap();
+        let mut indexer = RuneIndexer {
+            block_hash: BlockHash::all_zeros(),
+            block_time: 0,
+            client: &client,
+            height: HEIGHT,
+            minimum: Rune::minimum_at_height(Network::Regtest, Height(HEIGHT)),
+            network: Network::Regtest,
+            runes: 0,
+            cache: Default::default(),
         };
 
-        RuneMysqlDao::store_events(&mut self.conn, &event_entities)?;
-        RuneMysqlDao::store_balances(&mut self.conn, &balance_entities)?;
+        indexer.cache.prevouts = Some(prevouts);
 
-        for burn in burned.iter() {
-            let rune_id = RuneId::from_str(burn.rune_id.as_str()).unwrap();
-            RuneMysqlDao::update_rune_burned(
-                &mut self.conn,
-                &rune_id,
-                burn.burned.to_u128().unwrap(),
-            )?;
+        for (i, tx) in block.iter().enumerate() {
+            indexer.parse_tx(u32::try_from(i).unwrap(), tx, tx.txid())?;
         }
 
-        if let Some((rune_id, mint)) = mints {
-            RuneMysqlDao::update_rune_mints(&mut self.conn, &rune_id, mint.n())?;
-        }
+        Ok(indexer.cache)
+    }
 
-        Ok(())
+    fn event_types(cache: &BlockCache) -> Vec<u8> {
+        cache.events.iter().map(|event| event.event_type).collect()
     }
 
-    fn build_all_burned_rune(
-        &mut self,
-        burned: &HashMap<RuneId, Lot>,
-    ) -> Result<Vec<RuneEntryEntity>> {
-        let mut rune_ids = Vec::new();
-        let mut rune_id_val_map: HashMap<String, Lot> = HashMap::new();
-        for (rune_id, burn) in burned.iter() {
-            rune_ids.push(rune_id.to_string());
-            rune_id_val_map.insert(rune_id.to_string(), *burn);
-        }
-
-        let mut runes_entry = match RuneMysqlDao::gets_rune_entry(&mut self.conn, rune_ids) {
-            Ok(entry) => entry,
-            Err(e) => return Err(e),
+    const ID: RuneId = RuneId { block: 100, tx: 0 };
+
+    #[test]
+    fn multi_hop_transfer_within_block() {
+        let etching = etch(1000, None);
+
+        let split = tx(
+            &[outpoint(etching.txid(), 0)],
+            2,
+            Some(Runestone {
+                edicts: vec![Edict {
+                    id: ID,
+                    amount: 400,
+                    output: 1,
+                }],
+                ..Default::default()
+            }),
+        );
+
+        let forward = tx(&[outpoint(split.txid(), 1)], 1, None);
+
+        let cache = index(&[etching.clone(), split.clone(), forward.clone()]);
+
+        assert_eq!(
+            cache.balances,
+            [
+                (outpoint(split.txid(), 0), vec![(ID, Lot(600))]),
+                (outpoint(forward.txid(), 0), vec![(ID, Lot(400))]),
+            ]
+            .into_iter()
+            .collect()
+        );
+        assert_eq!(
+            cache.spent,
+            [
+                spend(
+                    split.input[0].previous_output,
+                    split.txid(),
+                    0,
+                    HEIGHT.into()
+                ),
+                spend(
+                    forward.input[0].previous_output,
+                    forward.txid(),
+                    0,
+                    HEIGHT.into()
+                )
+            ]
+        );
+        assert_eq!(cache.created.len(), 4);
+        assert_eq!(cache.etched, [ID]);
+        assert_eq!(event_types(&cache), [1, 3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn merge_outputs_created_within_block() {
+        let etching = etch(1000, None);
+        let split = tx(
+            &[outpoint(etching.txid(), 0)],
+            2,
+            Some(Runestone {
+                edicts: vec![Edict {
+                    id: ID,
+                    amount: 0,
+                    output: 3,
+                }],
+                ..Default::default()
+            }),
+        );
+        let merge = tx(
+            &[outpoint(split.txid(), 1), outpoint(split.txid(), 0)],
+            1,
+            None,
+        );
+
+        let cache = index(&[etching, split, merge.clone()]);
+
+        assert_eq!(
+            cache.balances,
+            [(outpoint(merge.txid(), 0), vec![(ID, Lot(1000))])]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(cache.spent.len(), 3);
+    }
+
+    #[test]
+    fn burn_output_created_within_block() {
+        let etching = etch(1000, None);
+        let burn = tx(
+            &[outpoint(etching.txid(), 0)],
+            0,
+            Some(Runestone::default()),
+        );
+
+        let cache = index(&[etching, burn]);
+
+        assert!(cache.balances.is_empty());
+        assert_eq!(cache.entries[&ID].burned, 1000);
+        assert_eq!(event_types(&cache), [1, 3, 4]);
+    }
+
+    #[test]
+    fn mint_and_transfer_within_block() {
+        let etching = etch(
+            0,
+            Some(Terms {
+                amount: Some(100),
+                cap: Some(10),
+                ..Default::default()
+            }),
+        );
+        let mint = tx(
+            &[],
+            1,
+            Some(Runestone {
+                mint: Some(ID),
+                ..Default::default()
+            }),
+        );
+        let transfer = tx(&[outpoint(mint.txid(), 0)], 2, None);
+
+        let cache = index(&[etching, mint.clone(), transfer.clone()]);
+
+        assert_eq!(cache.entries[&ID].mints, 1);
+        assert_eq!(
+            cache.balances,
+            [(outpoint(transfer.txid(), 0), vec![(ID, Lot(100))])]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            cache.spent,
+            [spend(
+                transfer.input[0].previous_output,
+                transfer.txid(),
+                0,
+                HEIGHT.into()
+            )]
+        );
+    }
+
+    fn etch_committed(rune: Rune, commit: OutPoint) -> Transaction {
+        let commitment = rune.commitment();
+        let tapscript = script::Builder::new()
+            .push_slice::<&script::PushBytes>(commitment.as_slice().try_into().unwrap())
+            .into_script();
+
+        let mut etching = tx(
+            &[commit],
+            1,
+            Some(Runestone {
+                etching: Some(Etching {
+                    rune: Some(rune),
+                    premine: Some(1000),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        );
+        etching.input[0].witness = Witness::from_slice(&[tapscript.into_bytes(), vec![0xc0; 33]]);
+        etching
+    }
+
+    #[test]
+    fn etching_commitment_is_checked_locally() {
+        let rune = Rune::minimum_at_height(Network::Regtest, Height(HEIGHT));
+        let commit = OutPoint {
+            txid: ordinals::inscription_id::txid(1),
+            vout: 0,
         };
+        let taproot = ScriptBuf::from_hex(
+            "5120cb03645b892a156641d1bc09ad63aeb33dd7ab070e1a70ab798bf7e5a52f14ee",
+        )
+        .unwrap();
+
+        let prevout = |height, script_pubkey| {
+            [(
+                commit,
+                Prevout {
+                    height,
+                    script_pubkey,
+                    value: 10_000,
+                },
+            )]
+            .into_iter()
+            .collect()
+        };
+
+        let cache = index_with_prevouts(
+            &[etch_committed(rune, commit)],
+            prevout(HEIGHT - 5, taproot.clone()),
+        )
+        .unwrap();
+        assert_eq!(cache.rune_to_id.get(&rune), Some(&ID));
+
+        let cache = index_with_prevouts(
+            &[etch_committed(rune, commit)],
+            prevout(HEIGHT - 4, taproot),
+        )
+        .unwrap();
+        assert!(cache.etched.is_empty());
+
+        let cache = index_with_prevouts(
+            &[etch_committed(rune, commit)],
+            prevout(HEIGHT - 5, script(1)),
+        )
+        .unwrap();
+        assert!(cache.etched.is_empty());
+
+        // a missing prevout is an error rather than a panic
+        assert!(index_with_prevouts(&[etch_committed(rune, commit)], HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn txs_record_input_value_and_fee_once() {
+        let etching = etch(1000, None);
+        let transfer = tx(&[outpoint(etching.txid(), 0)], 2, None);
+
+        let prevouts = [(
+            outpoint(etching.txid(), 0),
+            Prevout {
+                height: HEIGHT,
+                script_pubkey: script(0),
+                value: 25_000,
+            },
+        )]
+        .into_iter()
+        .collect();
+
+        let txids = [etching.txid(), transfer.txid()];
+
+        let cache = index_with_prevouts(&[etching, transfer], prevouts).unwrap();
+
+        assert_eq!(cache.events.len(), 3);
+
+        let values = cache
+            .txs
+            .iter()
+            .map(|tx| (tx.tx_id.clone(), tx.input_value, tx.fee))
+            .collect::<Vec<(String, Option<u64>, Option<u64>)>>();
+
+        // the etching spends the null outpoint, so it is treated as a coinbase
+        assert_eq!(
+            values,
+            [
+                (txids[0].to_string(), None, None),
+                (txids[1].to_string(), Some(25_000), Some(5_000))
+            ]
+        );
+    }
 
-        for rune in runes_entry.iter_mut() {
-            let burn = rune_id_val_map.get(&rune.rune_id).unwrap();
-            let burned = rune.burned.to_u128().unwrap();
-            let bruned_value = burned.checked_add(burn.n()).unwrap();
-            rune.burned = BigDecimal::from_u128(bruned_value).unwrap();
+    #[test]
+    fn rejected_mints_etchings_and_cenotaphs_are_recorded() {
+        let etching = etch(1000, None);
+
+        let mint = |id| {
+            tx(
+                &[],
+                1,
+                Some(Runestone {
+                    mint: Some(id),
+                    ..Default::default()
+                }),
+            )
+        };
+
+        let locked = tx(
+            &[],
+            1,
+            Some(Runestone {
+                etching: Some(Etching {
+                    rune: Some(Rune(0)),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        );
+
+        let cenotaph = tx(
+            &[],
+            1,
+            Some(Runestone {
+                edicts: vec![Edict {
+                    id: ID,
+                    amount: 1,
+                    output: 5,
+                }],
+                ..Default::default()
+            }),
+        );
+
+        let cache = index(&[
+            etching,
+            mint(ID),
+            mint(RuneId { block: 1, tx: 1 }),
+            locked,
+            cenotaph,
+        ]);
+
+        let events = cache
+            .events
+            .iter()
+            .skip(2)
+            .map(|event| {
+                (
+                    EventType::try_from(event.event_type).unwrap(),
+                    event.rune_id.as_str(),
+                    event.reason.as_str(),
+                )
+            })
+            .collect::<Vec<(EventType, &str, &str)>>();
+
+        assert_eq!(
+            events,
+            [
+                (EventType::MintRejected, "100:0", "not mintable"),
+                (EventType::MintRejected, "1:1", "rune does not exist"),
+                (
+                    EventType::EtchRejected,
+                    "",
+                    "A: rune is not unlocked, the minimum is ZWCOPMRHAMNL"
+                ),
+                (
+                    EventType::Cenotaph,
+                    "",
+                    "edict output greater than transaction output count"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn artifacts_are_stored_once_per_transaction() {
+        let etching = etch(1000, None);
+        let transfer = tx(
+            &[outpoint(etching.txid(), 0)],
+            2,
+            Some(Runestone {
+                edicts: vec![
+                    Edict {
+                        id: ID,
+                        amount: 400,
+                        output: 0,
+                    },
+                    Edict {
+                        id: ID,
+                        amount: 600,
+                        output: 1,
+                    },
+                ],
+                pointer: Some(1),
+                ..Default::default()
+            }),
+        );
+        let cenotaph = tx(
+            &[],
+            1,
+            Some(Runestone {
+                edicts: vec![Edict {
+                    id: ID,
+                    amount: 1,
+                    output: 5,
+                }],
+                ..Default::default()
+            }),
+        );
+        let plain = tx(&[], 1, None);
+
+        let block = [etching, transfer, cenotaph, plain];
+        let cache = index(&block);
+
+        assert_eq!(event_types(&cache), [1, 3, 3, 3, 5]);
+        assert_eq!(cache.runestones.len(), 3);
+        assert_eq!(cache.edicts.len(), 2);
+        assert_eq!(cache.runestones[2].flaw.as_deref(), Some("edict-output"));
+
+        for (tx, runestone) in block.iter().zip(&cache.runestones) {
+            let edicts = cache
+                .edicts
+                .iter()
+                .filter(|edict| edict.tx_id == runestone.tx_id)
+                .cloned()
+                .collect::<Vec<RunestoneEdictEntity>>();
+
+            assert_eq!(runestone.tx_id, tx.txid().to_string());
+            assert_eq!(
+                convert_model_to_artifact(runestone, &edicts, None).unwrap(),
+                Runestone::decipher(tx).unwrap()
+            );
         }
+    }
+
+    #[test]
+    fn edicts_of_the_etched_rune_are_stored_with_its_id() {
+        let etching = tx(
+            &[OutPoint::null()],
+            2,
+            Some(Runestone {
+                edicts: vec![Edict {
+                    id: RuneId::default(),
+                    amount: 400,
+                    output: 1,
+                }],
+                etching: Some(Etching {
+                    premine: Some(1000),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        );
+
+        let cache = index(std::slice::from_ref(&etching));
+
+        assert_eq!(cache.edicts.len(), 1);
+        assert_eq!(cache.edicts[0].rune_id, ID.to_string());
+        assert_eq!(
+            convert_model_to_artifact(&cache.runestones[0], &cache.edicts, Some(ID)).unwrap(),
+            Runestone::decipher(&etching).unwrap()
+        );
+    }
+
+    #[test]
+    fn stored_artifacts_with_fractional_values_are_errors() {
+        let cache = index(&[etch(1000, None)]);
+
+        let mut runestone = cache.runestones[0].clone();
+        assert!(convert_model_to_artifact(&runestone, &[], None).is_ok());
+
+        runestone.rune = Some("1.5".parse().unwrap());
+        assert!(convert_model_to_artifact(&runestone, &[], None).is_err());
+    }
+
+    // indexes a block the way the updater does, loading state from the store
+    // and writing the changes back
+    fn index_into(
+        store: &mut dyn RuneStore,
+        height: u32,
+        block: &[Transaction],
+        prevouts: HashMap<OutPoint, Prevout>,
+    ) -> Result {
+        let client = Client::new("http://127.0.0.1:1", Auth::None).unwrap();
+        let mut indexer = RuneIndexer {
+            block_hash: BlockHash::all_zeros(),
+            block_time: 0,
+            client: &client,
+            height,
+            minimum: Rune::minimum_at_height(Network::Regtest, Height(height)),
+            network: Network::Regtest,
+            runes: 0,
+            cache: Default::default(),
+        };
+
+        indexer.cache.prevouts = Some(prevouts);
+
+        let txdata = block
+            .iter()
+            .map(|tx| (tx.clone(), tx.txid()))
+            .collect::<Vec<(Transaction, Txid)>>();
+
+        store.with_transaction(&mut |store| {
+            indexer.preload(store, &txdata)?;
+
+            for (i, (tx, txid)) in txdata.iter().enumerate() {
+                indexer.parse_tx(u32::try_from(i)?, tx, *txid)?;
+            }
+
+            indexer.flush(store)
+        })
+    }
+
+    fn state_carries_across_blocks(store: &mut dyn RuneStore) {
+        let etching = etch(
+            1000,
+            Some(Terms {
+                amount: Some(100),
+                cap: Some(10),
+                ..Default::default()
+            }),
+        );
+
+        index_into(
+            store,
+            HEIGHT,
+            std::slice::from_ref(&etching),
+            HashMap::new(),
+        )
+        .unwrap();
+
+        let transfer = tx(&[outpoint(etching.txid(), 0)], 1, None);
+        let mint = tx(
+            &[],
+            1,
+            Some(Runestone {
+                mint: Some(ID),
+                ..Default::default()
+            }),
+        );
+
+        let prevout = |tx: &Transaction| {
+            [(
+                outpoint(tx.txid(), 0),
+                Prevout {
+                    height: HEIGHT,
+                    script_pubkey: script(0),
+                    value: 10_000,
+                },
+            )]
+            .into_iter()
+            .collect()
+        };
+
+        index_into(
+            store,
+            HEIGHT + 1,
+            &[transfer.clone(), mint.clone()],
+            prevout(&etching),
+        )
+        .unwrap();
+
+        assert_eq!(
+            store.gets_rune_entry(vec![ID.to_string()]).unwrap()[0].mints,
+            BigDecimal::from(1)
+        );
+
+        let balances = store
+            .load_by_outpoints(vec![
+                outpoint(etching.txid(), 0).to_string(),
+                outpoint(transfer.txid(), 0).to_string(),
+                outpoint(mint.txid(), 0).to_string(),
+            ])
+            .unwrap()
+            .into_iter()
+            .map(|balance| (balance.out_point, balance.amount, balance.spent))
+            .collect::<Vec<(String, BigDecimal, bool)>>();
+
+        assert_eq!(
+            balances,
+            [
+                (
+                    outpoint(etching.txid(), 0).to_string(),
+                    BigDecimal::from(1000),
+                    true
+                ),
+                (
+                    outpoint(transfer.txid(), 0).to_string(),
+                    BigDecimal::from(1000),
+                    false
+                ),
+                (
+                    outpoint(mint.txid(), 0).to_string(),
+                    BigDecimal::from(100),
+                    false
+                ),
+            ]
+        );
+
+        let spent = store.load_spent_by_tx(&transfer.txid()).unwrap();
+        assert_eq!(spent.len(), 1);
+        assert_eq!(spent[0].out_point, outpoint(etching.txid(), 0).to_string());
+        assert_eq!(spent[0].spent_txid, Some(transfer.txid().to_string()));
+        assert_eq!(spent[0].spent_vin, Some(0));
+        assert_eq!(spent[0].spent_block, Some(101));
+
+        let address = Address::from_script(&script(0), Network::Regtest)
+            .unwrap()
+            .to_string();
+        let holdings = store.load_balances_by_address(&address).unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!(holdings[0].amount, BigDecimal::from(1100));
+        assert_eq!(holdings[0].utxo_count, 2);
+
+        let events = |store: &mut dyn RuneStore| {
+            store
+                .load_events_by_rune(&ID, 0, 10)
+                .unwrap()
+                .iter()
+                .map(|event| (event.block, event.event_type))
+                .collect::<Vec<(u64, u8)>>()
+        };
+
+        assert_eq!(
+            events(store),
+            [(101, 3), (101, 2), (101, 3), (100, 3), (100, 1)]
+        );
+
+        // spending an already spent output fails after the new balances are
+        // written, and none of the block's writes are kept
+        let double_spend = tx(&[outpoint(etching.txid(), 0)], 2, None);
+        assert!(index_into(
+            store,
+            HEIGHT + 2,
+            std::slice::from_ref(&double_spend),
+            prevout(&etching)
+        )
+        .is_err());
+        assert!(store
+            .load_by_outpoint(&outpoint(double_spend.txid(), 0))
+            .unwrap()
+            .is_empty());
+        assert_eq!(events(store).len(), 5);
+    }
+
+    fn whole_supply_moves_to_the_same_address_twice_in_a_block(store: &mut dyn RuneStore) {
+        let etching = etch(u128::MAX, None);
+        index_into(
+            store,
+            HEIGHT,
+            std::slice::from_ref(&etching),
+            HashMap::new(),
+        )
+        .unwrap();
+
+        // both transfers pay the whole supply back to the etcher's address
+        let first = tx(&[outpoint(etching.txid(), 0)], 1, None);
+        let second = tx(&[outpoint(first.txid(), 0)], 1, None);
+
+        index_into(
+            store,
+            HEIGHT + 1,
+            &[first, second.clone()],
+            [(
+                outpoint(etching.txid(), 0),
+                Prevout {
+                    height: HEIGHT,
+                    script_pubkey: script(0),
+                    value: 10_000,
+                },
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+
+        let address = Address::from_script(&script(0), Network::Regtest)
+            .unwrap()
+            .to_string();
+        let holdings = store.load_balances_by_address(&address).unwrap();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!(holdings[0].amount, BigDecimal::from(u128::MAX));
+        assert_eq!(holdings[0].utxo_count, 1);
+        assert_eq!(
+            store.load_by_outpoint(&outpoint(second.txid(), 0)).unwrap()[0].amount,
+            BigDecimal::from(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn whole_supply_moves_to_the_same_address_twice_in_a_block_in_every_store() {
+        for_each_store(whole_supply_moves_to_the_same_address_twice_in_a_block);
+    }
 
-        Ok(runes_entry)
+    #[test]
+    fn state_carries_across_blocks_in_every_store() {
+        for_each_store(state_carries_across_blocks);
     }
 }
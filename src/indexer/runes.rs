@@ -1,10 +1,11 @@
 use super::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MintError {
     Cap(u128),
     End(u64),
     Start(u64),
+    Unknown,
     Unmintable,
 }
 
@@ -14,6 +15,7 @@ impl Display for MintError {
             MintError::Cap(cap) => write!(f, "limited to {cap} mints"),
             MintError::End(end) => write!(f, "mint ended on block {end}"),
             MintError::Start(start) => write!(f, "mint starts on block {start}"),
+            MintError::Unknown => write!(f, "rune does not exist"),
             MintError::Unmintable => write!(f, "not mintable"),
         }
     }
@@ -24,3 +26,26 @@ pub(crate) fn encode_rune_balance(id: RuneId, balance: u128, buffer: &mut Vec<u8
     varint::encode_to_vec(id.tx.into(), buffer);
     varint::encode_to_vec(balance, buffer);
 }
+
+pub(crate) fn decode_rune_balances(buffer: &[u8]) -> Result<Vec<(RuneId, u128)>> {
+    let mut balances = Vec::new();
+    let mut i = 0;
+    while i < buffer.len() {
+        let (block, length) = varint::decode(&buffer[i..])?;
+        i += length;
+        let (tx, length) = varint::decode(&buffer[i..])?;
+        i += length;
+        let (balance, length) = varint::decode(&buffer[i..])?;
+        i += length;
+
+        balances.push((
+            RuneId {
+                block: block.try_into()?,
+                tx: tx.try_into()?,
+            },
+            balance,
+        ));
+    }
+
+    Ok(balances)
+}
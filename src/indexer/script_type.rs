@@ -0,0 +1,154 @@
+use {
+    super::*,
+    bitcoin::{opcodes, script::Instruction},
+};
+
+/// Kind of output script holding runes. Runes may be sent to any output that
+/// is not an OP_RETURN, including ones that have no address encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    P2pk,
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    WitnessUnknown,
+    Multisig,
+    OpReturn,
+    NonStandard,
+}
+
+impl ScriptType {
+    pub fn from_script(script: &Script) -> Self {
+        if script.is_v1_p2tr() {
+            Self::P2tr
+        } else if script.is_v0_p2wpkh() {
+            Self::P2wpkh
+        } else if script.is_v0_p2wsh() {
+            Self::P2wsh
+        } else if script.is_witness_program() {
+            Self::WitnessUnknown
+        } else if script.is_p2sh() {
+            Self::P2sh
+        } else if script.is_p2pkh() {
+            Self::P2pkh
+        } else if script.is_p2pk() {
+            Self::P2pk
+        } else if script.is_op_return() {
+            Self::OpReturn
+        } else if Self::is_multisig(script) {
+            Self::Multisig
+        } else {
+            Self::NonStandard
+        }
+    }
+
+    // bare multisig: OP_m <pubkey>... OP_n OP_CHECKMULTISIG
+    fn is_multisig(script: &Script) -> bool {
+        let Ok(instructions) = script
+            .instructions()
+            .collect::<Result<Vec<Instruction>, _>>()
+        else {
+            return false;
+        };
+
+        let [Instruction::Op(m), pubkeys @ .., Instruction::Op(n), Instruction::Op(check)] =
+            instructions.as_slice()
+        else {
+            return false;
+        };
+
+        let (Some(m), Some(n)) = (Self::small_int(*m), Self::small_int(*n)) else {
+            return false;
+        };
+
+        *check == opcodes::all::OP_CHECKMULTISIG
+            && m <= n
+            && usize::from(n) == pubkeys.len()
+            && pubkeys.iter().all(|pubkey| match pubkey {
+                Instruction::PushBytes(bytes) => bytes.len() == 33 || bytes.len() == 65,
+                Instruction::Op(_) => false,
+            })
+    }
+
+    fn small_int(op: opcodes::All) -> Option<u8> {
+        let code = op.to_u8();
+        let first = opcodes::all::OP_PUSHNUM_1.to_u8();
+        let last = opcodes::all::OP_PUSHNUM_16.to_u8();
+
+        (first..=last).contains(&code).then(|| code - first + 1)
+    }
+}
+
+impl Display for ScriptType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::P2pk => "p2pk",
+                Self::P2pkh => "p2pkh",
+                Self::P2sh => "p2sh",
+                Self::P2wpkh => "p2wpkh",
+                Self::P2wsh => "p2wsh",
+                Self::P2tr => "p2tr",
+                Self::WitnessUnknown => "witness_unknown",
+                Self::Multisig => "multisig",
+                Self::OpReturn => "op_return",
+                Self::NonStandard => "nonstandard",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script_type(hex: &str) -> ScriptType {
+        ScriptType::from_script(&ScriptBuf::from_hex(hex).unwrap())
+    }
+
+    #[test]
+    fn address_scripts() {
+        assert_eq!(
+            script_type("5120cb03645b892a156641d1bc09ad63aeb33dd7ab070e1a70ab798bf7e5a52f14ee"),
+            ScriptType::P2tr
+        );
+        assert_eq!(
+            script_type("0014f84418c3e889f09f3598e164b221c52fda6ba657"),
+            ScriptType::P2wpkh
+        );
+        assert_eq!(
+            script_type("a9142d8c0937fe2314b2543196a40a5fa47592315c1987"),
+            ScriptType::P2sh
+        );
+        assert_eq!(
+            script_type("76a91429ad791e5913f9c4965ce084849ad7c810b4a07a88ac"),
+            ScriptType::P2pkh
+        );
+    }
+
+    #[test]
+    fn scripts_without_address() {
+        let pubkey = "0268b7232856bab37f5d30bffb47aaea1f652bc77f4191a55f4b88a786dd3b766a";
+
+        assert_eq!(script_type(&format!("21{pubkey}ac")), ScriptType::P2pk);
+        assert_eq!(
+            script_type(&format!("5121{pubkey}21{pubkey}52ae")),
+            ScriptType::Multisig
+        );
+        assert_eq!(script_type("5202abcd"), ScriptType::WitnessUnknown);
+        assert_eq!(script_type("6a0568656c6c6f"), ScriptType::OpReturn);
+        assert_eq!(script_type("51"), ScriptType::NonStandard);
+        assert_eq!(script_type(""), ScriptType::NonStandard);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(ScriptType::P2tr.to_string(), "p2tr");
+        assert_eq!(ScriptType::WitnessUnknown.to_string(), "witness_unknown");
+        assert_eq!(ScriptType::NonStandard.to_string(), "nonstandard");
+    }
+}
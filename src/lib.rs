@@ -36,7 +36,7 @@ use {
     http::HeaderMap,
     lazy_static::lazy_static,
     ordinals::{
-        varint, Artifact, Charm, Edict, Epoch, Etching, Height, Pile, Rarity, Rune, RuneId,
+        varint, Artifact, Charm, Edict, Epoch, Etching, Flaw, Height, Pile, Rarity, Rune, RuneId,
         Runestone, Sat, SatPoint, SpacedRune, Terms,
     },
     regex::Regex,
@@ -49,7 +49,7 @@ use {
         env,
         fmt::{self, Display, Formatter},
         fs,
-        io::{self, Cursor, Read},
+        io::{self, Cursor},
         mem,
         net::ToSocketAddrs,
         path::{Path, PathBuf},
@@ -66,22 +66,43 @@ use {
     tokio::{runtime::Runtime, task},
 };
 
-pub(crate) use self::{entry::RuneEntry, model::RuneEntryEntity};
+pub(crate) use self::{
+    entry::RuneEntry,
+    model::RuneEntryEntity,
+    options::Options,
+    settings::Settings,
+    subcommand::{Subcommand, SubcommandResult},
+};
 pub use self::{
     indexer::{Lot, MintError, RuneIndexer},
-    schema::etching as EtchingTable,
+    schema::etching::dsl::etching as EtchingTable,
+    schema::indexer_state::dsl::indexer_state as IndexerStateTable,
+    schema::rune_address_balance::dsl::rune_address_balance as RuneAddressBalanceTable,
     schema::rune_balance::dsl::rune_balance as RuneBalanceTable,
+    schema::rune_block::dsl::rune_block as RuneBlockTable,
     schema::rune_entry::dsl::rune_entry as RuneEntryTable,
     schema::rune_event::dsl::rune_event as RuneEventTable,
+    schema::rune_tx::dsl::rune_tx as RuneTxTable,
+    schema::runestone::dsl::runestone as RunestoneTable,
+    schema::runestone_edict::dsl::runestone_edict as RunestoneEdictTable,
 };
 pub use ordinals::InscriptionId;
 
+mod api;
+mod arguments;
 mod dao;
 mod entry;
+mod etcher;
 mod fetcher;
 mod indexer;
+mod lineage;
 mod model;
+mod options;
+mod psbt;
+mod reorg;
 pub mod schema;
+mod settings;
+pub mod subcommand;
 mod updater;
 mod mempool;
 
@@ -146,3 +167,51 @@ impl<T> BitcoinCoreRpcResultExt<T> for Result<T, bitcoincore_rpc::Error> {
         }
     }
 }
+
+pub fn main() {
+    env_logger::init();
+
+    ctrlc::set_handler(move || {
+        if SHUTTING_DOWN.fetch_or(true, atomic::Ordering::Relaxed) {
+            process::exit(1);
+        }
+
+        eprintln!("Shutting down gracefully. Press <CTRL-C> again to shutdown immediately.");
+
+        LISTENERS
+            .lock()
+            .unwrap()
+            .iter()
+            .for_each(|handle| handle.graceful_shutdown(Some(Duration::from_millis(100))));
+
+        gracefully_shutdown_indexer();
+    })
+    .expect("Error setting <CTRL-C> handler");
+
+    let args = arguments::Arguments::parse();
+
+    match args.run() {
+        Err(err) => {
+            eprintln!("error: {err}");
+            err.chain()
+                .skip(1)
+                .for_each(|cause| eprintln!("because: {cause}"));
+            if env::var_os("RUST_BACKTRACE")
+                .map(|val| val == "1")
+                .unwrap_or_default()
+            {
+                eprintln!("{}", err.backtrace());
+            }
+
+            gracefully_shutdown_indexer();
+
+            process::exit(1);
+        }
+        Ok(output) => {
+            if let Some(output) = output {
+                output.print_json();
+            }
+            gracefully_shutdown_indexer();
+        }
+    }
+}
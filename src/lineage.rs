@@ -0,0 +1,202 @@
+use {
+    super::*,
+    crate::{dao::RuneStore, model::RuneBalanceEntity},
+    std::collections::hash_map,
+};
+
+/// Most outputs a lineage walk visits.
+pub(crate) const MAX_LINEAGE_OUTPUTS: usize = 100;
+
+/// Balance of a rune at an output, with the outputs whose balances of the same
+/// rune were spent by the transaction that created it. Outputs without parents
+/// hold runes that were etched or minted.
+pub(crate) struct LineageNode {
+    pub(crate) balance: RuneBalanceEntity,
+    pub(crate) parents: Vec<String>,
+}
+
+pub(crate) struct Lineage {
+    /// the balances of the outpoint first, then their ancestors breadth first
+    pub(crate) nodes: Vec<LineageNode>,
+    /// whether the walk stopped before reaching every origin
+    pub(crate) truncated: bool,
+}
+
+/// Walk the ancestry of the runes held by `outpoint` back to the outputs they
+/// were etched or minted to, following the spends recorded on balances.
+/// Outputs spent before spends were recorded have no known spender, so walks
+/// through them stop early.
+pub(crate) fn lineage(
+    store: &mut dyn RuneStore,
+    outpoint: &OutPoint,
+    limit: usize,
+) -> Result<Lineage> {
+    let key = |balance: &RuneBalanceEntity| (balance.out_point.clone(), balance.rune_id.clone());
+
+    let mut queue = VecDeque::from(store.load_by_outpoint(outpoint)?);
+    let mut seen = queue.iter().map(key).collect::<HashSet<(String, String)>>();
+    let mut spent_by: HashMap<Txid, Vec<RuneBalanceEntity>> = HashMap::new();
+    let mut nodes = Vec::new();
+
+    while let Some(balance) = queue.pop_front() {
+        if nodes.len() == limit {
+            return Ok(Lineage {
+                nodes,
+                truncated: true,
+            });
+        }
+
+        let txid = OutPoint::from_str(&balance.out_point)?.txid;
+
+        let spent = match spent_by.entry(txid) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => entry.insert(store.load_spent_by_tx(&txid)?),
+        };
+
+        let mut parents = Vec::new();
+        for parent in spent.iter() {
+            if parent.rune_id != balance.rune_id {
+                continue;
+            }
+
+            parents.push(parent.out_point.clone());
+
+            if seen.insert(key(parent)) {
+                queue.push_back(parent.clone());
+            }
+        }
+
+        nodes.push(LineageNode { balance, parents });
+    }
+
+    Ok(Lineage {
+        nodes,
+        truncated: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::dao::{balance, for_each_store, outpoint, spend},
+        ordinals::inscription_id::txid,
+    };
+
+    // tx 1 etches 1:1 to (1, 0), tx 2 mints it to (2, 0), tx 3 splits (1, 0)
+    // along with a balance of 1:2, and tx 4 merges (3, 0) and (2, 0)
+    fn populate(store: &mut dyn RuneStore) {
+        store
+            .store_balances(&[
+                balance(outpoint(txid(1), 0), "1:1", 1000),
+                balance(outpoint(txid(1), 0), "1:2", 5),
+                balance(outpoint(txid(2), 0), "1:1", 100),
+                balance(outpoint(txid(3), 0), "1:1", 600),
+                balance(outpoint(txid(3), 0), "1:2", 5),
+                balance(outpoint(txid(3), 1), "1:1", 400),
+                balance(outpoint(txid(4), 0), "1:1", 700),
+            ])
+            .unwrap();
+
+        store
+            .updates_spend_out_point(&[
+                spend(outpoint(txid(1), 0), txid(3), 0, 2),
+                spend(outpoint(txid(3), 0), txid(4), 1, 2),
+                spend(outpoint(txid(2), 0), txid(4), 0, 2),
+            ])
+            .unwrap();
+    }
+
+    fn nodes(lineage: &Lineage) -> Vec<(String, String, Vec<String>)> {
+        lineage
+            .nodes
+            .iter()
+            .map(|node| {
+                (
+                    node.balance.out_point.clone(),
+                    node.balance.rune_id.clone(),
+                    node.parents.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn walks_back_to_origins() {
+        for_each_store(|store| {
+            populate(store);
+
+            let lineage = lineage(store, &outpoint(txid(4), 0), MAX_LINEAGE_OUTPUTS).unwrap();
+
+            assert!(!lineage.truncated);
+            assert_eq!(
+                nodes(&lineage),
+                [
+                    (
+                        outpoint(txid(4), 0).to_string(),
+                        "1:1".into(),
+                        vec![
+                            outpoint(txid(2), 0).to_string(),
+                            outpoint(txid(3), 0).to_string()
+                        ]
+                    ),
+                    (outpoint(txid(2), 0).to_string(), "1:1".into(), vec![]),
+                    (
+                        outpoint(txid(3), 0).to_string(),
+                        "1:1".into(),
+                        vec![outpoint(txid(1), 0).to_string()]
+                    ),
+                    (outpoint(txid(1), 0).to_string(), "1:1".into(), vec![]),
+                ]
+            );
+            assert_eq!(
+                lineage.nodes[1].balance.spent_txid,
+                Some(txid(4).to_string())
+            );
+        });
+    }
+
+    #[test]
+    fn each_rune_has_its_own_lineage() {
+        for_each_store(|store| {
+            populate(store);
+
+            let lineage = lineage(store, &outpoint(txid(3), 0), MAX_LINEAGE_OUTPUTS).unwrap();
+
+            assert_eq!(
+                nodes(&lineage),
+                [
+                    (
+                        outpoint(txid(3), 0).to_string(),
+                        "1:1".into(),
+                        vec![outpoint(txid(1), 0).to_string()]
+                    ),
+                    (
+                        outpoint(txid(3), 0).to_string(),
+                        "1:2".into(),
+                        vec![outpoint(txid(1), 0).to_string()]
+                    ),
+                    (outpoint(txid(1), 0).to_string(), "1:1".into(), vec![]),
+                    (outpoint(txid(1), 0).to_string(), "1:2".into(), vec![]),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn walks_stop_at_the_limit() {
+        for_each_store(|store| {
+            populate(store);
+
+            let truncated = lineage(store, &outpoint(txid(4), 0), 2).unwrap();
+
+            assert!(truncated.truncated);
+            assert_eq!(truncated.nodes.len(), 2);
+
+            assert!(lineage(store, &outpoint(txid(5), 0), 2)
+                .unwrap()
+                .nodes
+                .is_empty());
+        });
+    }
+}
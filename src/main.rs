@@ -1,3 +1,3 @@
-pub fn main() {
-    env_logger::init();
+fn main() {
+    runes::main();
 }
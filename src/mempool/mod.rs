@@ -1 +1,59 @@
-mod reader;
\ No newline at end of file
+use {
+    super::*,
+    crate::{dao::RuneStore, fetcher::Fetcher},
+};
+
+mod pending;
+mod reader;
+
+pub(crate) use pending::PendingState;
+
+/// Keep `pending` up to date with the node's mempool, polling every
+/// `interval` seconds until shutdown.
+pub(crate) fn follow(
+    settings: &Settings,
+    pending: &Mutex<Arc<PendingState>>,
+    interval: u64,
+) -> Result {
+    let client = settings.bitcoin_rpc_client()?;
+    let mut store = settings.store()?;
+    let fetcher = Fetcher::new(&settings.bitcoin_rpc_url(), settings.bitcoin_credentials()?)?;
+
+    loop {
+        // the node or database may be briefly unreachable, so failures are
+        // retried on the next poll rather than stopping the thread
+        if let Err(err) = poll(&client, &mut *store, &fetcher, settings.network(), pending) {
+            log::warn!("failed to index mempool: {err:#}");
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(interval);
+        while Instant::now() < deadline {
+            if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+                return Ok(());
+            }
+
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+/// Replace `pending` with the state of the mempool if the index is at the tip.
+fn poll(
+    client: &Client,
+    store: &mut dyn RuneStore,
+    fetcher: &Fetcher,
+    network: Network,
+    pending: &Mutex<Arc<PendingState>>,
+) -> Result {
+    let tip = client.get_block_count()?;
+    let indexed = store.load_indexer_state()?.map(|state| state.height);
+
+    // balances spent by the mempool may not be indexed yet while the index is
+    // catching up, so the last pending state is kept instead
+    if indexed == Some(tip) {
+        let state = PendingState::load(client, store, fetcher, network, tip.try_into()?)?;
+        *pending.lock().unwrap() = Arc::new(state);
+    }
+
+    Ok(())
+}
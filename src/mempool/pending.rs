@@ -0,0 +1,482 @@
+use {
+    super::*,
+    crate::{
+        dao::RuneStore,
+        fetcher::Fetcher,
+        indexer::{EventType, IntoUsize, Prevout, RuneIndexer},
+        mempool::reader::MempoolDat,
+    },
+};
+
+/// Number of mempool transactions per batched request.
+const CHUNK_SIZE: usize = 2048;
+
+/// Rune state the mempool would produce if it were mined in the next block.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct PendingState {
+    /// height of the block the mempool is indexed as
+    pub(crate) height: u32,
+    pub(crate) transactions: usize,
+    /// net change of the runes held by addresses, by address and rune
+    pub(crate) balances: BTreeMap<String, BTreeMap<RuneId, PendingBalance>>,
+    /// valid mints of each rune
+    pub(crate) mints: BTreeMap<RuneId, u64>,
+    /// runes etched by the mempool, with ids they would get if mined in order
+    pub(crate) etched: Vec<RuneId>,
+    /// entries of every rune touched by the mempool
+    pub(crate) entries: HashMap<RuneId, RuneEntry>,
+}
+
+/// Net change of a holding, so at most one of its amounts is non-zero.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub(crate) struct PendingBalance {
+    pub(crate) incoming: u128,
+    pub(crate) outgoing: u128,
+}
+
+impl PendingState {
+    /// Index the node's mempool on top of the confirmed rune state, which must
+    /// be at `height`, the current chain tip.
+    pub(crate) fn load(
+        client: &Client,
+        store: &mut dyn RuneStore,
+        fetcher: &Fetcher,
+        network: Network,
+        height: u32,
+    ) -> Result<Self> {
+        let txids = client.get_raw_mempool()?;
+
+        // a transaction evicted while the mempool is fetched fails the batch,
+        // and the mempool is indexed again on the next poll
+        let mut txs = Vec::with_capacity(txids.len());
+        let rt = Runtime::new()?;
+        for chunk in txids.chunks(CHUNK_SIZE) {
+            for (tx, _) in rt.block_on(fetcher.get_transactions(chunk.to_vec()))? {
+                txs.push(tx);
+            }
+        }
+
+        Self::load_transactions(client, store, network, height, txs)
+    }
+
+    /// Index the transactions of a `mempool.dat` snapshot, like `load`.
+    pub(crate) fn load_mempool_dat(
+        client: &Client,
+        store: &mut dyn RuneStore,
+        network: Network,
+        height: u32,
+        path: &Path,
+    ) -> Result<Self> {
+        let mut mempool = MempoolDat::open(path)?;
+
+        log::info!(
+            "Reading {} transactions from version {} mempool.dat",
+            mempool.transactions(),
+            mempool.version()
+        );
+
+        let txs = mempool
+            .by_ref()
+            .map(|entry| entry.map(|entry| entry.tx))
+            .collect::<Result<Vec<Transaction>>>()?;
+
+        // reading the tail checks that the file is complete
+        mempool.finish()?;
+
+        Self::load_transactions(client, store, network, height, txs)
+    }
+
+    fn load_transactions(
+        client: &Client,
+        store: &mut dyn RuneStore,
+        network: Network,
+        height: u32,
+        txs: Vec<Transaction>,
+    ) -> Result<Self> {
+        let txs = Self::sort(txs);
+        let height = height + 1;
+
+        let mut indexer = RuneIndexer {
+            block_hash: BlockHash::all_zeros(),
+            block_time: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_secs()
+                .try_into()?,
+            client,
+            height,
+            minimum: Rune::minimum_at_height(network, Height(height)),
+            network,
+            runes: store.gets_rune_number().map_or(0, |number| number + 1),
+            cache: default(),
+        };
+
+        indexer.cache.prevouts = Some(Self::load_prevouts(client, height, &txs)?);
+        indexer.preload(store, &txs)?;
+
+        Self::index(indexer, &txs)
+    }
+
+    /// Index `txs`, sorted so parents come before their children, as the
+    /// transactions of `indexer`'s block following the coinbase.
+    pub(crate) fn index(mut indexer: RuneIndexer, txs: &[(Transaction, Txid)]) -> Result<Self> {
+        for (i, (tx, txid)) in txs.iter().enumerate() {
+            indexer.parse_tx((i + 1).try_into()?, tx, *txid)?;
+        }
+
+        let cache = indexer.cache;
+
+        let mut balances = BTreeMap::<String, BTreeMap<RuneId, PendingBalance>>::new();
+        for ((address, id), delta) in cache.address_deltas {
+            let balance = if delta.decrease {
+                PendingBalance {
+                    incoming: 0,
+                    outgoing: delta.amount,
+                }
+            } else {
+                PendingBalance {
+                    incoming: delta.amount,
+                    outgoing: 0,
+                }
+            };
+
+            balances.entry(address).or_default().insert(id, balance);
+        }
+
+        let mut mints = BTreeMap::new();
+        for event in cache
+            .events
+            .iter()
+            .filter(|event| event.event_type == u8::from(EventType::Mint))
+        {
+            *mints.entry(RuneId::from_str(&event.rune_id)?).or_default() += 1;
+        }
+
+        Ok(Self {
+            height: indexer.height,
+            transactions: txs.len(),
+            balances,
+            mints,
+            etched: cache.etched,
+            entries: cache.entries,
+        })
+    }
+
+    // mempool transactions can spend each other's outputs, and must be indexed
+    // after the transactions they spend from
+    fn sort(txs: Vec<Transaction>) -> Vec<(Transaction, Txid)> {
+        fn visit(
+            txid: Txid,
+            txs: &mut HashMap<Txid, Transaction>,
+            sorted: &mut Vec<(Transaction, Txid)>,
+        ) {
+            let Some(tx) = txs.remove(&txid) else {
+                return;
+            };
+
+            for input in &tx.input {
+                visit(input.previous_output.txid, txs, sorted);
+            }
+
+            sorted.push((tx, txid));
+        }
+
+        let txids = txs.iter().map(Transaction::txid).collect::<Vec<Txid>>();
+        let mut txs = txids
+            .iter()
+            .copied()
+            .zip(txs)
+            .collect::<HashMap<Txid, Transaction>>();
+
+        let mut sorted = Vec::with_capacity(txids.len());
+        for txid in txids {
+            visit(txid, &mut txs, &mut sorted);
+        }
+
+        sorted
+    }
+
+    // only etchings check the outputs they spend, so only their prevouts are
+    // loaded. confirmed outputs are looked up with `gettxout`, which unlike
+    // `getrawtransaction` does not need `-txindex`.
+    fn load_prevouts(
+        client: &Client,
+        height: u32,
+        txs: &[(Transaction, Txid)],
+    ) -> Result<HashMap<OutPoint, Prevout>> {
+        let pending = txs
+            .iter()
+            .map(|(tx, txid)| (*txid, tx))
+            .collect::<HashMap<Txid, &Transaction>>();
+
+        let mut prevouts = HashMap::new();
+        for (tx, _) in txs {
+            let etching = match Runestone::decipher(tx) {
+                Some(Artifact::Runestone(runestone)) => runestone.etching.is_some(),
+                Some(Artifact::Cenotaph(cenotaph)) => cenotaph.etching.is_some(),
+                None => false,
+            };
+
+            if !etching {
+                continue;
+            }
+
+            for input in &tx.input {
+                let outpoint = input.previous_output;
+
+                let prevout = if let Some(parent) = pending.get(&outpoint.txid) {
+                    let output = parent
+                        .output
+                        .get(outpoint.vout.into_usize())
+                        .ok_or_else(|| anyhow!("mempool transaction spends missing {outpoint}"))?;
+
+                    Prevout {
+                        height,
+                        script_pubkey: output.script_pubkey.clone(),
+                        value: output.value,
+                    }
+                } else {
+                    let output = client
+                        .get_tx_out(&outpoint.txid, outpoint.vout, Some(false))?
+                        .ok_or_else(|| anyhow!("mempool transaction spends unknown {outpoint}"))?;
+
+                    Prevout {
+                        height: height.saturating_sub(output.confirmations),
+                        script_pubkey: ScriptBuf::from_bytes(output.script_pub_key.hex),
+                        value: output.value.to_sat(),
+                    }
+                };
+
+                prevouts.insert(outpoint, prevout);
+            }
+        }
+
+        Ok(prevouts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, bitcoincore_rpc::Auth};
+
+    const HEIGHT: u32 = 100;
+
+    const ID: RuneId = RuneId { block: 10, tx: 1 };
+
+    fn address(n: u8) -> (ScriptBuf, String) {
+        let script = ScriptBuf::new_v0_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([n; 20]));
+        let address = Address::from_script(&script, Network::Regtest)
+            .unwrap()
+            .to_string();
+        (script, address)
+    }
+
+    fn tx(inputs: &[OutPoint], outputs: &[u8], runestone: Option<Runestone>) -> Transaction {
+        let mut output = outputs
+            .iter()
+            .map(|n| TxOut {
+                value: 10_000,
+                script_pubkey: address(*n).0,
+            })
+            .collect::<Vec<TxOut>>();
+
+        if let Some(runestone) = runestone {
+            output.push(TxOut {
+                value: 0,
+                script_pubkey: runestone.encipher(),
+            });
+        }
+
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|previous_output| TxIn {
+                    previous_output: *previous_output,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output,
+        }
+    }
+
+    fn index(client: &Client, confirmed: OutPoint, txs: Vec<Transaction>) -> PendingState {
+        let mut indexer = RuneIndexer {
+            block_hash: BlockHash::all_zeros(),
+            block_time: 0,
+            client,
+            height: HEIGHT,
+            minimum: Rune::minimum_at_height(Network::Regtest, Height(HEIGHT)),
+            network: Network::Regtest,
+            runes: 1,
+            cache: default(),
+        };
+
+        indexer.cache.prevouts = Some(HashMap::new());
+        indexer.cache.insert_loaded(
+            ID,
+            RuneEntry {
+                block: ID.block,
+                terms: Some(Terms {
+                    amount: Some(10),
+                    cap: Some(2),
+                    ..default()
+                }),
+                mints: 1,
+                ..default()
+            },
+        );
+        indexer
+            .cache
+            .balances
+            .insert(confirmed, vec![(ID, Lot(1000))]);
+        indexer.cache.owners.insert(confirmed, address(0).1);
+
+        PendingState::index(indexer, &PendingState::sort(txs)).unwrap()
+    }
+
+    #[test]
+    fn sort_puts_parents_first() {
+        let parent = tx(&[OutPoint::null()], &[1], None);
+        let child = tx(
+            &[OutPoint {
+                txid: parent.txid(),
+                vout: 0,
+            }],
+            &[2],
+            None,
+        );
+
+        let sorted = PendingState::sort(vec![child.clone(), parent.clone()]);
+
+        assert_eq!(
+            sorted,
+            vec![
+                (parent.clone(), parent.txid()),
+                (child.clone(), child.txid())
+            ]
+        );
+    }
+
+    #[test]
+    fn pending_transfers_and_mints() {
+        let client = Client::new("http://127.0.0.1:1", Auth::None).unwrap();
+
+        let confirmed = OutPoint {
+            txid: tx(&[], &[0], None).txid(),
+            vout: 0,
+        };
+
+        // confirmed runes of address 0 are sent to address 1, which sends some
+        // of them on to address 2 in a child transaction
+        let send = tx(
+            &[confirmed],
+            &[1],
+            Some(Runestone {
+                edicts: vec![Edict {
+                    id: ID,
+                    amount: 0,
+                    output: 0,
+                }],
+                ..default()
+            }),
+        );
+        let forward = tx(
+            &[OutPoint {
+                txid: send.txid(),
+                vout: 0,
+            }],
+            &[2, 1],
+            Some(Runestone {
+                edicts: vec![Edict {
+                    id: ID,
+                    amount: 300,
+                    output: 0,
+                }],
+                pointer: Some(1),
+                ..default()
+            }),
+        );
+
+        // the cap only leaves room for one more mint
+        let mint = |n| {
+            tx(
+                &[OutPoint {
+                    txid: Txid::from_byte_array([n; 32]),
+                    vout: 0,
+                }],
+                &[3],
+                Some(Runestone {
+                    mint: Some(ID),
+                    ..default()
+                }),
+            )
+        };
+
+        let state = index(&client, confirmed, vec![forward, mint(1), send, mint(2)]);
+
+        assert_eq!(state.height, HEIGHT);
+        assert_eq!(state.transactions, 4);
+        assert_eq!(state.mints, [(ID, 1)].into_iter().collect());
+        assert!(state.etched.is_empty());
+
+        let balance = |n, incoming, outgoing| {
+            (
+                address(n).1,
+                [(ID, PendingBalance { incoming, outgoing })]
+                    .into_iter()
+                    .collect(),
+            )
+        };
+
+        assert_eq!(
+            state.balances,
+            [
+                balance(0, 0, 1000),
+                balance(1, 700, 0),
+                balance(2, 300, 0),
+                balance(3, 10, 0),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn mempool_dat_snapshot() {
+        use crate::mempool::reader::{
+            tests::mempool_dat, MempoolDat, MempoolDatTail, MempoolEntry,
+        };
+
+        let client = Client::new("http://127.0.0.1:1", Auth::None).unwrap();
+
+        let confirmed = OutPoint {
+            txid: tx(&[], &[0], None).txid(),
+            vout: 0,
+        };
+
+        let send = tx(&[confirmed], &[1], None);
+
+        let data = mempool_dat(
+            Some([7; 8]),
+            &[MempoolEntry {
+                tx: send,
+                time: 0,
+                fee_delta: 0,
+            }],
+            &MempoolDatTail::default(),
+        );
+
+        let txs = MempoolDat::new(data.as_slice())
+            .unwrap()
+            .map(|entry| entry.unwrap().tx)
+            .collect();
+
+        let state = index(&client, confirmed, txs);
+
+        assert_eq!(state.transactions, 1);
+        assert_eq!(state.balances[&address(1).1][&ID].incoming, 1000);
+    }
+}
@@ -1,13 +1,344 @@
-use anyhow::Ok;
-use bitcoin::{consensus::Decodable, Transaction};
+use {
+    super::*,
+    bitcoin::VarInt,
+    std::{fs::File, io::BufReader},
+};
 
-use super::*;
-use std::{fs::File, io::BufReader};
+/// `mempool.dat` written before Bitcoin Core 28.0.
+const VERSION_NO_XOR_KEY: u64 = 1;
 
-fn read_mempool_dat(filename: &str) -> Result<(), anyhow::Error> {
-    let mut file = File::open(filename)?;
-    let mut reader = BufReader::new(file);
+/// `mempool.dat` obfuscated with a key stored after the version.
+const VERSION: u64 = 2;
 
-    let _ = Transaction::consensus_decode_from_finite_reader(&mut reader);
-    Ok(())
+/// Transaction saved in `mempool.dat`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct MempoolEntry {
+    pub(crate) tx: Transaction,
+    /// when the transaction entered the mempool, in seconds since the epoch
+    pub(crate) time: i64,
+    /// fee delta set with `prioritisetransaction`, in satoshis
+    pub(crate) fee_delta: i64,
+}
+
+/// Data saved after the transactions of `mempool.dat`.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct MempoolDatTail {
+    /// fee deltas of transactions, which may not be in the mempool
+    pub(crate) fee_deltas: Vec<(Txid, i64)>,
+    /// transactions not yet relayed to any peer
+    pub(crate) unbroadcast: Vec<Txid>,
+}
+
+/// Streaming reader of the `mempool.dat` file Bitcoin Core saves on shutdown.
+/// Entries are read one at a time, followed by the tail with `finish`.
+pub(crate) struct MempoolDat<R> {
+    reader: XorReader<R>,
+    version: u64,
+    count: u64,
+    read: u64,
+}
+
+impl MempoolDat<BufReader<File>> {
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+
+        Self::new(BufReader::new(file))
+            .with_context(|| format!("failed to read {}", path.display()))
+    }
+}
+
+impl<R: io::Read> MempoolDat<R> {
+    pub(crate) fn new(reader: R) -> Result<Self> {
+        let mut reader = XorReader {
+            inner: reader,
+            key: Vec::new(),
+            position: 0,
+        };
+
+        let version = decode::<u64, R>(&mut reader, "version")?;
+
+        match version {
+            VERSION_NO_XOR_KEY => {}
+            VERSION => {
+                let key = decode::<Vec<u8>, R>(&mut reader, "obfuscation key")?;
+                ensure!(!key.is_empty(), "empty obfuscation key");
+                reader.key = key;
+            }
+            _ => bail!("unsupported mempool.dat version {version}"),
+        }
+
+        let count = decode::<u64, R>(&mut reader, "transaction count")?;
+
+        Ok(Self {
+            reader,
+            version,
+            count,
+            read: 0,
+        })
+    }
+
+    pub(crate) fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Number of transactions in the file.
+    pub(crate) fn transactions(&self) -> u64 {
+        self.count
+    }
+
+    pub(crate) fn next_entry(&mut self) -> Result<Option<MempoolEntry>> {
+        if self.read == self.count {
+            return Ok(None);
+        }
+
+        let what = format!("transaction {} of {}", self.read, self.count);
+
+        let entry = MempoolEntry {
+            tx: decode(&mut self.reader, &what)?,
+            time: decode(&mut self.reader, &what)?,
+            fee_delta: decode(&mut self.reader, &what)?,
+        };
+
+        self.read += 1;
+
+        Ok(Some(entry))
+    }
+
+    /// Skip any entries not yet read and read the tail of the file.
+    pub(crate) fn finish(mut self) -> Result<MempoolDatTail> {
+        while self.next_entry()?.is_some() {}
+
+        let deltas = decode::<VarInt, R>(&mut self.reader, "fee delta count")?.0;
+        let mut fee_deltas = Vec::new();
+        for _ in 0..deltas {
+            fee_deltas.push((
+                decode(&mut self.reader, "fee delta")?,
+                decode(&mut self.reader, "fee delta")?,
+            ));
+        }
+
+        let unbroadcast = decode::<VarInt, R>(&mut self.reader, "unbroadcast count")?.0;
+        let unbroadcast = (0..unbroadcast)
+            .map(|_| decode(&mut self.reader, "unbroadcast transaction"))
+            .collect::<Result<Vec<Txid>>>()?;
+
+        ensure!(
+            io::Read::read(&mut self.reader, &mut [0])? == 0,
+            "unexpected data after mempool.dat tail"
+        );
+
+        Ok(MempoolDatTail {
+            fee_deltas,
+            unbroadcast,
+        })
+    }
+}
+
+impl<R: io::Read> Iterator for MempoolDat<R> {
+    type Item = Result<MempoolEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
+
+fn decode<T: Decodable, R: io::Read>(reader: &mut XorReader<R>, what: &str) -> Result<T> {
+    T::consensus_decode_from_finite_reader(reader).map_err(|err| match err {
+        consensus::encode::Error::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            anyhow!("mempool.dat truncated in {what}")
+        }
+        err => anyhow!("invalid {what} in mempool.dat: {err}"),
+    })
+}
+
+// everything after the key is xored with it, starting from the key byte at
+// the position of the data in the file
+struct XorReader<R> {
+    inner: R,
+    key: Vec<u8>,
+    position: usize,
+}
+
+impl<R: io::Read> io::Read for XorReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+
+        if !self.key.is_empty() {
+            for (i, byte) in buf[..n].iter_mut().enumerate() {
+                *byte ^= self.key[(self.position + i) % self.key.len()];
+            }
+        }
+
+        self.position += n;
+
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn tx(n: u8) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_byte_array([n; 32]),
+                    vout: 0,
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[[n; 64]]),
+            }],
+            output: vec![TxOut {
+                value: 1000,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        }
+    }
+
+    /// Serialize a `mempool.dat` the way Bitcoin Core does.
+    pub(crate) fn mempool_dat(
+        key: Option<[u8; 8]>,
+        entries: &[MempoolEntry],
+        tail: &MempoolDatTail,
+    ) -> Vec<u8> {
+        let mut header = Vec::new();
+        let mut body = Vec::new();
+
+        match key {
+            Some(key) => {
+                VERSION.consensus_encode(&mut header).unwrap();
+                key.to_vec().consensus_encode(&mut header).unwrap();
+            }
+            None => {
+                VERSION_NO_XOR_KEY.consensus_encode(&mut header).unwrap();
+            }
+        }
+
+        u64::try_from(entries.len())
+            .unwrap()
+            .consensus_encode(&mut body)
+            .unwrap();
+        for entry in entries {
+            entry.tx.consensus_encode(&mut body).unwrap();
+            entry.time.consensus_encode(&mut body).unwrap();
+            entry.fee_delta.consensus_encode(&mut body).unwrap();
+        }
+
+        VarInt(tail.fee_deltas.len().try_into().unwrap())
+            .consensus_encode(&mut body)
+            .unwrap();
+        for (txid, delta) in &tail.fee_deltas {
+            txid.consensus_encode(&mut body).unwrap();
+            delta.consensus_encode(&mut body).unwrap();
+        }
+
+        VarInt(tail.unbroadcast.len().try_into().unwrap())
+            .consensus_encode(&mut body)
+            .unwrap();
+        for txid in &tail.unbroadcast {
+            txid.consensus_encode(&mut body).unwrap();
+        }
+
+        if let Some(key) = key {
+            for (i, byte) in body.iter_mut().enumerate() {
+                *byte ^= key[(header.len() + i) % key.len()];
+            }
+        }
+
+        header.extend(body);
+        header
+    }
+
+    fn entries() -> Vec<MempoolEntry> {
+        (0..3)
+            .map(|n| MempoolEntry {
+                tx: tx(n),
+                time: 1_700_000_000 + i64::from(n),
+                fee_delta: i64::from(n) - 1,
+            })
+            .collect()
+    }
+
+    fn tail() -> MempoolDatTail {
+        MempoolDatTail {
+            fee_deltas: vec![(tx(7).txid(), 500), (tx(8).txid(), -20)],
+            unbroadcast: vec![tx(2).txid()],
+        }
+    }
+
+    #[test]
+    fn read_without_key() {
+        let data = mempool_dat(None, &entries(), &tail());
+
+        let mut mempool = MempoolDat::new(data.as_slice()).unwrap();
+        assert_eq!(mempool.version(), VERSION_NO_XOR_KEY);
+        assert_eq!(mempool.transactions(), 3);
+        assert_eq!(
+            mempool
+                .by_ref()
+                .collect::<Result<Vec<MempoolEntry>>>()
+                .unwrap(),
+            entries()
+        );
+        assert_eq!(mempool.finish().unwrap(), tail());
+    }
+
+    #[test]
+    fn read_with_key() {
+        let data = mempool_dat(Some([1, 2, 3, 4, 5, 6, 7, 8]), &entries(), &tail());
+
+        let mut mempool = MempoolDat::new(data.as_slice()).unwrap();
+        assert_eq!(mempool.version(), VERSION);
+        assert_eq!(mempool.next_entry().unwrap(), entries().into_iter().next());
+        assert_eq!(mempool.finish().unwrap(), tail());
+    }
+
+    #[test]
+    fn truncated() {
+        let data = mempool_dat(Some([9; 8]), &entries(), &tail());
+
+        let mut mempool = MempoolDat::new(&data[..100]).unwrap();
+        assert_eq!(
+            mempool.next_entry().unwrap_err().to_string(),
+            "mempool.dat truncated in transaction 0 of 3"
+        );
+
+        assert_eq!(
+            MempoolDat::new(&data[..10]).err().unwrap().to_string(),
+            "mempool.dat truncated in obfuscation key"
+        );
+
+        assert_eq!(
+            MempoolDat::new(&data[..data.len() - 1])
+                .unwrap()
+                .finish()
+                .unwrap_err()
+                .to_string(),
+            "mempool.dat truncated in unbroadcast transaction"
+        );
+    }
+
+    #[test]
+    fn trailing_data() {
+        let mut data = mempool_dat(None, &entries(), &tail());
+        data.push(0);
+
+        assert!(MempoolDat::new(data.as_slice()).unwrap().finish().is_err());
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut data = Vec::new();
+        3u64.consensus_encode(&mut data).unwrap();
+
+        assert_eq!(
+            MempoolDat::new(data.as_slice()).err().unwrap().to_string(),
+            "unsupported mempool.dat version 3"
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use bigdecimal::BigDecimal;
 use diesel::prelude::*;
 
-#[derive(Queryable, Selectable, Insertable, AsChangeset)]
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Clone)]
 #[diesel(table_name = crate::schema::rune_entry)]
 #[diesel(primary_key(id))]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
@@ -28,7 +28,7 @@ pub(crate) struct RuneEntryEntity {
     pub offset_end: Option<u64>,
 }
 
-#[derive(Queryable, Selectable, Insertable, Default)]
+#[derive(Queryable, Selectable, Insertable, Default, Clone)]
 #[diesel(table_name = crate::schema::rune_event)]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub(crate) struct RuneEventEntity {
@@ -40,12 +40,71 @@ pub(crate) struct RuneEventEntity {
     pub amount: Option<BigDecimal>,
     pub address: String,
     pub pk_script_hex: String,
+    pub script_type: String,
     pub vout: u32,
-    pub rune_stone: String,
     pub timestamp: u64,
+    /// why a cenotaph, rejected mint or rejected etching did nothing
+    pub reason: String,
 }
 
-#[derive(Queryable, Selectable, Insertable)]
+/// Transaction with rune events, with what it spent and paid, stored once
+/// for all of its events. Both are unknown for coinbase transactions.
+#[derive(Queryable, Selectable, Insertable, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::rune_tx)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub(crate) struct RuneTxEntity {
+    pub id: u64,
+    pub block: u64,
+    pub tx_id: String,
+    /// sats spent by the tx
+    pub input_value: Option<u64>,
+    /// sats paid as fee by the tx
+    pub fee: Option<u64>,
+}
+
+/// Runestone or cenotaph of a transaction, stored once for all of the
+/// transaction's events. Etching and term columns are empty unless it etches.
+#[derive(Queryable, Selectable, Insertable, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::runestone)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub(crate) struct RunestoneEntity {
+    pub id: u64,
+    pub block: u64,
+    pub tx_id: String,
+    pub cenotaph: bool,
+    pub flaw: Option<String>,
+    pub pointer: Option<u32>,
+    pub mint: Option<String>,
+    pub etching: bool,
+    pub rune: Option<BigDecimal>,
+    pub spacers: Option<u32>,
+    pub divisibility: Option<u8>,
+    pub premine: Option<BigDecimal>,
+    pub symbol: Option<String>,
+    pub turbo: bool,
+    pub terms: bool,
+    pub amount: Option<BigDecimal>,
+    pub cap: Option<BigDecimal>,
+    pub height_start: Option<u64>,
+    pub height_end: Option<u64>,
+    pub offset_start: Option<u64>,
+    pub offset_end: Option<u64>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, PartialEq, Clone)]
+#[diesel(table_name = crate::schema::runestone_edict)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub(crate) struct RunestoneEdictEntity {
+    pub id: u64,
+    pub block: u64,
+    pub tx_id: String,
+    pub edict_index: u32,
+    pub rune_id: String,
+    pub amount: BigDecimal,
+    pub output: u32,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone)]
 #[diesel(table_name = crate::schema::rune_balance)]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub(crate) struct RuneBalanceEntity {
@@ -55,6 +114,63 @@ pub(crate) struct RuneBalanceEntity {
     pub amount: BigDecimal,
     pub address: String,
     pub pk_script_hex: String,
+    pub script_type: String,
     pub out_point: String,
     pub spent: bool,
+    /// input that spent the output, set along with `spent`
+    pub spent_txid: Option<String>,
+    pub spent_vin: Option<u32>,
+    pub spent_block: Option<u64>,
+}
+
+/// Unspent balance of a rune held by an address, summed over its outputs.
+#[derive(Queryable, Selectable, Insertable, Clone)]
+#[diesel(table_name = crate::schema::rune_address_balance)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub(crate) struct RuneAddressBalanceEntity {
+    pub id: u64,
+    pub address: String,
+    pub rune_id: String,
+    pub amount: BigDecimal,
+    pub utxo_count: u64,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone)]
+#[diesel(table_name = crate::schema::rune_block)]
+#[diesel(primary_key(height))]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub(crate) struct RuneBlockEntity {
+    pub height: u64,
+    pub block_hash: String,
+}
+
+#[derive(Queryable, Selectable, Insertable, Clone)]
+#[diesel(table_name = crate::schema::indexer_state)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub(crate) struct IndexerStateEntity {
+    pub id: u32,
+    pub network: String,
+    pub height: u64,
+    pub block_hash: String,
+}
+
+/// Rune being etched by `runes etch`, with the transactions of each step.
+#[derive(Queryable, Selectable, Insertable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = crate::schema::etching)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub(crate) struct EtchingEntity {
+    pub id: u64,
+    pub rune: BigDecimal,
+    pub state: u8,
+    pub destination: String,
+    /// secret key of the commit output, unencrypted, erased once etched
+    pub commit_key: String,
+    pub runestone: String,
+    pub fee_rate: u64,
+    pub postage: u64,
+    pub commit_tx_id: String,
+    pub commit_tx: String,
+    pub reveal_tx_id: String,
+    pub reveal_tx: String,
+    pub inscription_output: String,
 }
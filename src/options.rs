@@ -0,0 +1,36 @@
+use super::*;
+
+#[derive(Clone, Default, Debug, Parser)]
+pub struct Options {
+    #[arg(long, help = "Load configuration from <CONFIG>.")]
+    pub(crate) config: Option<PathBuf>,
+    #[arg(long, help = "Connect to Bitcoin Core RPC at <BITCOIN_RPC_URL>.")]
+    pub(crate) bitcoin_rpc_url: Option<String>,
+    #[arg(
+        long,
+        requires = "bitcoin_rpc_password",
+        help = "Authenticate to Bitcoin Core RPC as <BITCOIN_RPC_USERNAME>."
+    )]
+    pub(crate) bitcoin_rpc_username: Option<String>,
+    #[arg(
+        long,
+        requires = "bitcoin_rpc_username",
+        help = "Authenticate to Bitcoin Core RPC with <BITCOIN_RPC_PASSWORD>."
+    )]
+    pub(crate) bitcoin_rpc_password: Option<String>,
+    #[arg(long, help = "Load Bitcoin Core RPC cookie file from <COOKIE_FILE>.")]
+    pub(crate) cookie_file: Option<PathBuf>,
+    #[arg(long, help = "Store rune state in the database at <DATABASE_URL>.")]
+    pub(crate) database_url: Option<String>,
+    #[arg(
+        long,
+        alias = "chain",
+        help = "Index runes on <NETWORK>. [default: bitcoin] [possible values: bitcoin, testnet, signet, regtest]"
+    )]
+    pub(crate) network: Option<Network>,
+    #[arg(
+        long,
+        help = "Start indexing at <START_HEIGHT> when the database is empty. [default: first rune height of <NETWORK>]"
+    )]
+    pub(crate) start_height: Option<u32>,
+}
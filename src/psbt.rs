@@ -0,0 +1,129 @@
+use {
+    super::*,
+    crate::dao::{decimal_to_u128, RuneStore},
+    base64::Engine,
+    bitcoin::psbt::Psbt,
+};
+
+pub(crate) use {
+    transfer::{RuneTransfer, TransferBuilder},
+    tx_detail::TxDetail,
+};
+
+mod transfer;
+mod tx_detail;
+
+/// Marketplace order, either a PSBT or a signed transaction.
+#[derive(Debug)]
+pub(crate) enum Offer {
+    Psbt(Box<Psbt>),
+    Transaction(Transaction),
+}
+
+impl FromStr for Offer {
+    type Err = Error;
+
+    /// Parse a hex or base64 encoded PSBT, or a hex encoded transaction.
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+
+        let bytes = match hex::decode(s) {
+            Ok(bytes) => bytes,
+            Err(_) => base64::engine::general_purpose::STANDARD
+                .decode(s)
+                .context("offer is neither hex nor base64")?,
+        };
+
+        if bytes.starts_with(b"psbt\xff") {
+            Ok(Self::Psbt(Box::new(Psbt::deserialize(&bytes)?)))
+        } else {
+            Ok(Self::Transaction(consensus::deserialize(&bytes)?))
+        }
+    }
+}
+
+impl Offer {
+    pub(crate) fn transaction(&self) -> &Transaction {
+        match self {
+            Self::Psbt(psbt) => &psbt.unsigned_tx,
+            Self::Transaction(tx) => tx,
+        }
+    }
+
+    /// Analyze the offer's signatures, with the rune balances of the outputs
+    /// it spends looked up in the index.
+    pub(crate) fn analyze(&self, store: &mut dyn RuneStore, network: Network) -> Result<TxDetail> {
+        let tx = self.transaction();
+
+        let outpoints = tx
+            .input
+            .iter()
+            .map(|input| input.previous_output.to_string())
+            .collect();
+
+        let mut runes = HashMap::<OutPoint, Vec<(RuneId, u128)>>::new();
+        for balance in store.load_by_outpoints(outpoints)? {
+            if balance.spent {
+                continue;
+            }
+
+            let outpoint = OutPoint::from_str(&balance.out_point)?;
+            let amount = decimal_to_u128(&balance.amount)
+                .with_context(|| format!("invalid balance amount of {outpoint}"))?;
+
+            runes
+                .entry(outpoint)
+                .or_default()
+                .push((RuneId::from_str(&balance.rune_id)?, amount));
+        }
+
+        let psbt = match self {
+            Self::Psbt(psbt) => Some(psbt.as_ref()),
+            Self::Transaction(_) => None,
+        };
+
+        tx_detail::parse_tx(tx, psbt, &runes, network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_offer() {
+        let tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: 1,
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+
+        let psbt = Psbt::from_unsigned_tx(tx.clone()).unwrap().serialize();
+
+        assert!(matches!(
+            hex::encode(&psbt).parse::<Offer>().unwrap(),
+            Offer::Psbt(psbt) if psbt.unsigned_tx == tx
+        ));
+        assert!(matches!(
+            base64::engine::general_purpose::STANDARD
+                .encode(&psbt)
+                .parse::<Offer>()
+                .unwrap(),
+            Offer::Psbt(psbt) if psbt.unsigned_tx == tx
+        ));
+        assert!(matches!(
+            consensus::encode::serialize_hex(&tx).parse::<Offer>().unwrap(),
+            Offer::Transaction(parsed) if parsed == tx
+        ));
+        assert!("!".parse::<Offer>().is_err());
+    }
+}
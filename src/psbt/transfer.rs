@@ -0,0 +1,620 @@
+use {
+    super::*,
+    crate::{
+        dao::{decimal_to_u128, RuneStore},
+        indexer::ScriptType,
+    },
+    bitcoin::{psbt::Psbt, Weight},
+};
+
+/// Output holding runes, spendable by the sender.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RuneUtxo {
+    pub(crate) outpoint: OutPoint,
+    pub(crate) output: TxOut,
+    pub(crate) runes: Vec<(RuneId, u128)>,
+}
+
+/// Amount of a rune to send to a script.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RuneTransfer {
+    pub(crate) destination: ScriptBuf,
+    pub(crate) id: RuneId,
+    pub(crate) amount: u128,
+}
+
+/// Builds an unsigned transaction sending runes. Runes in the selected inputs
+/// that are not sent are kept in a change output the runestone points to, so
+/// they are never left to the default output, burned in the OP_RETURN or
+/// burned by a cenotaph.
+#[derive(Debug)]
+pub(crate) struct TransferBuilder {
+    pub(crate) rune_utxos: Vec<RuneUtxo>,
+    /// outputs holding no runes, used to pay for postage and fees
+    pub(crate) funding: Vec<(OutPoint, TxOut)>,
+    pub(crate) transfers: Vec<RuneTransfer>,
+    /// script receiving leftover runes and bitcoin
+    pub(crate) change: ScriptBuf,
+    /// in sats per vbyte
+    pub(crate) fee_rate: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct UnsignedTransfer {
+    pub(crate) psbt: Psbt,
+    pub(crate) fee: u64,
+    pub(crate) vsize: u64,
+}
+
+impl TransferBuilder {
+    /// Load the unspent rune outputs of `source` from the index, and the
+    /// `funding` outputs from the node, which must hold no runes.
+    pub(crate) fn load(
+        client: &Client,
+        store: &mut dyn RuneStore,
+        source: &str,
+        funding: &[OutPoint],
+        transfers: Vec<RuneTransfer>,
+        change: ScriptBuf,
+        fee_rate: u64,
+    ) -> Result<Self> {
+        let mut runes = BTreeMap::<OutPoint, Vec<(RuneId, u128)>>::new();
+        for balance in store.load_unspent_by_address(source)? {
+            let outpoint = OutPoint::from_str(&balance.out_point)?;
+            let amount = decimal_to_u128(&balance.amount)
+                .with_context(|| format!("invalid balance amount of {outpoint}"))?;
+
+            runes
+                .entry(outpoint)
+                .or_default()
+                .push((RuneId::from_str(&balance.rune_id)?, amount));
+        }
+
+        let mut rune_utxos = Vec::new();
+        for (outpoint, runes) in runes {
+            // outputs spent since they were indexed are skipped
+            let Some(output) = Self::get_tx_out(client, outpoint)? else {
+                continue;
+            };
+
+            rune_utxos.push(RuneUtxo {
+                outpoint,
+                output,
+                runes,
+            });
+        }
+
+        let outpoints = funding
+            .iter()
+            .map(|outpoint| outpoint.to_string())
+            .collect();
+        if let Some(balance) = store
+            .load_by_outpoints(outpoints)?
+            .into_iter()
+            .find(|balance| !balance.spent)
+        {
+            bail!("funding output {} holds runes", balance.out_point);
+        }
+
+        let funding = funding
+            .iter()
+            .map(|outpoint| {
+                Self::get_tx_out(client, *outpoint)?
+                    .map(|output| (*outpoint, output))
+                    .ok_or_else(|| anyhow!("funding output {outpoint} is spent or unknown"))
+            })
+            .collect::<Result<Vec<(OutPoint, TxOut)>>>()?;
+
+        Ok(Self {
+            rune_utxos,
+            funding,
+            transfers,
+            change,
+            fee_rate,
+        })
+    }
+
+    // unspent output, including ones created by mempool transactions
+    fn get_tx_out(client: &Client, outpoint: OutPoint) -> Result<Option<TxOut>> {
+        Ok(client
+            .get_tx_out(&outpoint.txid, outpoint.vout, Some(true))?
+            .map(|output| TxOut {
+                value: output.value.to_sat(),
+                script_pubkey: ScriptBuf::from_bytes(output.script_pub_key.hex),
+            }))
+    }
+
+    pub(crate) fn build(&self) -> Result<UnsignedTransfer> {
+        ensure!(!self.transfers.is_empty(), "no runes to transfer");
+        ensure!(self.fee_rate > 0, "fee rate must be at least 1 sat/vB");
+        ensure!(
+            !self.change.is_op_return(),
+            "change can't be sent to an OP_RETURN output"
+        );
+
+        let mut needed = BTreeMap::<RuneId, u128>::new();
+        for transfer in &self.transfers {
+            ensure!(transfer.amount > 0, "transfer of zero {}", transfer.id);
+            ensure!(
+                !transfer.destination.is_op_return(),
+                "runes can't be sent to an OP_RETURN output"
+            );
+
+            let total = needed.entry(transfer.id).or_default();
+            *total = total
+                .checked_add(transfer.amount)
+                .ok_or_else(|| anyhow!("transfer amount of {} overflows", transfer.id))?;
+        }
+
+        let inputs = self.select_rune_utxos(&needed)?;
+
+        let mut held = BTreeMap::<RuneId, u128>::new();
+        for utxo in &inputs {
+            for (id, amount) in &utxo.runes {
+                let total = held.entry(*id).or_default();
+                *total = total
+                    .checked_add(*amount)
+                    .ok_or_else(|| anyhow!("balance of {id} overflows"))?;
+            }
+        }
+
+        let leftover = held
+            .iter()
+            .any(|(id, amount)| *amount > needed.get(id).copied().unwrap_or_default());
+
+        // one output per destination, in the order they first appear
+        let mut destinations = Vec::<ScriptBuf>::new();
+        let mut edicts = Vec::new();
+        for transfer in &self.transfers {
+            let output = match destinations
+                .iter()
+                .position(|destination| *destination == transfer.destination)
+            {
+                Some(output) => output,
+                None => {
+                    destinations.push(transfer.destination.clone());
+                    destinations.len() - 1
+                }
+            };
+
+            edicts.push(Edict {
+                id: transfer.id,
+                amount: transfer.amount,
+                output: output.try_into()?,
+            });
+        }
+
+        // edicts are encoded sorted by id, and are sorted here as well so the
+        // runestone compares equal to the one deciphered from the transaction
+        edicts.sort_by_key(|edict| edict.id);
+
+        let mut output = destinations
+            .into_iter()
+            .map(|script_pubkey| TxOut {
+                value: TARGET_POSTAGE.to_sat(),
+                script_pubkey,
+            })
+            .collect::<Vec<TxOut>>();
+
+        let pointer = if leftover {
+            output.push(TxOut {
+                value: TARGET_POSTAGE.to_sat(),
+                script_pubkey: self.change.clone(),
+            });
+            Some((output.len() - 1).try_into()?)
+        } else {
+            None
+        };
+
+        let runestone = Runestone {
+            edicts,
+            pointer,
+            ..default()
+        };
+
+        output.push(TxOut {
+            value: 0,
+            script_pubkey: runestone.encipher(),
+        });
+
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: inputs
+                .iter()
+                .map(|utxo| Self::unsigned_input(utxo.outpoint))
+                .collect(),
+            output,
+        };
+
+        ensure!(
+            Runestone::decipher(&tx) == Some(Artifact::Runestone(runestone)),
+            "transfer runestone does not round trip"
+        );
+
+        let mut spent = inputs
+            .iter()
+            .map(|utxo| utxo.output.clone())
+            .collect::<Vec<TxOut>>();
+
+        let fee = self.fund(&mut tx, &mut spent)?;
+        let vsize = Self::weight(&tx, &spent)?.to_vbytes_ceil();
+
+        let mut psbt = Psbt::from_unsigned_tx(tx)?;
+        for (input, output) in psbt.inputs.iter_mut().zip(spent) {
+            if output.script_pubkey.is_witness_program() {
+                input.witness_utxo = Some(output);
+            }
+        }
+
+        Ok(UnsignedTransfer { psbt, fee, vsize })
+    }
+
+    // for each rune, the outputs holding the most of it are used first
+    fn select_rune_utxos(&self, needed: &BTreeMap<RuneId, u128>) -> Result<Vec<RuneUtxo>> {
+        let mut selected = Vec::<RuneUtxo>::new();
+
+        for (id, needed) in needed {
+            let amount = |utxo: &RuneUtxo| {
+                utxo.runes
+                    .iter()
+                    .filter(|(rune_id, _)| rune_id == id)
+                    .map(|(_, amount)| *amount)
+                    .sum::<u128>()
+            };
+
+            let mut have = selected.iter().map(amount).sum::<u128>();
+
+            let mut candidates = self
+                .rune_utxos
+                .iter()
+                .filter(|utxo| !selected.contains(utxo) && amount(utxo) > 0)
+                .collect::<Vec<&RuneUtxo>>();
+            candidates.sort_by_key(|utxo| Reverse(amount(utxo)));
+
+            for utxo in candidates {
+                if have >= *needed {
+                    break;
+                }
+
+                have += amount(utxo);
+                selected.push(utxo.clone());
+            }
+
+            ensure!(
+                have >= *needed,
+                "insufficient {id} balance: {have} held, {needed} needed"
+            );
+        }
+
+        Ok(selected)
+    }
+
+    // add funding inputs until they pay for the outputs and fee, returning the
+    // fee. change below the dust limit is left to the fee.
+    fn fund(&self, tx: &mut Transaction, spent: &mut Vec<TxOut>) -> Result<u64> {
+        let mut funding = self.funding.clone();
+        funding.sort_by_key(|(_, output)| Reverse(output.value));
+        let mut funding = funding.into_iter();
+
+        let change = TxOut {
+            value: 0,
+            script_pubkey: self.change.clone(),
+        };
+        let dust = self.change.dust_value().to_sat();
+
+        loop {
+            let input_value = spent.iter().map(|output| output.value).sum::<u64>();
+            let output_value = tx.output.iter().map(|output| output.value).sum::<u64>();
+
+            tx.output.push(change.clone());
+            let fee_with_change = self.fee(tx, spent)?;
+            tx.output.pop();
+
+            if let Some(value) = input_value
+                .checked_sub(output_value)
+                .and_then(|excess| excess.checked_sub(fee_with_change))
+                .filter(|value| *value >= dust)
+            {
+                tx.output.push(TxOut {
+                    value,
+                    ..change.clone()
+                });
+                return Ok(fee_with_change);
+            }
+
+            let fee = self.fee(tx, spent)?;
+            if let Some(excess) = input_value.checked_sub(output_value + fee) {
+                return Ok(fee + excess);
+            }
+
+            let Some((outpoint, output)) = funding.next() else {
+                bail!(
+                    "insufficient funds: {input_value} sats available, {} needed",
+                    output_value + fee
+                );
+            };
+
+            tx.input.push(Self::unsigned_input(outpoint));
+            spent.push(output);
+        }
+    }
+
+    fn fee(&self, tx: &Transaction, spent: &[TxOut]) -> Result<u64> {
+        Self::weight(tx, spent)?
+            .to_vbytes_ceil()
+            .checked_mul(self.fee_rate)
+            .ok_or_else(|| anyhow!("fee overflows"))
+    }
+
+    // weight of `tx` once its inputs, spending `spent`, are signed
+    fn weight(tx: &Transaction, spent: &[TxOut]) -> Result<Weight> {
+        let mut weight = tx.weight().to_wu();
+
+        // segwit marker and flag
+        weight += 2;
+
+        for output in spent {
+            // signatures are counted at their maximum size
+            weight += match ScriptType::from_script(&output.script_pubkey) {
+                ScriptType::P2tr => 66,
+                ScriptType::P2wpkh => 108,
+                ScriptType::P2pkh => 107 * 4,
+                // P2SH-P2WPKH, the only kind of P2SH supported
+                ScriptType::P2sh => 23 * 4 + 108,
+                script_type => bail!(
+                    "can't estimate the size of spending {script_type} output {}",
+                    output.script_pubkey
+                ),
+            };
+        }
+
+        Ok(Weight::from_wu(weight))
+    }
+
+    fn unsigned_input(previous_output: OutPoint) -> TxIn {
+        TxIn {
+            previous_output,
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            dao::outpoint,
+            indexer::{Lot, RuneIndexer},
+        },
+        bitcoincore_rpc::Auth,
+        ordinals::inscription_id::txid,
+    };
+
+    const ID: RuneId = RuneId { block: 10, tx: 1 };
+    const OTHER: RuneId = RuneId { block: 20, tx: 2 };
+
+    fn script(n: u8) -> ScriptBuf {
+        ScriptBuf::new_v0_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([n; 20]))
+    }
+
+    fn rune_utxo(n: u8, runes: Vec<(RuneId, u128)>) -> RuneUtxo {
+        RuneUtxo {
+            outpoint: outpoint(txid(n.into()), 0),
+            output: TxOut {
+                value: 546,
+                script_pubkey: script(0),
+            },
+            runes,
+        }
+    }
+
+    fn builder(transfers: Vec<RuneTransfer>) -> TransferBuilder {
+        TransferBuilder {
+            rune_utxos: vec![
+                rune_utxo(1, vec![(ID, 100)]),
+                rune_utxo(2, vec![(ID, 1000), (OTHER, 5)]),
+                rune_utxo(3, vec![(OTHER, 50)]),
+            ],
+            funding: vec![
+                (
+                    outpoint(txid(10), 0),
+                    TxOut {
+                        value: 5_000,
+                        script_pubkey: script(0),
+                    },
+                ),
+                (
+                    outpoint(txid(11), 0),
+                    TxOut {
+                        value: 100_000,
+                        script_pubkey: script(0),
+                    },
+                ),
+            ],
+            transfers,
+            change: script(0),
+            fee_rate: 10,
+        }
+    }
+
+    fn transfer(n: u8, id: RuneId, amount: u128) -> RuneTransfer {
+        RuneTransfer {
+            destination: script(n),
+            id,
+            amount,
+        }
+    }
+
+    // index the transfer, returning the runes held by each of its outputs
+    fn index(builder: &TransferBuilder, tx: &Transaction) -> Vec<Vec<(RuneId, Lot)>> {
+        let client = Client::new("http://127.0.0.1:1", Auth::None).unwrap();
+        let mut indexer = RuneIndexer {
+            block_hash: BlockHash::all_zeros(),
+            block_time: 0,
+            client: &client,
+            height: 100,
+            minimum: Rune::minimum_at_height(Network::Regtest, Height(100)),
+            network: Network::Regtest,
+            runes: 2,
+            cache: default(),
+        };
+
+        indexer.cache.prevouts = Some(HashMap::new());
+        for id in [ID, OTHER] {
+            indexer.cache.insert_loaded(
+                id,
+                RuneEntry {
+                    block: id.block,
+                    spaced_rune: SpacedRune {
+                        rune: Rune(id.block.into()),
+                        spacers: 0,
+                    },
+                    ..default()
+                },
+            );
+        }
+        for utxo in &builder.rune_utxos {
+            indexer.cache.balances.insert(
+                utxo.outpoint,
+                utxo.runes
+                    .iter()
+                    .map(|(id, amount)| (*id, Lot(*amount)))
+                    .collect(),
+            );
+        }
+
+        indexer.parse_tx(1, tx, tx.txid()).unwrap();
+
+        assert!(indexer
+            .cache
+            .entries
+            .values()
+            .all(|entry| entry.burned == 0));
+
+        (0..tx.output.len())
+            .map(|vout| {
+                indexer
+                    .cache
+                    .balances
+                    .get(&OutPoint {
+                        txid: tx.txid(),
+                        vout: vout.try_into().unwrap(),
+                    })
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leftover_runes_go_to_change() {
+        let builder = builder(vec![transfer(1, ID, 150), transfer(2, OTHER, 5)]);
+        let transfer = builder.build().unwrap();
+        let tx = transfer.psbt.unsigned_tx.clone();
+
+        // the output holding the most of the first rune is selected, and
+        // already holds enough of the second
+        assert_eq!(tx.input[0].previous_output, outpoint(txid(2), 0));
+        assert_eq!(tx.input[1].previous_output, outpoint(txid(11), 0));
+        assert_eq!(tx.input.len(), 2);
+
+        assert_eq!(
+            index(&builder, &tx),
+            vec![
+                vec![(ID, Lot(150))],
+                vec![(OTHER, Lot(5))],
+                vec![(ID, Lot(850))],
+                Vec::new(),
+                Vec::new(),
+            ]
+        );
+
+        assert_eq!(tx.output[3].value, 0);
+        assert_eq!(tx.output[4].script_pubkey, script(0));
+        assert_eq!(
+            transfer.psbt.inputs[0].witness_utxo,
+            Some(builder.rune_utxos[1].output.clone())
+        );
+
+        let input_value = 546 + 100_000;
+        let output_value = tx.output.iter().map(|output| output.value).sum::<u64>();
+        assert_eq!(input_value - output_value, transfer.fee);
+        assert_eq!(transfer.fee, transfer.vsize * 10);
+    }
+
+    #[test]
+    fn exact_transfer_has_no_rune_change() {
+        let builder = builder(vec![
+            transfer(1, ID, 600),
+            transfer(1, OTHER, 55),
+            transfer(1, ID, 500),
+        ]);
+        let tx = builder.build().unwrap().psbt.unsigned_tx;
+
+        assert_eq!(
+            tx.input[..3]
+                .iter()
+                .map(|input| input.previous_output)
+                .collect::<Vec<OutPoint>>(),
+            vec![
+                outpoint(txid(2), 0),
+                outpoint(txid(1), 0),
+                outpoint(txid(3), 0)
+            ]
+        );
+        assert_eq!(
+            Runestone::decipher(&tx),
+            Some(Artifact::Runestone(Runestone {
+                edicts: vec![
+                    Edict {
+                        id: ID,
+                        amount: 600,
+                        output: 0,
+                    },
+                    Edict {
+                        id: ID,
+                        amount: 500,
+                        output: 0,
+                    },
+                    Edict {
+                        id: OTHER,
+                        amount: 55,
+                        output: 0,
+                    },
+                ],
+                ..default()
+            }))
+        );
+        assert_eq!(
+            index(&builder, &tx)[0],
+            vec![(ID, Lot(1100)), (OTHER, Lot(55))]
+        );
+    }
+
+    #[test]
+    fn insufficient_runes() {
+        assert_eq!(
+            builder(vec![transfer(1, OTHER, 56)])
+                .build()
+                .unwrap_err()
+                .to_string(),
+            "insufficient 20:2 balance: 55 held, 56 needed"
+        );
+    }
+
+    #[test]
+    fn insufficient_funds() {
+        let mut builder = builder(vec![transfer(1, ID, 1)]);
+        builder.funding.truncate(1);
+
+        assert!(builder
+            .build()
+            .unwrap_err()
+            .to_string()
+            .starts_with("insufficient funds"));
+    }
+}
@@ -1,87 +1,564 @@
-use bitcoin::{script::Instruction, sighash::EcdsaSighashType};
-use log::info;
-
-use super::*;
-
-pub(super) fn parse_tx(tx: &Transaction) -> Result<()> {
-    for input in tx.input.iter() {
-        let sig_hash_type = match input.script_sig.instructions().next() {
-            Some(push) => match push {
-                Ok(Instruction::PushBytes(b)) => {
-                    let sig_hash_type = b[b.len() - 1];
-                    Some(sig_hash_type)
-                }
-                _ => None,
-            },
-            None => None,
-        };
+use {
+    super::*,
+    bitcoin::{
+        ecdsa,
+        psbt::{Input, Psbt},
+        script::Instruction,
+        sighash::{EcdsaSighashType, TapSighashType},
+        taproot,
+    },
+};
 
-        let sig_type = match sig_hash_type {
-            Some(t) => Some(EcdsaSighashType::from_consensus(t as u32)),
-            None => match input.witness.tapscript() {
-                Some(s) => match s.instructions().next() {
-                    Some(Ok(Instruction::PushBytes(b))) => {
-                        let sig_hash_type = b[b.len() - 1];
-                        Some(EcdsaSighashType::from_consensus(sig_hash_type as u32))
-                    }
-                    _ => None,
-                },
-                None => None,
-            },
-        };
+/// Sighash type of an input signature, for ECDSA and Schnorr signatures alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SerializeDisplay)]
+pub(crate) enum SighashType {
+    /// taproot signature without a sighash byte, which signs like `All`
+    Default,
+    All,
+    None,
+    Single,
+    AllPlusAnyoneCanPay,
+    NonePlusAnyoneCanPay,
+    SinglePlusAnyoneCanPay,
+}
+
+impl SighashType {
+    pub(crate) fn anyone_can_pay(self) -> bool {
+        matches!(
+            self,
+            Self::AllPlusAnyoneCanPay | Self::NonePlusAnyoneCanPay | Self::SinglePlusAnyoneCanPay
+        )
+    }
+
+    /// Whether the signature commits to every output.
+    pub(crate) fn all(self) -> bool {
+        matches!(self, Self::Default | Self::All | Self::AllPlusAnyoneCanPay)
+    }
+
+    pub(crate) fn none(self) -> bool {
+        matches!(self, Self::None | Self::NonePlusAnyoneCanPay)
+    }
+
+    pub(crate) fn single(self) -> bool {
+        matches!(self, Self::Single | Self::SinglePlusAnyoneCanPay)
+    }
+}
 
-        match sig_type {
-            Some(t) => {
-                if t == EcdsaSighashType::Single || t == EcdsaSighashType::None {
-                    info!("Found! txid: {}, input: {:?}", tx.txid(), input);
-                } else {
-                    info!("Not Found! txid: {}, type: {:?}", tx.txid(), t);
+impl Display for SighashType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "DEFAULT"),
+            Self::All => write!(f, "ALL"),
+            Self::None => write!(f, "NONE"),
+            Self::Single => write!(f, "SINGLE"),
+            Self::AllPlusAnyoneCanPay => write!(f, "ALL|ANYONECANPAY"),
+            Self::NonePlusAnyoneCanPay => write!(f, "NONE|ANYONECANPAY"),
+            Self::SinglePlusAnyoneCanPay => write!(f, "SINGLE|ANYONECANPAY"),
+        }
+    }
+}
+
+impl From<EcdsaSighashType> for SighashType {
+    fn from(sighash: EcdsaSighashType) -> Self {
+        match sighash {
+            EcdsaSighashType::All => Self::All,
+            EcdsaSighashType::None => Self::None,
+            EcdsaSighashType::Single => Self::Single,
+            EcdsaSighashType::AllPlusAnyoneCanPay => Self::AllPlusAnyoneCanPay,
+            EcdsaSighashType::NonePlusAnyoneCanPay => Self::NonePlusAnyoneCanPay,
+            EcdsaSighashType::SinglePlusAnyoneCanPay => Self::SinglePlusAnyoneCanPay,
+        }
+    }
+}
+
+impl From<TapSighashType> for SighashType {
+    fn from(sighash: TapSighashType) -> Self {
+        match sighash {
+            TapSighashType::Default => Self::Default,
+            TapSighashType::All => Self::All,
+            TapSighashType::None => Self::None,
+            TapSighashType::Single => Self::Single,
+            TapSighashType::AllPlusAnyoneCanPay => Self::AllPlusAnyoneCanPay,
+            TapSighashType::NonePlusAnyoneCanPay => Self::NonePlusAnyoneCanPay,
+            TapSighashType::SinglePlusAnyoneCanPay => Self::SinglePlusAnyoneCanPay,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SignatureKind {
+    Ecdsa,
+    Schnorr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) struct InputSignature {
+    pub(crate) kind: SignatureKind,
+    pub(crate) sighash: SighashType,
+}
+
+/// Output a `SINGLE` signature commits to. For a listing it is the seller's
+/// payment, which callers compare with the price and payee they expect.
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct PinnedOutput {
+    pub(crate) vout: u32,
+    pub(crate) value: u64,
+    pub(crate) script_pubkey: ScriptBuf,
+    pub(crate) address: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct InputDetail {
+    pub(crate) previous_output: OutPoint,
+    pub(crate) signatures: Vec<InputSignature>,
+    pub(crate) runes: Vec<(RuneId, u128)>,
+    pub(crate) pinned_output: Option<PinnedOutput>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OfferKind {
+    /// no input is signed yet
+    Unsigned,
+    /// rune inputs are signed `SINGLE|ANYONECANPAY`, so a buyer can add their
+    /// own inputs and outputs as long as the seller's payment is kept
+    RuneListing,
+    /// every signature commits to all outputs
+    Transaction,
+    /// any other combination of signatures
+    Other,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct TxDetail {
+    pub(crate) txid: Txid,
+    pub(crate) kind: OfferKind,
+    pub(crate) inputs: Vec<InputDetail>,
+    /// reasons the offer may lose runes or payment to a third party
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Signatures of a signed transaction input, or of a PSBT input if `psbt` is
+/// given. `taproot` tells whether the output spent is a taproot output, if
+/// known, since only then can a 64 or 65 byte push be a Schnorr signature.
+fn input_signatures(
+    input: &TxIn,
+    psbt: Option<&Input>,
+    taproot: Option<bool>,
+) -> Vec<InputSignature> {
+    let mut signatures = Vec::new();
+
+    let ecdsa = |signature: &ecdsa::Signature| InputSignature {
+        kind: SignatureKind::Ecdsa,
+        sighash: signature.hash_ty.into(),
+    };
+    let schnorr = |signature: &taproot::Signature| InputSignature {
+        kind: SignatureKind::Schnorr,
+        sighash: signature.hash_ty.into(),
+    };
+
+    if let Some(psbt) = psbt {
+        signatures.extend(psbt.partial_sigs.values().map(ecdsa));
+        signatures.extend(psbt.tap_key_sig.iter().map(schnorr));
+        signatures.extend(psbt.tap_script_sigs.values().map(schnorr));
+    }
+
+    let script_sig = psbt
+        .and_then(|psbt| psbt.final_script_sig.as_ref())
+        .unwrap_or(&input.script_sig);
+
+    for instruction in script_sig.instructions() {
+        if let Ok(Instruction::PushBytes(push)) = instruction {
+            if let Ok(signature) = ecdsa::Signature::from_slice(push.as_bytes()) {
+                signatures.push(ecdsa(&signature));
+            }
+        }
+    }
+
+    let witness = psbt
+        .and_then(|psbt| psbt.final_script_witness.as_ref())
+        .unwrap_or(&input.witness);
+
+    let mut elements = witness.iter().collect::<Vec<&[u8]>>();
+
+    // a last element starting with 0x50 is a taproot annex
+    if elements.len() > 1 && elements.last().and_then(|last| last.first()) == Some(&0x50) {
+        elements.pop();
+    }
+
+    // a taproot script path spend ends with the script and control block
+    let schnorr_elements = match elements.len() {
+        1 => 1,
+        len => {
+            if witness.tapscript().is_some() {
+                len - 2
+            } else {
+                0
+            }
+        }
+    };
+
+    for (i, element) in elements.iter().enumerate() {
+        if let Ok(signature) = ecdsa::Signature::from_slice(element) {
+            signatures.push(ecdsa(&signature));
+        } else if i < schnorr_elements && taproot != Some(false) {
+            if let Ok(signature) = taproot::Signature::from_slice(element) {
+                signatures.push(schnorr(&signature));
+            }
+        }
+    }
+
+    signatures
+}
+
+/// Analyze a transaction, or the transaction of a PSBT, as a rune offer.
+/// `runes` are the rune balances of the outpoints it spends.
+pub(super) fn parse_tx(
+    tx: &Transaction,
+    psbt: Option<&Psbt>,
+    runes: &HashMap<OutPoint, Vec<(RuneId, u128)>>,
+    network: Network,
+) -> Result<TxDetail> {
+    let mut inputs = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (i, input) in tx.input.iter().enumerate() {
+        let psbt_input = psbt.and_then(|psbt| psbt.inputs.get(i));
+
+        // the output spent, if the PSBT input carries it
+        let spent = psbt_input.and_then(|psbt_input| {
+            psbt_input.witness_utxo.as_ref().or_else(|| {
+                psbt_input.non_witness_utxo.as_ref().and_then(|prev| {
+                    prev.output
+                        .get(usize::try_from(input.previous_output.vout).ok()?)
+                })
+            })
+        });
+
+        let taproot = spent.map(|output| output.script_pubkey.is_v1_p2tr());
+
+        let signatures = input_signatures(input, psbt_input, taproot);
+        let runes = runes
+            .get(&input.previous_output)
+            .cloned()
+            .unwrap_or_default();
+
+        let mut pinned_output = None;
+
+        for signature in &signatures {
+            if signature.sighash.none() {
+                warnings.push(format!(
+                    "input {i} is signed {} and commits to no outputs, so anyone can redirect it",
+                    signature.sighash
+                ));
+            }
+
+            if signature.sighash.single() {
+                match tx.output.get(i) {
+                    Some(output) => {
+                        if output.script_pubkey.is_op_return() || output.value == 0 {
+                            warnings.push(format!(
+                                "input {i} is signed {} but output {i} pays nothing",
+                                signature.sighash
+                            ));
+                        }
+
+                        pinned_output = Some(PinnedOutput {
+                            vout: i.try_into()?,
+                            value: output.value,
+                            script_pubkey: output.script_pubkey.clone(),
+                            address: Address::from_script(&output.script_pubkey, network)
+                                .ok()
+                                .map(|address| address.to_string()),
+                        });
+                    }
+                    None => warnings.push(format!(
+                        "input {i} is signed {} but there is no output {i} to commit to",
+                        signature.sighash
+                    )),
                 }
             }
-            None => (),
+
+            if !runes.is_empty()
+                && signature.sighash.single()
+                && !signature.sighash.anyone_can_pay()
+            {
+                warnings.push(format!(
+                    "input {i} holds runes and is signed {}, so buyers can't add inputs",
+                    signature.sighash
+                ));
+            }
         }
+
+        inputs.push(InputDetail {
+            previous_output: input.previous_output,
+            signatures,
+            runes,
+            pinned_output,
+        });
     }
 
-    Ok(())
+    let signatures = inputs
+        .iter()
+        .flat_map(|input| &input.signatures)
+        .collect::<Vec<&InputSignature>>();
+
+    let kind = if signatures.is_empty() {
+        OfferKind::Unsigned
+    } else if inputs.iter().any(|input| {
+        !input.runes.is_empty()
+            && !input.signatures.is_empty()
+            && input
+                .signatures
+                .iter()
+                .all(|signature| signature.sighash == SighashType::SinglePlusAnyoneCanPay)
+    }) {
+        OfferKind::RuneListing
+    } else if signatures.iter().all(|signature| signature.sighash.all()) {
+        OfferKind::Transaction
+    } else {
+        OfferKind::Other
+    };
+
+    Ok(TxDetail {
+        txid: tx.txid(),
+        kind,
+        inputs,
+        warnings,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use bitcoin::{consensus::Decodable, hashes, Transaction};
+    use {super::*, bitcoin::hashes::hex::FromHex};
+
+    fn decode(hex: &str) -> Transaction {
+        consensus::deserialize(&Vec::<u8>::from_hex(hex).unwrap()).unwrap()
+    }
 
-    use super::parse_tx;
+    fn sighashes(tx: &Transaction) -> Vec<Vec<(SignatureKind, SighashType)>> {
+        parse_tx(tx, None, &HashMap::new(), Network::Bitcoin)
+            .unwrap()
+            .inputs
+            .into_iter()
+            .map(|input| {
+                input
+                    .signatures
+                    .into_iter()
+                    .map(|signature| (signature.kind, signature.sighash))
+                    .collect()
+            })
+            .collect()
+    }
 
     #[test]
     fn test_tx_witness_type_all() {
-        env_logger::init();
-        let raw_tx = <Vec<u8> as hashes::hex::FromHex>::from_hex("01000000000101b0e2d7ce5d23477af9eeb9c5a8f4863f94562b285916f2ca2d39d207d6fc43600000000000ffffffff022aa90d00000000001600146414fb09febc915551e92d90eaec1181faa8b258ac82030000000000160014b34e3991855417654e0ad20ad012e06480a457bb02483045022100b7da811a3bd23fdeb3d432361e1af07f0aa938844ecc1a8596828e46bbe5ed6a02204d02bd97fd26042fa75ea1f68f46e2f35e1b75909dae5710b6f768745fd6dca80121038de7eec284e374c0b0b38ce3d3672ba93f66c06c0131574feb5206dff61585de00000000").unwrap();
-        let tx: Transaction = Decodable::consensus_decode(&mut raw_tx.as_slice()).unwrap();
-        match parse_tx(&tx) {
-            Ok(_) => {}
-            Err(e) => println!("{}", e),
-        }
+        let tx = decode("01000000000101b0e2d7ce5d23477af9eeb9c5a8f4863f94562b285916f2ca2d39d207d6fc43600000000000ffffffff022aa90d00000000001600146414fb09febc915551e92d90eaec1181faa8b258ac82030000000000160014b34e3991855417654e0ad20ad012e06480a457bb02483045022100b7da811a3bd23fdeb3d432361e1af07f0aa938844ecc1a8596828e46bbe5ed6a02204d02bd97fd26042fa75ea1f68f46e2f35e1b75909dae5710b6f768745fd6dca80121038de7eec284e374c0b0b38ce3d3672ba93f66c06c0131574feb5206dff61585de00000000");
+
+        assert_eq!(
+            sighashes(&tx),
+            vec![vec![(SignatureKind::Ecdsa, SighashType::All)]]
+        );
     }
 
     #[test]
     fn test_tx_script_sig_type_all() {
-        env_logger::init();
-        let raw_tx = <Vec<u8> as hashes::hex::FromHex>::from_hex("01000000011cfd1d332444f740bcfe6bc80de53da6c54a111941faedf56c25bb68d129160c000000006a47304402203ff7162d6635246dbf59b7fa9e72e3023e959a73b1fbc51edbaaa5a8dbc6d2f70220776e2fa5740df01cc0ac47bda713e87fc59044960122ba45abb11c949655c584012103bc3c9134f5a5e3f08287d175d7e43368f72cb93a2e6cbb801b5e90d1ed628e60ffffffff01fa3b1d00000000001976a91429ad791e5913f9c4965ce084849ad7c810b4a07a88ac00000000").unwrap();
-        let tx: Transaction = Decodable::consensus_decode(&mut raw_tx.as_slice()).unwrap();
-        match parse_tx(&tx) {
-            Ok(_) => {}
-            Err(e) => println!("{}", e),
-        }
-    }
+        let tx = decode("01000000011cfd1d332444f740bcfe6bc80de53da6c54a111941faedf56c25bb68d129160c000000006a47304402203ff7162d6635246dbf59b7fa9e72e3023e959a73b1fbc51edbaaa5a8dbc6d2f70220776e2fa5740df01cc0ac47bda713e87fc59044960122ba45abb11c949655c584012103bc3c9134f5a5e3f08287d175d7e43368f72cb93a2e6cbb801b5e90d1ed628e60ffffffff01fa3b1d00000000001976a91429ad791e5913f9c4965ce084849ad7c810b4a07a88ac00000000");
 
+        assert_eq!(
+            sighashes(&tx),
+            vec![vec![(SignatureKind::Ecdsa, SighashType::All)]]
+        );
+        assert_eq!(
+            parse_tx(&tx, None, &HashMap::new(), Network::Bitcoin)
+                .unwrap()
+                .kind,
+            OfferKind::Transaction
+        );
+    }
 
     #[test]
     fn test_tx_script_sig_type_all_plus_anyonecanpay() {
-        env_logger::init();
-        let raw_tx = <Vec<u8> as hashes::hex::FromHex>::from_hex("020000000001054c4d24324f889f17510326f1802fd9b67be69bf3178a7f732a3eae81a4fd250d0500000000ffffffff4c4d24324f889f17510326f1802fd9b67be69bf3178a7f732a3eae81a4fd250d0600000000ffffffffcbc703d0dcb717554ed55b7d59c1546b3599dde90d07dbfb199148a9919513360000000000ffffffff46832914defffe12ee6bf56a762b7d509c90cd0c4a33d8d7b3a942f516e00c160400000000ffffffff4c4d24324f889f17510326f1802fd9b67be69bf3178a7f732a3eae81a4fd250d0000000000ffffffff075802000000000000160014f84418c3e889f09f3598e164b221c52fda6ba6572202000000000000225120cb03645b892a156641d1bc09ad63aeb33dd7ab070e1a70ab798bf7e5a52f14ee405dc6000000000017a9142d8c0937fe2314b2543196a40a5fa47592315c1987e8fd00000000000016001449a0f76f14c5bc5dfdf7fc1d5dba6a9306c69a12632d3c0300000000225120cb03645b892a156641d1bc09ad63aeb33dd7ab070e1a70ab798bf7e5a52f14ee2c01000000000000160014f84418c3e889f09f3598e164b221c52fda6ba6572c01000000000000160014f84418c3e889f09f3598e164b221c52fda6ba6570247304402205cae7ab389d0de0442d45039dfe3130de3c9464f553e053899b92ee1267c10ec0220410bf90c6aafb6beb017058f9ec09d1c9d24ea53420da77bd3efeb3327d624d201210268b7232856bab37f5d30bffb47aaea1f652bc77f4191a55f4b88a786dd3b766a02483045022100a3135f3ad238b0255ba481f9d298d865a1f62f26e027503133f6827487e7812602201c56d39b5565f33f03ce82d2502f88f71fb6c5f755d0d99141e462f498bcbcee01210268b7232856bab37f5d30bffb47aaea1f652bc77f4191a55f4b88a786dd3b766a01414efafde2431539ee38f4d7e71e695fc5cb13fa55c8260c1170525ca595452cab3c3d69d034827a1f832907e2f1d2e636b51838e3f6512d04438ea377287bdd388301402c5914c7e92f2a025e61293c502112a0f059be60008a63c9e51733acba8610a80021b2e7a6614e958aeb35dd147c40094033137dcb8f6df28dac323a07ce6a6a0247304402205b7f54392e9c91587b61b45830dd262161a8192b5fa6d1075c9bfc67fe43786002202cc8575d2ba60d4c84952bb03bacff6054100b313d517dee544de03da56c9e2d01210268b7232856bab37f5d30bffb47aaea1f652bc77f4191a55f4b88a786dd3b766a00000000").unwrap();
-        let tx: Transaction = Decodable::consensus_decode(&mut raw_tx.as_slice()).unwrap();
-        match parse_tx(&tx) {
-            Ok(_) => {}
-            Err(e) => println!("{}", e),
+        let tx = decode("020000000001054c4d24324f889f17510326f1802fd9b67be69bf3178a7f732a3eae81a4fd250d0500000000ffffffff4c4d24324f889f17510326f1802fd9b67be69bf3178a7f732a3eae81a4fd250d0600000000ffffffffcbc703d0dcb717554ed55b7d59c1546b3599dde90d07dbfb199148a9919513360000000000ffffffff46832914defffe12ee6bf56a762b7d509c90cd0c4a33d8d7b3a942f516e00c160400000000ffffffff4c4d24324f889f17510326f1802fd9b67be69bf3178a7f732a3eae81a4fd250d0000000000ffffffff075802000000000000160014f84418c3e889f09f3598e164b221c52fda6ba6572202000000000000225120cb03645b892a156641d1bc09ad63aeb33dd7ab070e1a70ab798bf7e5a52f14ee405dc6000000000017a9142d8c0937fe2314b2543196a40a5fa47592315c1987e8fd00000000000016001449a0f76f14c5bc5dfdf7fc1d5dba6a9306c69a12632d3c0300000000225120cb03645b892a156641d1bc09ad63aeb33dd7ab070e1a70ab798bf7e5a52f14ee2c01000000000000160014f84418c3e889f09f3598e164b221c52fda6ba6572c01000000000000160014f84418c3e889f09f3598e164b221c52fda6ba6570247304402205cae7ab389d0de0442d45039dfe3130de3c9464f553e053899b92ee1267c10ec0220410bf90c6aafb6beb017058f9ec09d1c9d24ea53420da77bd3efeb3327d624d201210268b7232856bab37f5d30bffb47aaea1f652bc77f4191a55f4b88a786dd3b766a02483045022100a3135f3ad238b0255ba481f9d298d865a1f62f26e027503133f6827487e7812602201c56d39b5565f33f03ce82d2502f88f71fb6c5f755d0d99141e462f498bcbcee01210268b7232856bab37f5d30bffb47aaea1f652bc77f4191a55f4b88a786dd3b766a01414efafde2431539ee38f4d7e71e695fc5cb13fa55c8260c1170525ca595452cab3c3d69d034827a1f832907e2f1d2e636b51838e3f6512d04438ea377287bdd388301402c5914c7e92f2a025e61293c502112a0f059be60008a63c9e51733acba8610a80021b2e7a6614e958aeb35dd147c40094033137dcb8f6df28dac323a07ce6a6a0247304402205b7f54392e9c91587b61b45830dd262161a8192b5fa6d1075c9bfc67fe43786002202cc8575d2ba60d4c84952bb03bacff6054100b313d517dee544de03da56c9e2d01210268b7232856bab37f5d30bffb47aaea1f652bc77f4191a55f4b88a786dd3b766a00000000");
+
+        assert_eq!(
+            sighashes(&tx),
+            vec![
+                vec![(SignatureKind::Ecdsa, SighashType::All)],
+                vec![(SignatureKind::Ecdsa, SighashType::All)],
+                vec![(SignatureKind::Schnorr, SighashType::SinglePlusAnyoneCanPay)],
+                vec![(SignatureKind::Schnorr, SighashType::Default)],
+                vec![(SignatureKind::Ecdsa, SighashType::All)],
+            ]
+        );
+
+        // the third input is a rune listing filled by the buyer
+        let listing = tx.input[2].previous_output;
+        let detail = parse_tx(
+            &tx,
+            None,
+            &[(
+                listing,
+                vec![(
+                    RuneId {
+                        block: 840000,
+                        tx: 1,
+                    },
+                    100,
+                )],
+            )]
+            .into_iter()
+            .collect(),
+            Network::Bitcoin,
+        )
+        .unwrap();
+
+        assert_eq!(detail.kind, OfferKind::RuneListing);
+        assert!(detail.warnings.is_empty());
+        assert_eq!(
+            detail.inputs[2].pinned_output,
+            Some(PinnedOutput {
+                vout: 2,
+                value: 13_000_000,
+                script_pubkey: tx.output[2].script_pubkey.clone(),
+                address: Some("35qr63n929U59W9mtvAN5bVxEM6LDVYVPc".into()),
+            })
+        );
+    }
+
+    #[test]
+    fn unsafe_offers() {
+        let listing = OutPoint {
+            txid: Txid::all_zeros(),
+            vout: 0,
+        };
+
+        let signed = |hash_ty: TapSighashType, outputs: Vec<TxOut>| Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: listing,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::from_slice(&[taproot::Signature {
+                    sig: bitcoin::secp256k1::schnorr::Signature::from_slice(&[1; 64]).unwrap(),
+                    hash_ty,
+                }
+                .to_vec()]),
+            }],
+            output: outputs,
+        };
+
+        let runes = [(listing, vec![(RuneId { block: 1, tx: 0 }, 1)])]
+            .into_iter()
+            .collect();
+
+        let detail = parse_tx(
+            &signed(TapSighashType::NonePlusAnyoneCanPay, Vec::new()),
+            None,
+            &runes,
+            Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(detail.kind, OfferKind::Other);
+        assert_eq!(
+            detail.warnings,
+            ["input 0 is signed NONE|ANYONECANPAY and commits to no outputs, so anyone can redirect it"]
+        );
+
+        let detail = parse_tx(
+            &signed(TapSighashType::SinglePlusAnyoneCanPay, Vec::new()),
+            None,
+            &runes,
+            Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(detail.kind, OfferKind::RuneListing);
+        assert_eq!(
+            detail.warnings,
+            ["input 0 is signed SINGLE|ANYONECANPAY but there is no output 0 to commit to"]
+        );
+
+        let detail = parse_tx(
+            &signed(
+                TapSighashType::Single,
+                vec![TxOut {
+                    value: 0,
+                    script_pubkey: ScriptBuf::new(),
+                }],
+            ),
+            None,
+            &runes,
+            Network::Bitcoin,
+        )
+        .unwrap();
+        assert_eq!(detail.kind, OfferKind::Other);
+        assert_eq!(
+            detail.warnings,
+            [
+                "input 0 is signed SINGLE but output 0 pays nothing",
+                "input 0 holds runes and is signed SINGLE, so buyers can't add inputs",
+            ]
+        );
+    }
+
+    #[test]
+    fn listings_report_the_payment_to_the_seller() {
+        let seller = ScriptBuf::from_bytes(vec![0x51]);
+
+        let listing = |payee: ScriptBuf| {
+            let mut psbt = Psbt::from_unsigned_tx(Transaction {
+                version: 2,
+                lock_time: LockTime::ZERO,
+                input: vec![TxIn {
+                    previous_output: OutPoint {
+                        txid: Txid::all_zeros(),
+                        vout: 0,
+                    },
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                }],
+                output: vec![TxOut {
+                    value: 10_000,
+                    script_pubkey: payee,
+                }],
+            })
+            .unwrap();
+
+            psbt.inputs[0].witness_utxo = Some(TxOut {
+                value: 546,
+                script_pubkey: seller.clone(),
+            });
+            psbt.inputs[0].tap_key_sig = Some(taproot::Signature {
+                sig: bitcoin::secp256k1::schnorr::Signature::from_slice(&[1; 64]).unwrap(),
+                hash_ty: TapSighashType::SinglePlusAnyoneCanPay,
+            });
+
+            parse_tx(
+                &psbt.unsigned_tx,
+                Some(&psbt),
+                &HashMap::new(),
+                Network::Bitcoin,
+            )
+            .unwrap()
+        };
+
+        // sellers are usually paid at an address other than the one holding
+        // their runes
+        for payee in [seller.clone(), ScriptBuf::from_bytes(vec![0x52])] {
+            let detail = listing(payee.clone());
+            assert!(detail.warnings.is_empty());
+            assert_eq!(
+                detail.inputs[0].pinned_output,
+                Some(PinnedOutput {
+                    vout: 0,
+                    value: 10_000,
+                    script_pubkey: payee,
+                    address: None,
+                })
+            );
         }
     }
 }
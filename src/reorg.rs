@@ -0,0 +1,281 @@
+use {
+    super::*,
+    crate::{
+        dao::{decimal_to_u128, RuneStore},
+        indexer::EventType,
+        updater::{BlockData, Updater},
+    },
+};
+
+/// Number of blocks we are willing to walk back looking for a common ancestor
+/// before giving up on a reorg.
+pub(crate) const MAX_RECOVERABLE_REORG_DEPTH: u32 = 100;
+
+/// Number of addresses whose holdings are rebuilt per update while rolling back.
+const REBUILD_BATCH_SIZE: usize = 10_000;
+
+/// Number of outpoints spent before spends were recorded restored per update
+/// while rolling back.
+const UNSPEND_BATCH_SIZE: usize = 10_000;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Error {
+    Recoverable { height: u32, depth: u32 },
+    Unrecoverable,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Recoverable { height, depth } => {
+                write!(f, "{depth} block deep reorg detected at height {height}")
+            }
+            Self::Unrecoverable => write!(f, "unrecoverable reorg detected"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub(crate) struct Reorg {}
+
+impl Reorg {
+    pub(crate) fn detect_reorg(
+        block: &BlockData,
+        height: u32,
+        client: &Client,
+        store: &mut dyn RuneStore,
+    ) -> Result {
+        let Some(prev_height) = height.checked_sub(1) else {
+            return Ok(());
+        };
+
+        let bitcoind_prev_blockhash = block.header.prev_blockhash;
+
+        match store.load_block_hash(prev_height.into())? {
+            Some(index_prev_blockhash) if index_prev_blockhash == bitcoind_prev_blockhash => Ok(()),
+            Some(_) => {
+                for depth in 1..MAX_RECOVERABLE_REORG_DEPTH {
+                    let Some(ancestor) = height.checked_sub(depth) else {
+                        break;
+                    };
+
+                    let Some(index_block_hash) = store.load_block_hash(ancestor.into())? else {
+                        break;
+                    };
+
+                    let bitcoind_block_hash =
+                        client.get_block_hash(ancestor.into()).into_option()?;
+
+                    if Some(index_block_hash) == bitcoind_block_hash {
+                        return Err(anyhow!(Error::Recoverable { height, depth }));
+                    }
+                }
+
+                Err(anyhow!(Error::Unrecoverable))
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn handle_reorg(updater: &mut Updater, height: u32, depth: u32) -> Result {
+        let ancestor = height
+            .checked_sub(depth)
+            .ok_or_else(|| anyhow!("reorg depth {depth} exceeds height {height}"))?;
+
+        Self::rollback_to(updater, ancestor)
+    }
+
+    /// Roll rune state back so that `ancestor` is the last indexed block and
+    /// indexing resumes at `ancestor + 1`.
+    pub(crate) fn rollback_to(updater: &mut Updater, ancestor: u32) -> Result {
+        let height = updater.height;
+
+        log::info!("rolling back rune state from height {height} to {ancestor}");
+
+        // outpoints spent before spends were recorded have no spend block, so
+        // the orphaned blocks are fetched back from the node to find them. a
+        // block the node can't serve fails the rollback rather than leave
+        // them spent on the new branch. spends recorded since are rolled back
+        // by their block, so without older ones no block is fetched
+        let mut spent_outpoints = HashSet::new();
+        if updater.store.has_unrecorded_spends()? {
+            for orphan in (ancestor + 1)..height {
+                let Some(hash) = updater.store.load_block_hash(orphan.into())? else {
+                    continue;
+                };
+
+                let block = updater.client.get_block(&hash).with_context(|| {
+                    format!("failed to fetch orphaned block {hash} at height {orphan}")
+                })?;
+
+                let inputs = block
+                    .txdata
+                    .iter()
+                    .filter(|tx| !tx.is_coin_base())
+                    .flat_map(|tx| &tx.input)
+                    .map(|input| input.previous_output.to_string())
+                    .collect::<Vec<String>>();
+
+                for outpoints in inputs.chunks(UNSPEND_BATCH_SIZE) {
+                    for balance in updater.store.load_by_outpoints(outpoints.to_vec())? {
+                        if balance.spent && balance.spent_block.is_none() {
+                            spent_outpoints.insert(balance.out_point);
+                        }
+                    }
+                }
+            }
+        }
+        let spent_outpoints = spent_outpoints.into_iter().collect::<Vec<String>>();
+
+        let network = updater.network;
+        updater.store.with_transaction(&mut |store| {
+            Self::rollback(store, network, ancestor.into(), &spent_outpoints)
+        })?;
+
+        updater.height = ancestor + 1;
+
+        log::info!("successfully rolled back rune state to height {ancestor}");
+
+        Ok(())
+    }
+
+    fn rollback(
+        store: &mut dyn RuneStore,
+        network: Network,
+        ancestor: u64,
+        spent_outpoints: &[String],
+    ) -> Result {
+        // mints and burns of orphaned blocks are recorded as events, which lets
+        // us revert the counters of runes etched before the fork
+        let mut mints: HashMap<String, u128> = HashMap::new();
+        let mut burned: HashMap<String, u128> = HashMap::new();
+        for event in store.load_events_after(ancestor)? {
+            match EventType::try_from(event.event_type)? {
+                EventType::Mint => *mints.entry(event.rune_id).or_default() += 1,
+                EventType::Burn => {
+                    *burned.entry(event.rune_id).or_default() += event
+                        .amount
+                        .as_ref()
+                        .map(decimal_to_u128)
+                        .transpose()?
+                        .unwrap_or_default()
+                }
+                _ => {}
+            }
+        }
+
+        // holders of balances created or spent by orphaned blocks are
+        // recomputed from their outputs once those are rolled back
+        let mut addresses = store
+            .load_addresses_after(ancestor)?
+            .into_iter()
+            .collect::<HashSet<String>>();
+        for outpoints in spent_outpoints.chunks(UNSPEND_BATCH_SIZE) {
+            for balance in store.load_by_outpoints(outpoints.to_vec())? {
+                if balance.spent && balance.spent_block.is_none() {
+                    addresses.insert(balance.address);
+                }
+            }
+        }
+        addresses.remove("");
+
+        store.delete_events_after(ancestor)?;
+        store.delete_txs_after(ancestor)?;
+        store.delete_runestones_after(ancestor)?;
+        store.delete_balances_after(ancestor)?;
+        store.delete_rune_entries_after(ancestor)?;
+
+        // outpoints consumed by orphaned blocks become spendable again
+        store.unspend_out_points_after(ancestor)?;
+
+        for outpoints in spent_outpoints.chunks(UNSPEND_BATCH_SIZE) {
+            store.unspend_unrecorded_out_points(outpoints.to_vec())?;
+        }
+
+        let addresses = addresses.into_iter().collect::<Vec<String>>();
+        for chunk in addresses.chunks(REBUILD_BATCH_SIZE) {
+            store.rebuild_address_balances(chunk.to_vec())?;
+        }
+
+        let rune_ids = mints.keys().chain(burned.keys()).cloned().collect();
+        for entity in store.gets_rune_entry(rune_ids)? {
+            let rune_id = RuneId::from_str(entity.rune_id.as_str())?;
+
+            if let Some(reverted) = mints.get(&entity.rune_id) {
+                let current = decimal_to_u128(&entity.mints)
+                    .with_context(|| format!("invalid mints for rune {rune_id}"))?;
+                store.update_rune_mints(&rune_id, current.saturating_sub(*reverted))?;
+            }
+
+            if let Some(reverted) = burned.get(&entity.rune_id) {
+                let current = decimal_to_u128(&entity.burned)
+                    .with_context(|| format!("invalid burned for rune {rune_id}"))?;
+                store.update_rune_burned(&rune_id, current.saturating_sub(*reverted))?;
+            }
+        }
+
+        store.delete_blocks_after(ancestor)?;
+
+        // rolling back past the first indexed block leaves nothing indexed
+        match store.load_block_hash(ancestor)? {
+            Some(hash) => store.store_indexer_state(network, ancestor, &hash)?,
+            None => store.delete_indexer_state()?,
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            dao::{balance, for_each_store, outpoint, spend},
+            model::RuneBalanceEntity,
+        },
+        ordinals::inscription_id::txid,
+    };
+
+    #[test]
+    fn rollback_restores_spends_with_and_without_blocks() {
+        for_each_store(|store| {
+            let first = outpoint(txid(1), 0);
+            let second = outpoint(txid(2), 0);
+
+            // the second was spent by block 3 before spends were recorded
+            store
+                .store_balances(&[
+                    balance(first, "1:1", 100),
+                    RuneBalanceEntity {
+                        spent: true,
+                        ..balance(second, "1:1", 100)
+                    },
+                ])
+                .unwrap();
+            store
+                .updates_spend_out_point(&[spend(first, txid(3), 0, 3)])
+                .unwrap();
+
+            assert!(store.has_unrecorded_spends().unwrap());
+
+            Reorg::rollback(store, Network::Bitcoin, 2, &[second.to_string()]).unwrap();
+
+            assert!(!store.has_unrecorded_spends().unwrap());
+
+            let balances = store
+                .load_by_outpoints(vec![first.to_string(), second.to_string()])
+                .unwrap();
+            assert_eq!(balances.len(), 2);
+            assert!(balances
+                .iter()
+                .all(|balance| !balance.spent && balance.spent_txid.is_none()));
+
+            let holdings = store.load_balances_by_address("bc1qfoo").unwrap();
+            assert_eq!(holdings.len(), 1);
+            assert_eq!(holdings[0].amount, BigDecimal::from(200));
+            assert_eq!(holdings[0].utxo_count, 2);
+        });
+    }
+}
@@ -3,9 +3,15 @@
 diesel::table! {
     etching (id) {
         id -> Unsigned<Bigint>,
-        rune_id -> Decimal,
+        rune -> Decimal,
+        state -> Unsigned<Tinyint>,
         #[max_length = 256]
         destination -> Varchar,
+        #[max_length = 64]
+        commit_key -> Varchar,
+        runestone -> Text,
+        fee_rate -> Unsigned<Bigint>,
+        postage -> Unsigned<Bigint>,
         #[max_length = 256]
         commit_tx_id -> Varchar,
         commit_tx -> Text,
@@ -18,6 +24,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    indexer_state (id) {
+        id -> Unsigned<Integer>,
+        #[max_length = 16]
+        network -> Varchar,
+        height -> Unsigned<Bigint>,
+        #[max_length = 64]
+        block_hash -> Varchar,
+    }
+}
+
+diesel::table! {
+    rune_address_balance (id) {
+        id -> Unsigned<Bigint>,
+        #[max_length = 256]
+        address -> Varchar,
+        #[max_length = 64]
+        rune_id -> Varchar,
+        amount -> Decimal,
+        utxo_count -> Unsigned<Bigint>,
+    }
+}
+
 diesel::table! {
     rune_balance (id) {
         id -> Unsigned<Bigint>,
@@ -26,12 +55,25 @@ diesel::table! {
         rune_id -> Varchar,
         #[max_length = 256]
         address -> Varchar,
-        #[max_length = 256]
-        pk_script_hex -> Varchar,
+        pk_script_hex -> Text,
+        #[max_length = 16]
+        script_type -> Varchar,
         #[max_length = 266]
         out_point -> Varchar,
         amount -> Decimal,
         spent -> Bool,
+        #[max_length = 64]
+        spent_txid -> Nullable<Varchar>,
+        spent_vin -> Nullable<Unsigned<Integer>>,
+        spent_block -> Nullable<Unsigned<Bigint>>,
+    }
+}
+
+diesel::table! {
+    rune_block (height) {
+        height -> Unsigned<Bigint>,
+        #[max_length = 64]
+        block_hash -> Varchar,
     }
 }
 
@@ -75,18 +117,81 @@ diesel::table! {
         rune_id -> Varchar,
         #[max_length = 256]
         address -> Varchar,
-        #[max_length = 256]
-        pk_script_hex -> Varchar,
+        pk_script_hex -> Text,
+        #[max_length = 16]
+        script_type -> Varchar,
         amount -> Nullable<Decimal>,
         vout -> Unsigned<Integer>,
-        rune_stone -> Text,
         timestamp -> Unsigned<Bigint>,
+        #[max_length = 256]
+        reason -> Varchar,
+    }
+}
+
+diesel::table! {
+    rune_tx (id) {
+        id -> Unsigned<Bigint>,
+        block -> Unsigned<Bigint>,
+        #[max_length = 64]
+        tx_id -> Varchar,
+        input_value -> Nullable<Unsigned<Bigint>>,
+        fee -> Nullable<Unsigned<Bigint>>,
+    }
+}
+
+diesel::table! {
+    runestone (id) {
+        id -> Unsigned<Bigint>,
+        block -> Unsigned<Bigint>,
+        #[max_length = 64]
+        tx_id -> Varchar,
+        cenotaph -> Bool,
+        #[max_length = 32]
+        flaw -> Nullable<Varchar>,
+        pointer -> Nullable<Unsigned<Integer>>,
+        #[max_length = 64]
+        mint -> Nullable<Varchar>,
+        etching -> Bool,
+        rune -> Nullable<Decimal>,
+        spacers -> Nullable<Unsigned<Integer>>,
+        divisibility -> Nullable<Unsigned<Tinyint>>,
+        premine -> Nullable<Decimal>,
+        #[max_length = 8]
+        symbol -> Nullable<Varchar>,
+        turbo -> Bool,
+        terms -> Bool,
+        amount -> Nullable<Decimal>,
+        cap -> Nullable<Decimal>,
+        height_start -> Nullable<Unsigned<Bigint>>,
+        height_end -> Nullable<Unsigned<Bigint>>,
+        offset_start -> Nullable<Unsigned<Bigint>>,
+        offset_end -> Nullable<Unsigned<Bigint>>,
+    }
+}
+
+diesel::table! {
+    runestone_edict (id) {
+        id -> Unsigned<Bigint>,
+        block -> Unsigned<Bigint>,
+        #[max_length = 64]
+        tx_id -> Varchar,
+        edict_index -> Unsigned<Integer>,
+        #[max_length = 64]
+        rune_id -> Varchar,
+        amount -> Decimal,
+        output -> Unsigned<Integer>,
     }
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
     etching,
+    indexer_state,
+    rune_address_balance,
     rune_balance,
+    rune_block,
     rune_entry,
     rune_event,
+    rune_tx,
+    runestone,
+    runestone_edict,
 );
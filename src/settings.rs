@@ -0,0 +1,348 @@
+use {
+    super::*,
+    crate::dao::{RedbStore, RuneStore, StorePool},
+    bitcoincore_rpc::Auth,
+    diesel::{
+        r2d2::{ConnectionManager, Pool, R2D2Connection},
+        Connection, MysqlConnection, PgConnection,
+    },
+};
+
+/// Indexer configuration, merged from command line flags, environment
+/// variables (including a `.env` file) and a YAML config file, in that order of
+/// precedence.
+#[derive(Default, Debug, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub(crate) bitcoin_rpc_url: Option<String>,
+    pub(crate) bitcoin_rpc_username: Option<String>,
+    pub(crate) bitcoin_rpc_password: Option<String>,
+    pub(crate) cookie_file: Option<PathBuf>,
+    pub(crate) database_url: Option<String>,
+    pub(crate) network: Option<Network>,
+    pub(crate) start_height: Option<u32>,
+}
+
+impl Settings {
+    pub(crate) fn load(options: Options) -> Result<Self> {
+        dotenv::dotenv().ok();
+
+        let config = match options
+            .config
+            .clone()
+            .or_else(|| env::var_os("RUNES_CONFIG").map(PathBuf::from))
+        {
+            Some(path) => Self::from_config_file(&path)?,
+            None => Self::default(),
+        };
+
+        Ok(Self::from_options(options)
+            .or(Self::from_env(|key| env::var(key).ok())?)
+            .or(config))
+    }
+
+    fn from_config_file(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path)
+            .with_context(|| format!("failed to open config file `{}`", path.display()))?;
+
+        serde_yaml::from_reader(file)
+            .with_context(|| format!("failed to deserialize config file `{}`", path.display()))
+    }
+
+    fn from_options(options: Options) -> Self {
+        Self {
+            bitcoin_rpc_url: options.bitcoin_rpc_url,
+            bitcoin_rpc_username: options.bitcoin_rpc_username,
+            bitcoin_rpc_password: options.bitcoin_rpc_password,
+            cookie_file: options.cookie_file,
+            database_url: options.database_url,
+            network: options.network,
+            start_height: options.start_height,
+        }
+    }
+
+    fn from_env(get: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        Ok(Self {
+            bitcoin_rpc_url: get("BITCOIN_URL"),
+            bitcoin_rpc_username: get("BITCOIN_USER"),
+            bitcoin_rpc_password: get("BITCOIN_PASSWD"),
+            cookie_file: get("BITCOIN_COOKIE_FILE").map(PathBuf::from),
+            database_url: get("DATABASE_URL"),
+            network: get("RUNES_NETWORK")
+                .map(|network| {
+                    network
+                        .parse::<Network>()
+                        .with_context(|| format!("invalid RUNES_NETWORK `{network}`"))
+                })
+                .transpose()?,
+            start_height: get("RUNES_START_HEIGHT")
+                .map(|height| {
+                    height
+                        .parse::<u32>()
+                        .with_context(|| format!("invalid RUNES_START_HEIGHT `{height}`"))
+                })
+                .transpose()?,
+        })
+    }
+
+    fn or(self, source: Self) -> Self {
+        Self {
+            bitcoin_rpc_url: self.bitcoin_rpc_url.or(source.bitcoin_rpc_url),
+            bitcoin_rpc_username: self.bitcoin_rpc_username.or(source.bitcoin_rpc_username),
+            bitcoin_rpc_password: self.bitcoin_rpc_password.or(source.bitcoin_rpc_password),
+            cookie_file: self.cookie_file.or(source.cookie_file),
+            database_url: self.database_url.or(source.database_url),
+            network: self.network.or(source.network),
+            start_height: self.start_height.or(source.start_height),
+        }
+    }
+
+    pub(crate) fn network(&self) -> Network {
+        self.network.unwrap_or(Network::Bitcoin)
+    }
+
+    pub(crate) fn start_height(&self) -> u32 {
+        self.start_height
+            .unwrap_or_else(|| Rune::first_rune_height(self.network()))
+    }
+
+    pub(crate) fn bitcoin_rpc_url(&self) -> String {
+        self.bitcoin_rpc_url.clone().unwrap_or_else(|| {
+            let port = match self.network() {
+                Network::Bitcoin => 8332,
+                Network::Testnet => 18332,
+                Network::Signet => 38332,
+                Network::Regtest => 18443,
+                _ => 8332,
+            };
+
+            format!("127.0.0.1:{port}")
+        })
+    }
+
+    pub(crate) fn bitcoin_credentials(&self) -> Result<Auth> {
+        if let Some((user, pass)) = self
+            .bitcoin_rpc_username
+            .as_ref()
+            .zip(self.bitcoin_rpc_password.as_ref())
+        {
+            return Ok(Auth::UserPass(user.clone(), pass.clone()));
+        }
+
+        ensure!(
+            self.bitcoin_rpc_username.is_none() && self.bitcoin_rpc_password.is_none(),
+            "bitcoin rpc username and password must be configured together"
+        );
+
+        Ok(Auth::CookieFile(self.cookie_file()?))
+    }
+
+    pub(crate) fn cookie_file(&self) -> Result<PathBuf> {
+        if let Some(cookie_file) = &self.cookie_file {
+            return Ok(cookie_file.clone());
+        }
+
+        let path = if cfg!(target_os = "linux") {
+            dirs::home_dir()
+                .ok_or_else(|| anyhow!("failed to get cookie file path: could not get home dir"))?
+                .join(".bitcoin")
+        } else {
+            dirs::data_dir()
+                .ok_or_else(|| anyhow!("failed to get cookie file path: could not get data dir"))?
+                .join("Bitcoin")
+        };
+
+        let path = match self.network() {
+            Network::Testnet => path.join("testnet3"),
+            Network::Signet => path.join("signet"),
+            Network::Regtest => path.join("regtest"),
+            _ => path,
+        };
+
+        Ok(path.join(".cookie"))
+    }
+
+    pub(crate) fn database_url(&self) -> Result<&str> {
+        self.database_url.as_deref().ok_or_else(|| {
+            anyhow!("no database url configured, pass --database-url or set DATABASE_URL")
+        })
+    }
+
+    pub(crate) fn bitcoin_rpc_client(&self) -> Result<Client> {
+        let rpc_url = self.bitcoin_rpc_url();
+
+        Client::new(&rpc_url, self.bitcoin_credentials()?)
+            .with_context(|| format!("failed to connect to Bitcoin Core RPC at `{rpc_url}`"))
+    }
+
+    pub(crate) fn database_connection<C: Connection>(&self) -> Result<C> {
+        let database_url = self.database_url()?;
+
+        C::establish(database_url)
+            .with_context(|| format!("failed to connect to database at `{database_url}`"))
+    }
+
+    /// Path of the redb file, for `redb://<path>` database URLs.
+    pub(crate) fn redb_path(&self) -> Result<Option<&Path>> {
+        Ok(self.database_url()?.strip_prefix("redb://").map(Path::new))
+    }
+
+    /// Whether the database URL points at PostgreSQL rather than MySQL.
+    pub(crate) fn is_postgres(&self) -> Result<bool> {
+        let database_url = self.database_url()?;
+
+        Ok(database_url.starts_with("postgres://") || database_url.starts_with("postgresql://"))
+    }
+
+    /// Rune store for the database URL.
+    pub(crate) fn store(&self) -> Result<Box<dyn RuneStore>> {
+        if let Some(path) = self.redb_path()? {
+            return Ok(Box::new(RedbStore::open(path)?));
+        }
+
+        if self.is_postgres()? {
+            return Ok(Box::new(self.database_connection::<PgConnection>()?));
+        }
+
+        Ok(Box::new(self.database_connection::<MysqlConnection>()?))
+    }
+
+    /// Pool of rune stores for the database URL.
+    pub(crate) fn store_pool(&self) -> Result<StorePool> {
+        if let Some(path) = self.redb_path()? {
+            return Ok(StorePool::Redb(path.into()));
+        }
+
+        if self.is_postgres()? {
+            return Ok(StorePool::Postgres(self.connection_pool()?));
+        }
+
+        Ok(StorePool::Mysql(self.connection_pool()?))
+    }
+
+    fn connection_pool<C: R2D2Connection + 'static>(&self) -> Result<Pool<ConnectionManager<C>>> {
+        let database_url = self.database_url()?;
+
+        Pool::builder()
+            .build(ConnectionManager::new(database_url))
+            .with_context(|| format!("failed to connect to database at `{database_url}`"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_take_precedence_over_env_and_config() {
+        let options = Options {
+            database_url: Some("mysql://flag".into()),
+            ..default()
+        };
+
+        let env = Settings::from_env(|key| match key {
+            "DATABASE_URL" => Some("mysql://env".into()),
+            "BITCOIN_URL" => Some("env:8332".into()),
+            _ => None,
+        })
+        .unwrap();
+
+        let config = Settings {
+            bitcoin_rpc_url: Some("config:8332".into()),
+            start_height: Some(100),
+            ..default()
+        };
+
+        let settings = Settings::from_options(options).or(env).or(config);
+
+        assert_eq!(settings.database_url().unwrap(), "mysql://flag");
+        assert_eq!(settings.bitcoin_rpc_url(), "env:8332");
+        assert_eq!(settings.start_height(), 100);
+    }
+
+    #[test]
+    fn invalid_env_network_is_an_error() {
+        assert!(Settings::from_env(|key| (key == "RUNES_NETWORK").then(|| "foo".into())).is_err());
+    }
+
+    #[test]
+    fn config_file_is_yaml() {
+        let settings: Settings = serde_yaml::from_str(
+            "bitcoin_rpc_url: 127.0.0.1:1234\nnetwork: regtest\nstart_height: 5\n",
+        )
+        .unwrap();
+
+        assert_eq!(settings.bitcoin_rpc_url(), "127.0.0.1:1234");
+        assert_eq!(settings.network(), Network::Regtest);
+        assert_eq!(settings.start_height(), 5);
+    }
+
+    #[test]
+    fn defaults_follow_network() {
+        let settings = Settings {
+            network: Some(Network::Signet),
+            ..default()
+        };
+
+        assert_eq!(settings.bitcoin_rpc_url(), "127.0.0.1:38332");
+        assert_eq!(
+            settings.start_height(),
+            Rune::first_rune_height(Network::Signet)
+        );
+        assert!(settings.cookie_file().unwrap().ends_with("signet/.cookie"));
+        assert_eq!(Settings::default().start_height(), 840_000);
+    }
+
+    #[test]
+    fn credentials() {
+        let settings = Settings {
+            bitcoin_rpc_username: Some("foo".into()),
+            bitcoin_rpc_password: Some("bar".into()),
+            ..default()
+        };
+
+        assert_eq!(
+            settings.bitcoin_credentials().unwrap(),
+            Auth::UserPass("foo".into(), "bar".into())
+        );
+
+        let settings = Settings {
+            bitcoin_rpc_username: Some("foo".into()),
+            ..default()
+        };
+
+        assert!(settings.bitcoin_credentials().is_err());
+
+        let settings = Settings {
+            cookie_file: Some("/foo/.cookie".into()),
+            ..default()
+        };
+
+        assert_eq!(
+            settings.bitcoin_credentials().unwrap(),
+            Auth::CookieFile("/foo/.cookie".into())
+        );
+
+        assert!(Settings::default().database_url().is_err());
+    }
+
+    #[test]
+    fn backend_follows_database_url() {
+        let settings = |url: &str| Settings {
+            database_url: Some(url.into()),
+            ..default()
+        };
+
+        assert!(settings("postgres://runes@localhost/runes")
+            .is_postgres()
+            .unwrap());
+        assert!(settings("postgresql://runes@localhost/runes")
+            .is_postgres()
+            .unwrap());
+        assert!(!settings("mysql://runes@localhost/runes")
+            .is_postgres()
+            .unwrap());
+        assert!(!settings("redb:///tmp/runes.redb").is_postgres().unwrap());
+        assert!(Settings::default().is_postgres().is_err());
+    }
+}
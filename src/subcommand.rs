@@ -0,0 +1,61 @@
+use super::*;
+
+pub(crate) mod availability;
+mod etch;
+mod index;
+mod migrate;
+mod pending;
+mod reindex;
+mod server;
+pub(crate) mod status;
+
+#[derive(Debug, Parser)]
+pub(crate) enum Subcommand {
+    #[command(about = "Display whether a rune can be etched and when it unlocks")]
+    Availability(availability::Availability),
+    #[command(subcommand, about = "Etch runes with the node's wallet")]
+    Etch(etch::Etch),
+    #[command(about = "Index runes and follow the chain tip")]
+    Index(index::Index),
+    #[command(about = "Display rune changes pending in the mempool")]
+    Pending(pending::Pending),
+    #[command(about = "Roll back rune state and index again from a height")]
+    Reindex(reindex::Reindex),
+    #[command(about = "Serve indexed runes over HTTP")]
+    Server(server::Server),
+    #[command(about = "Display indexer progress")]
+    Status,
+    #[command(about = "Apply pending database migrations")]
+    Migrate,
+}
+
+impl Subcommand {
+    pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+        match self {
+            Self::Availability(availability) => availability.run(settings),
+            Self::Etch(etch) => etch.run(settings),
+            Self::Index(index) => index.run(settings),
+            Self::Pending(pending) => pending.run(settings),
+            Self::Reindex(reindex) => reindex.run(settings),
+            Self::Server(server) => server.run(settings),
+            Self::Status => status::run(settings),
+            Self::Migrate => migrate::run(settings),
+        }
+    }
+}
+
+pub trait Output: Send {
+    fn print_json(&self);
+}
+
+impl<T> Output for T
+where
+    T: Serialize + Send,
+{
+    fn print_json(&self) {
+        serde_json::to_writer_pretty(io::stdout(), self).ok();
+        println!();
+    }
+}
+
+pub(crate) type SubcommandResult = Result<Option<Box<dyn Output>>>;
@@ -0,0 +1,134 @@
+use {super::*, crate::dao::RuneStore};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Availability {
+    #[arg(help = "Check whether <RUNE> can be etched.")]
+    pub(crate) rune: SpacedRune,
+}
+
+/// Whether a rune can be etched in the next block, and when it unlocks if it
+/// can't be yet.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+    pub rune: SpacedRune,
+    pub network: Network,
+    pub height: u64,
+    pub reserved: bool,
+    pub unlock_height: Option<u32>,
+    pub blocks_until_unlock: Option<u64>,
+    pub unlocked: bool,
+    pub etching: Option<Txid>,
+    pub available: bool,
+}
+
+impl Output {
+    /// Availability of `rune` in a block at `height`, given the entry of the
+    /// rune if it has been etched.
+    pub(crate) fn new(
+        rune: SpacedRune,
+        network: Network,
+        height: u64,
+        entry: Option<&RuneEntry>,
+    ) -> Self {
+        let unlock_height = rune.rune.unlock_height(network).map(|height| height.0);
+        let unlocked = unlock_height.is_some_and(|unlock| height >= u64::from(unlock));
+
+        Self {
+            rune,
+            network,
+            height,
+            reserved: rune.rune.is_reserved(),
+            unlock_height,
+            blocks_until_unlock: unlock_height
+                .map(|unlock| u64::from(unlock).saturating_sub(height)),
+            unlocked,
+            etching: entry.map(|entry| entry.etching),
+            available: unlocked && entry.is_none(),
+        }
+    }
+
+    /// Availability of `rune` in the block after the node's chain tip.
+    pub(crate) fn load(
+        client: &Client,
+        store: &mut dyn RuneStore,
+        network: Network,
+        rune: SpacedRune,
+    ) -> Result<Self> {
+        let entry = store.load_entry_by_rune(&rune.rune)?;
+
+        Ok(Self::new(
+            rune,
+            network,
+            client.get_block_count()? + 1,
+            entry.as_ref(),
+        ))
+    }
+}
+
+impl Availability {
+    pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+        let client = settings.bitcoin_rpc_client()?;
+        let mut store = settings.store()?;
+
+        Ok(Some(Box::new(Output::load(
+            &client,
+            &mut *store,
+            settings.network(),
+            self.rune,
+        )?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(rune: &str, network: Network, height: u64, entry: Option<&RuneEntry>) -> Output {
+        Output::new(rune.parse().unwrap(), network, height, entry)
+    }
+
+    #[test]
+    fn locked() {
+        let output = output("A", Network::Bitcoin, 840_000, None);
+        assert_eq!(output.unlock_height, Some(1_049_999));
+        assert_eq!(output.blocks_until_unlock, Some(209_999));
+        assert!(!output.unlocked);
+        assert!(!output.available);
+    }
+
+    #[test]
+    fn unlocked() {
+        let output = output("AAAAAAAAAAAAA", Network::Bitcoin, 840_000, None);
+        assert_eq!(output.unlock_height, Some(840_000));
+        assert_eq!(output.blocks_until_unlock, Some(0));
+        assert!(output.unlocked);
+        assert!(output.available);
+    }
+
+    #[test]
+    fn taken() {
+        let entry = RuneEntry {
+            etching: Txid::from_byte_array([1; 32]),
+            ..default()
+        };
+
+        let output = output("AAAAAAAAAAAAA", Network::Bitcoin, 840_000, Some(&entry));
+        assert!(output.unlocked);
+        assert_eq!(output.etching, Some(entry.etching));
+        assert!(!output.available);
+    }
+
+    #[test]
+    fn reserved() {
+        let output = output(
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAA",
+            Network::Bitcoin,
+            840_000,
+            None,
+        );
+        assert!(output.reserved);
+        assert_eq!(output.unlock_height, None);
+        assert_eq!(output.blocks_until_unlock, None);
+        assert!(!output.available);
+    }
+}
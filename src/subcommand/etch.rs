@@ -0,0 +1,132 @@
+use {
+    super::*,
+    crate::etcher::{Etcher, EtchingRequest},
+};
+
+#[derive(Debug, Parser)]
+pub(crate) enum Etch {
+    #[command(about = "Commit to and etch a new rune")]
+    New(New),
+    #[command(about = "Continue unfinished etchings")]
+    Resume,
+    #[command(about = "Send the commit output of an unrevealed etching back")]
+    Recover(Recover),
+    #[command(about = "Display an etching")]
+    Show(Show),
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct New {
+    #[arg(long, help = "Etch <RUNE>, with `.` or `•` spacers.")]
+    pub(crate) rune: SpacedRune,
+    #[arg(
+        long,
+        default_value = "0",
+        help = "Divide amounts into 10^<DIVISIBILITY> units."
+    )]
+    pub(crate) divisibility: u8,
+    #[arg(long, help = "Display amounts with currency <SYMBOL>.")]
+    pub(crate) symbol: Option<char>,
+    #[arg(long, help = "Send <PREMINE> units to the destination.")]
+    pub(crate) premine: Option<u128>,
+    #[arg(long, help = "Allow minting <AMOUNT> units per mint.")]
+    pub(crate) amount: Option<u128>,
+    #[arg(long, help = "Allow at most <CAP> mints.")]
+    pub(crate) cap: Option<u128>,
+    #[arg(long, help = "Allow minting from block <HEIGHT_START>.")]
+    pub(crate) height_start: Option<u64>,
+    #[arg(long, help = "Allow minting before block <HEIGHT_END>.")]
+    pub(crate) height_end: Option<u64>,
+    #[arg(long, help = "Allow minting <OFFSET_START> blocks after the etching.")]
+    pub(crate) offset_start: Option<u64>,
+    #[arg(
+        long,
+        help = "Allow minting until <OFFSET_END> blocks after the etching."
+    )]
+    pub(crate) offset_end: Option<u64>,
+    #[arg(long, help = "Opt in to future protocol changes.")]
+    pub(crate) turbo: bool,
+    #[arg(
+        long,
+        help = "Send the premine and any recovered funds to <DESTINATION>."
+    )]
+    pub(crate) destination: Address<NetworkUnchecked>,
+    #[arg(long, help = "Pay <FEE_RATE> sat/vB for the commit and reveal.")]
+    pub(crate) fee_rate: u64,
+    #[arg(
+        long,
+        default_value = "10000",
+        help = "Send <POSTAGE> sats with the premine."
+    )]
+    pub(crate) postage: u64,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct Recover {
+    #[arg(help = "Recover etching <ID>.")]
+    pub(crate) id: u64,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct Show {
+    #[arg(help = "Display etching <ID>.")]
+    pub(crate) id: u64,
+}
+
+impl New {
+    fn request(self, network: Network) -> Result<EtchingRequest> {
+        let terms = (self.amount.is_some()
+            || self.cap.is_some()
+            || self.height_start.is_some()
+            || self.height_end.is_some()
+            || self.offset_start.is_some()
+            || self.offset_end.is_some())
+        .then_some(Terms {
+            amount: self.amount,
+            cap: self.cap,
+            height: (self.height_start, self.height_end),
+            offset: (self.offset_start, self.offset_end),
+        });
+
+        ensure!(
+            self.divisibility <= Etching::MAX_DIVISIBILITY,
+            "divisibility is greater than {}",
+            Etching::MAX_DIVISIBILITY
+        );
+
+        Ok(EtchingRequest {
+            etching: Etching {
+                divisibility: Some(self.divisibility),
+                premine: self.premine,
+                rune: Some(self.rune.rune),
+                spacers: Some(self.rune.spacers),
+                symbol: self.symbol,
+                terms,
+                turbo: self.turbo,
+            },
+            destination: self.destination.require_network(network)?,
+            fee_rate: self.fee_rate,
+            postage: Amount::from_sat(self.postage),
+        })
+    }
+}
+
+impl Etch {
+    pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+        let client = settings.bitcoin_rpc_client()?;
+        let mut store = settings.store()?;
+        let mut etcher = Etcher::new(&client, &mut *store, settings.network());
+
+        match self {
+            Self::New(new) => Ok(Some(Box::new(
+                etcher.etch(new.request(settings.network())?)?,
+            ))),
+            Self::Resume => Ok(Some(Box::new(etcher.resume()?))),
+            Self::Recover(recover) => Ok(Some(Box::new(etcher.recover(recover.id)?))),
+            Self::Show(show) => {
+                let entity = etcher.load(show.id)?;
+                Ok(Some(Box::new(etcher.output(&entity)?)))
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use {super::*, crate::updater::Updater};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Index {
+    #[arg(long, help = "Exit once the index has caught up with the chain tip.")]
+    pub(crate) once: bool,
+    #[arg(
+        long,
+        default_value = "5",
+        help = "Poll for new blocks every <INTERVAL> seconds."
+    )]
+    pub(crate) interval: u64,
+}
+
+impl Index {
+    pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+        let client = settings.bitcoin_rpc_client()?;
+
+        let mut store = settings.store()?;
+        store.migrate()?;
+
+        let mut updater =
+            Updater::new(&client, store, settings.network(), settings.start_height())?;
+
+        self.follow(&settings, &mut updater)?;
+
+        Ok(None)
+    }
+
+    pub(crate) fn follow(&self, settings: &Settings, updater: &mut Updater) -> Result {
+        loop {
+            updater.update_index(&settings.bitcoin_rpc_url(), settings.bitcoin_credentials()?)?;
+
+            if self.once {
+                return Ok(());
+            }
+
+            let deadline = Instant::now() + Duration::from_secs(self.interval);
+            while Instant::now() < deadline {
+                if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+                    return Ok(());
+                }
+
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use super::*;
+
+pub(crate) fn run(settings: Settings) -> SubcommandResult {
+    settings.store()?.migrate()?;
+
+    Ok(None)
+}
@@ -0,0 +1,45 @@
+use {
+    super::*,
+    crate::{fetcher::Fetcher, mempool::PendingState},
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Pending {
+    #[arg(
+        long,
+        help = "Read transactions from <MEMPOOL_DAT> instead of the node's mempool."
+    )]
+    pub(crate) mempool_dat: Option<PathBuf>,
+}
+
+impl Pending {
+    pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+        let client = settings.bitcoin_rpc_client()?;
+        let mut store = settings.store()?;
+
+        let height = store
+            .load_indexer_state()?
+            .ok_or_else(|| anyhow!("nothing has been indexed yet"))?
+            .height
+            .try_into()?;
+
+        let state = match self.mempool_dat {
+            Some(path) => PendingState::load_mempool_dat(
+                &client,
+                &mut *store,
+                settings.network(),
+                height,
+                &path,
+            )?,
+            None => PendingState::load(
+                &client,
+                &mut *store,
+                &Fetcher::new(&settings.bitcoin_rpc_url(), settings.bitcoin_credentials()?)?,
+                settings.network(),
+                height,
+            )?,
+        };
+
+        Ok(Some(Box::new(state)))
+    }
+}
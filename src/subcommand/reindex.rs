@@ -0,0 +1,63 @@
+use {
+    super::*,
+    crate::{
+        reorg::{Reorg, MAX_RECOVERABLE_REORG_DEPTH},
+        updater::Updater,
+    },
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Reindex {
+    #[arg(
+        long,
+        help = "Discard rune state from block <FROM> onwards and index it again."
+    )]
+    pub(crate) from: u32,
+    #[arg(
+        long,
+        help = "Reindex from more than 100 blocks below the next block to index."
+    )]
+    pub(crate) force: bool,
+    #[command(flatten)]
+    pub(crate) index: index::Index,
+}
+
+impl Reindex {
+    pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+        let client = settings.bitcoin_rpc_client()?;
+
+        let mut store = settings.store()?;
+        store.migrate()?;
+
+        let mut updater =
+            Updater::new(&client, store, settings.network(), settings.start_height())?;
+
+        ensure!(
+            self.from <= updater.height,
+            "cannot reindex from block {}, next block to index is {}",
+            self.from,
+            updater.height
+        );
+
+        // rolling back fetches the discarded blocks from the node while spends
+        // without a block remain, so reindexing deeper than any recoverable
+        // reorg has to be forced
+        let depth = updater.height - self.from;
+        ensure!(
+            self.force || depth <= MAX_RECOVERABLE_REORG_DEPTH,
+            "cannot reindex {depth} blocks, more than the {MAX_RECOVERABLE_REORG_DEPTH} \
+             of a recoverable reorg, without --force"
+        );
+
+        let ancestor = self
+            .from
+            .checked_sub(1)
+            .ok_or_else(|| anyhow!("cannot reindex from the genesis block"))?;
+
+        Reorg::rollback_to(&mut updater, ancestor)?;
+
+        self.index.follow(&settings, &mut updater)?;
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,92 @@
+use {
+    super::*,
+    crate::{
+        api::{self, ApiState},
+        mempool,
+    },
+    std::net::SocketAddr,
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Server {
+    #[arg(
+        long,
+        default_value = "0.0.0.0",
+        help = "Listen on <ADDRESS> for incoming requests."
+    )]
+    pub(crate) address: String,
+    #[arg(
+        long,
+        default_value = "8080",
+        help = "Listen on <HTTP_PORT> for incoming HTTP requests."
+    )]
+    pub(crate) http_port: u16,
+    #[arg(long, help = "Serve the index without updating it.")]
+    pub(crate) no_index: bool,
+    #[arg(long, help = "Don't index pending runes in the mempool.")]
+    pub(crate) no_mempool: bool,
+    #[arg(
+        long,
+        default_value = "10",
+        help = "Poll the mempool every <MEMPOOL_INTERVAL> seconds."
+    )]
+    pub(crate) mempool_interval: u64,
+}
+
+impl Server {
+    pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+        let addr = (self.address.as_str(), self.http_port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("failed to resolve {}:{}", self.address, self.http_port))?;
+
+        let state = ApiState::new(
+            settings.bitcoin_rpc_client()?,
+            settings.store_pool()?,
+            settings.network(),
+        );
+
+        if !self.no_index {
+            let settings = settings.clone();
+            *INDEXER.lock().unwrap() = Some(thread::spawn(move || {
+                let index = index::Index {
+                    once: false,
+                    interval: 5,
+                };
+
+                if let Err(err) = index.run(settings) {
+                    log::error!("indexer stopped: {err:#}");
+                }
+            }));
+        }
+
+        if !self.no_mempool {
+            let settings = settings.clone();
+            let pending = state.pending();
+            let interval = self.mempool_interval;
+            thread::spawn(move || {
+                if let Err(err) = mempool::follow(&settings, &pending, interval) {
+                    log::error!("mempool indexer stopped: {err:#}");
+                }
+            });
+        }
+
+        Runtime::new()?.block_on(Self::serve(addr, api::router(state)))?;
+
+        Ok(None)
+    }
+
+    async fn serve(addr: SocketAddr, router: axum::Router) -> Result {
+        let handle = axum_server::Handle::new();
+        LISTENERS.lock().unwrap().push(handle.clone());
+
+        log::info!("Listening on http://{addr}");
+
+        axum_server::Server::bind(addr)
+            .handle(handle)
+            .serve(router.into_make_service())
+            .await?;
+
+        Ok(())
+    }
+}
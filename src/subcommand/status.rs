@@ -0,0 +1,41 @@
+use {super::*, crate::dao::RuneStore};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+    pub network: Network,
+    pub height: Option<u64>,
+    pub block_hash: Option<BlockHash>,
+    pub chain_tip: u64,
+    pub runes: u64,
+}
+
+impl Output {
+    pub(crate) fn load(
+        client: &Client,
+        store: &mut dyn RuneStore,
+        network: Network,
+    ) -> Result<Self> {
+        let state = store.load_indexer_state()?;
+
+        Ok(Self {
+            network,
+            height: state.as_ref().map(|state| state.height),
+            block_hash: state
+                .map(|state| BlockHash::from_str(&state.block_hash))
+                .transpose()?,
+            chain_tip: client.get_block_count()?,
+            runes: store.gets_rune_number().map_or(0, |number| number + 1),
+        })
+    }
+}
+
+pub(crate) fn run(settings: Settings) -> SubcommandResult {
+    let client = settings.bitcoin_rpc_client()?;
+    let mut store = settings.store()?;
+
+    Ok(Some(Box::new(Output::load(
+        &client,
+        &mut *store,
+        settings.network(),
+    )?)))
+}
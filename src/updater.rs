@@ -1,16 +1,20 @@
 use {
-    super::{fetcher::Fetcher, *},
-    crate::dao::{RuneEntryDao, RuneMysqlDao},
+    super::{
+        fetcher::Fetcher,
+        reorg::{self, Reorg},
+        *,
+    },
+    crate::dao::RuneStore,
     bitcoincore_rpc::Auth,
-    diesel::MysqlConnection,
-    futures::future::try_join_all,
+    futures::stream::{self, StreamExt, TryStreamExt},
+    indexer::Prevout,
     std::sync::mpsc,
-    tokio::sync::mpsc::{error::TryRecvError, Receiver, Sender},
 };
 
 pub(crate) struct BlockData {
     pub(crate) header: Header,
     pub(crate) txdata: Vec<(Transaction, Txid)>,
+    pub(crate) prevouts: HashMap<OutPoint, Prevout>,
 }
 
 impl From<Block> for BlockData {
@@ -25,6 +29,7 @@ impl From<Block> for BlockData {
                     (transaction, txid)
                 })
                 .collect(),
+            prevouts: HashMap::new(),
         }
     }
 }
@@ -32,25 +37,99 @@ impl From<Block> for BlockData {
 pub(crate) struct Updater<'client> {
     pub(super) height: u32,
     pub(super) client: &'client Client,
-    pub(super) conn: MysqlConnection,
+    pub(super) store: Box<dyn RuneStore>,
+    pub(super) network: Network,
+    /// whether prevouts are fetched by txid from the node's transaction
+    /// index, rather than from its undo data
+    pub(super) txindex: bool,
 }
 
 impl<'index> Updater<'index> {
-    pub(crate) fn update_index(
-        &mut self,
-        bitcoin_rpc_url: &str,
-        user: &str,
-        passwd: &str,
-    ) -> Result {
-        let rx = Self::fetch_blocks_from(bitcoin_rpc_url, user, passwd, self.height)?;
+    /// Resume right after the last fully indexed block, or start at
+    /// `start_height` when the database has never been indexed. Refuses to
+    /// resume a database that was indexed for another network.
+    pub(crate) fn new(
+        client: &'index Client,
+        mut store: Box<dyn RuneStore>,
+        network: Network,
+        start_height: u32,
+    ) -> Result<Self> {
+        let height = match store.load_indexer_state()? {
+            Some(state) => {
+                let indexed = Network::from_str(&state.network).with_context(|| {
+                    format!("invalid network `{}` in indexer state", state.network)
+                })?;
+
+                ensure!(
+                    indexed == network,
+                    "database was indexed for network `{indexed}`, refusing to index `{network}`"
+                );
+
+                u32::try_from(state.height)?
+                    .checked_add(1)
+                    .ok_or_else(|| anyhow!("indexer state height overflow"))?
+            }
+            None => start_height,
+        };
+
+        let txindex = Self::has_txindex(client)?;
+
+        if !txindex {
+            log::info!("node has no synced transaction index, fetching prevouts from undo data");
+        }
+
+        Ok(Self {
+            height,
+            client,
+            store,
+            network,
+            txindex,
+        })
+    }
+
+    /// Whether the node has a transaction index that is caught up with its
+    /// chain, so transactions of any block can be fetched by txid.
+    fn has_txindex(client: &Client) -> Result<bool> {
+        #[derive(Deserialize)]
+        struct IndexInfo {
+            synced: bool,
+        }
+
+        let indexes: HashMap<String, IndexInfo> = client
+            .call("getindexinfo", &[])
+            .context("failed to fetch index info of the node")?;
 
-        let (mut outpoint_sender, mut value_receiver) = Self::spawn_fetcher(
+        Ok(indexes.get("txindex").is_some_and(|txindex| txindex.synced))
+    }
+
+    pub(crate) fn update_index(&mut self, bitcoin_rpc_url: &str, auth: Auth) -> Result {
+        loop {
+            match self.index_blocks(bitcoin_rpc_url, auth.clone()) {
+                Ok(()) => return Ok(()),
+                Err(err) => match err.downcast_ref() {
+                    Some(&reorg::Error::Recoverable { height, depth }) => {
+                        Reorg::handle_reorg(self, height, depth)?;
+                    }
+                    _ => return Err(err),
+                },
+            }
+        }
+    }
+
+    fn index_blocks(&mut self, bitcoin_rpc_url: &str, auth: Auth) -> Result {
+        let blocks = Self::fetch_blocks_from(bitcoin_rpc_url, auth.clone(), self.height)?;
+
+        let rx = Self::spawn_prevout_fetcher(
             bitcoin_rpc_url,
-            Auth::UserPass(user.to_string(), passwd.to_string()),
+            auth,
+            self.height,
+            Rune::first_rune_height(self.network),
+            self.txindex,
+            blocks,
         )?;
 
         while let Ok(block) = rx.recv() {
-            self.index_block(&mut outpoint_sender, &mut value_receiver, block)?;
+            self.index_block(block?)?;
 
             if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
                 break;
@@ -62,17 +141,14 @@ impl<'index> Updater<'index> {
 
     fn fetch_blocks_from(
         bitcoin_rpc_url: &str,
-        name: &str,
-        passwd: &str,
+        auth: Auth,
         mut height: u32,
     ) -> Result<mpsc::Receiver<BlockData>> {
         let (tx, rx) = mpsc::sync_channel(32);
 
-        let client = Client::new(
-            bitcoin_rpc_url,
-            Auth::UserPass(name.to_string(), passwd.to_string()),
-        )
-        .with_context(|| format!("failed to connect to Bitcoin Core RPC at `{bitcoin_rpc_url}`"))?;
+        let client = Client::new(bitcoin_rpc_url, auth).with_context(|| {
+            format!("failed to connect to Bitcoin Core RPC at `{bitcoin_rpc_url}`")
+        })?;
 
         thread::spawn(move || loop {
             match Self::get_block_with_retries(&client, height) {
@@ -123,84 +199,193 @@ impl<'index> Updater<'index> {
         }
     }
 
-    fn spawn_fetcher(
+    /// Attach to every block from `first_height` on the outputs spent by its
+    /// inputs, since earlier blocks have no runes to index. Prevouts are
+    /// fetched while earlier blocks are being indexed, in parallel batches if
+    /// the node has a transaction index and from its undo data otherwise. A
+    /// block whose prevouts can't be fetched, or a fetcher that can't start, is
+    /// sent as an error, after which nothing more is sent.
+    fn spawn_prevout_fetcher(
         bitcoin_rpc_url: &str,
         auth: Auth,
-    ) -> Result<(Sender<OutPoint>, Receiver<u64>)> {
-        let fetcher = Fetcher::new(bitcoin_rpc_url, auth)?;
+        mut height: u32,
+        first_height: u32,
+        txindex: bool,
+        blocks: mpsc::Receiver<BlockData>,
+    ) -> Result<mpsc::Receiver<Result<BlockData>>> {
+        let fetcher = Fetcher::new(bitcoin_rpc_url, auth.clone())?;
+
+        let client = Client::new(bitcoin_rpc_url, auth).with_context(|| {
+            format!("failed to connect to Bitcoin Core RPC at `{bitcoin_rpc_url}`")
+        })?;
 
-        // Not sure if any block has more than 20k inputs, but none so far after first inscription block
-        const CHANNEL_BUFFER_SIZE: usize = 20_000;
-        let (outpoint_sender, mut outpoint_receiver) =
-            tokio::sync::mpsc::channel::<OutPoint>(CHANNEL_BUFFER_SIZE);
-        let (value_sender, value_receiver) = tokio::sync::mpsc::channel::<u64>(CHANNEL_BUFFER_SIZE);
+        let (tx, rx) = mpsc::sync_channel(8);
+
+        thread::spawn(move || {
+            let rt = match tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt,
+                Err(err) => {
+                    tx.send(Err(
+                        Error::from(err).context("failed to start the prevout fetcher runtime")
+                    ))
+                    .ok();
+                    return;
+                }
+            };
 
-        // Batch 2048 missing inputs at a time. Arbitrarily chosen for now, maybe higher or lower can be faster?
-        // Did rudimentary benchmarks with 1024 and 4096 and time was roughly the same.
+            for mut block in blocks {
+                let prevouts = if height < first_height {
+                    Ok(HashMap::new())
+                } else if txindex {
+                    rt.block_on(Self::fetch_prevouts(&fetcher, &block, height))
+                        .with_context(|| format!("failed to fetch prevouts of block {height}"))
+                } else {
+                    Prevout::load_block(&client, &block.header.block_hash())
+                        .with_context(|| format!("failed to fetch prevouts of block {height}"))
+                };
+
+                let failed = prevouts.is_err();
+
+                if let Err(err) = tx.send(prevouts.map(|prevouts| {
+                    block.prevouts = prevouts;
+                    block
+                })) {
+                    log::info!("Block receiver disconnected: {err}");
+                    break;
+                }
+
+                if failed {
+                    break;
+                }
+
+                height += 1;
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn fetch_prevouts(
+        fetcher: &Fetcher,
+        block: &BlockData,
+        height: u32,
+    ) -> Result<HashMap<OutPoint, Prevout>> {
+        // Batch at most 2048 transactions per request. Arbitrarily chosen, 1024 and 4096
+        // performed roughly the same.
         const BATCH_SIZE: usize = 2048;
         // Default rpcworkqueue in bitcoind is 16, meaning more than 16 concurrent requests will be rejected.
         // Since we are already requesting blocks on a separate thread, and we don't want to break if anything
         // else runs a request, we keep this to 12.
         const PARALLEL_REQUESTS: usize = 12;
 
-        thread::spawn(move || {
-            let rt = tokio::runtime::Builder::new_multi_thread()
-                .enable_all()
-                .build()
-                .unwrap();
-            rt.block_on(async move {
-                loop {
-                    let Some(outpoint) = outpoint_receiver.recv().await else {
-                        log::debug!("Outpoint channel closed");
-                        return;
-                    };
-                    // There's no try_iter on tokio::sync::mpsc::Receiver like std::sync::mpsc::Receiver.
-                    // So we just loop until BATCH_SIZE doing try_recv until it returns None.
-                    let mut outpoints = vec![outpoint];
-                    for _ in 0..BATCH_SIZE - 1 {
-                        let Ok(outpoint) = outpoint_receiver.try_recv() else {
-                            break;
-                        };
-                        outpoints.push(outpoint);
-                    }
-                    // Break outpoints into chunks for parallel requests
-                    let chunk_size = (outpoints.len() / PARALLEL_REQUESTS) + 1;
-                    let mut futs = Vec::with_capacity(PARALLEL_REQUESTS);
-                    for chunk in outpoints.chunks(chunk_size) {
-                        let txids = chunk.iter().map(|outpoint| outpoint.txid).collect();
-                        let fut = fetcher.get_transactions(txids);
-                        futs.push(fut);
-                    }
-                    let txs = match try_join_all(futs).await {
-                        Ok(txs) => txs,
-                        Err(e) => {
-                            log::error!("Couldn't receive txs {e}");
-                            return;
-                        }
-                    };
-                    // Send all tx output values back in order
-                    for (i, tx) in txs.iter().flatten().enumerate() {
-                        let Ok(_) = value_sender
-                            .send(tx.output[usize::try_from(outpoints[i].vout).unwrap()].value)
-                            .await
-                        else {
-                            log::error!("Value channel closed unexpectedly");
-                            return;
-                        };
+        let output = |tx: &Transaction, outpoint: &OutPoint| {
+            usize::try_from(outpoint.vout)
+                .ok()
+                .and_then(|vout| tx.output.get(vout))
+                .cloned()
+                .ok_or_else(|| anyhow!("input spends missing output {outpoint}"))
+        };
+
+        let created = block
+            .txdata
+            .iter()
+            .map(|(tx, txid)| (*txid, tx))
+            .collect::<HashMap<Txid, &Transaction>>();
+
+        // outputs created by the block itself are resolved locally
+        let mut prevouts = HashMap::new();
+        let mut outpoints = Vec::new();
+        for (tx, _) in block.txdata.iter().filter(|(tx, _)| !tx.is_coin_base()) {
+            for input in &tx.input {
+                let outpoint = input.previous_output;
+
+                match created.get(&outpoint.txid) {
+                    Some(parent) => {
+                        let output = output(parent, &outpoint)?;
+                        prevouts.insert(
+                            outpoint,
+                            Prevout {
+                                height,
+                                script_pubkey: output.script_pubkey,
+                                value: output.value,
+                            },
+                        );
                     }
+                    None => outpoints.push(outpoint),
                 }
+            }
+        }
+
+        let mut txids = outpoints
+            .iter()
+            .map(|outpoint| outpoint.txid)
+            .collect::<Vec<Txid>>();
+        txids.sort();
+        txids.dedup();
+
+        let chunk_size = (txids.len() / PARALLEL_REQUESTS + 1).min(BATCH_SIZE);
+
+        let transactions = stream::iter(txids.chunks(chunk_size))
+            .map(|chunk| fetcher.get_transactions(chunk.to_vec()))
+            .buffered(PARALLEL_REQUESTS)
+            .try_collect::<Vec<Vec<(Transaction, Option<BlockHash>)>>>()
+            .await?;
+
+        // a transaction without a block hash is still in the mempool
+        let transactions = txids
+            .into_iter()
+            .zip(transactions.into_iter().flatten())
+            .map(|(txid, (tx, hash))| {
+                let hash = hash.ok_or_else(|| {
+                    anyhow!("block {height} spends unconfirmed transaction {txid}")
+                })?;
+                Ok((txid, (tx, hash)))
             })
-        });
+            .collect::<Result<HashMap<Txid, (Transaction, BlockHash)>>>()?;
+
+        let mut hashes = transactions
+            .values()
+            .map(|(_, hash)| *hash)
+            .collect::<Vec<BlockHash>>();
+        hashes.sort();
+        hashes.dedup();
+
+        let heights = stream::iter(hashes.chunks(chunk_size))
+            .map(|chunk| fetcher.get_block_heights(chunk.to_vec()))
+            .buffered(PARALLEL_REQUESTS)
+            .try_collect::<Vec<Vec<u32>>>()
+            .await?;
+
+        let heights = hashes
+            .into_iter()
+            .zip(heights.into_iter().flatten())
+            .collect::<HashMap<BlockHash, u32>>();
+
+        for outpoint in outpoints {
+            let (tx, hash) = transactions
+                .get(&outpoint.txid)
+                .ok_or_else(|| anyhow!("missing transaction {}", outpoint.txid))?;
+            let output = output(tx, &outpoint)?;
+
+            prevouts.insert(
+                outpoint,
+                Prevout {
+                    height: *heights
+                        .get(hash)
+                        .ok_or_else(|| anyhow!("missing height of block {hash}"))?,
+                    script_pubkey: output.script_pubkey,
+                    value: output.value,
+                },
+            );
+        }
 
-        Ok((outpoint_sender, value_receiver))
+        Ok(prevouts)
     }
 
-    fn index_block(
-        &mut self,
-        outpoint_sender: &mut Sender<OutPoint>,
-        value_receiver: &mut Receiver<u64>,
-        block: BlockData,
-    ) -> Result<()> {
+    fn index_block(&mut self, mut block: BlockData) -> Result<()> {
         let start = Instant::now();
         log::info!(
             "Block {} at {} with {} transactions…",
@@ -209,29 +394,44 @@ impl<'index> Updater<'index> {
             block.txdata.len()
         );
 
-        // If value_receiver still has values something went wrong with the last block
-        // Could be an assert, shouldn't recover from this and commit the last block
-        let Err(TryRecvError::Empty) = value_receiver.try_recv() else {
-            return Err(anyhow!("Previous block did not consume all input values"));
-        };
-
-        let start_heigth = Rune::first_rune_height(Network::Bitcoin);
-        if self.height >= start_heigth {
-            let gets_rune_number = RuneMysqlDao::gets_rune_number(&mut self.conn);
-            let mut rune_updater = RuneIndexer {
-                block_time: block.header.time,
-                burned: HashMap::new(),
-                client: &self.client,
-                height: self.height,
-                minimum: Rune::minimum_at_height(Network::Bitcoin, Height(self.height)),
-                runes: gets_rune_number.map_or(0, |f| f + 1),
-                conn: &mut self.conn,
-            };
+        Reorg::detect_reorg(&block, self.height, self.client, &mut *self.store)?;
+
+        let height = self.height;
+        let client = self.client;
+        let network = self.network;
+        let block_hash = block.header.block_hash();
+
+        // every write of a block lands in one transaction together with the
+        // indexer state, so a crash never leaves a half-indexed block behind
+        self.store.with_transaction(&mut |store| {
+            let start_heigth = Rune::first_rune_height(network);
+            if height >= start_heigth {
+                let gets_rune_number = store.gets_rune_number();
+                let mut rune_updater = RuneIndexer {
+                    block_hash,
+                    block_time: block.header.time,
+                    client,
+                    height,
+                    minimum: Rune::minimum_at_height(network, Height(height)),
+                    network,
+                    runes: gets_rune_number.map_or(0, |f| f + 1),
+                    cache: Default::default(),
+                };
+
+                rune_updater.cache.prevouts = Some(mem::take(&mut block.prevouts));
+
+                rune_updater.preload(store, &block.txdata)?;
+
+                for (i, (tx, txid)) in block.txdata.iter().enumerate() {
+                    rune_updater.parse_tx(u32::try_from(i).unwrap(), tx, *txid)?;
+                }
 
-            for (i, (tx, txid)) in block.txdata.iter().enumerate() {
-                rune_updater.parse_tx(u32::try_from(i).unwrap(), tx, *txid)?;
+                rune_updater.flush(store)?;
             }
-        }
+
+            store.store_block_header(height.into(), &block.header)?;
+            store.store_indexer_state(network, height.into(), &block_hash)
+        })?;
 
         self.height += 1;
 
@@ -245,6 +445,7 @@ impl<'index> Updater<'index> {
 mod tests {
     use crate::dao::new_db_conn;
     use anyhow::Context;
+    use bitcoin::Network;
     use bitcoincore_rpc::{Auth, Client};
     use dotenv::dotenv;
     use std::env;
@@ -259,21 +460,20 @@ mod tests {
         let bitcoin_url = env::var("BITCOIN_URL").unwrap();
         let bitcoin_user = env::var("BITCOIN_USER").unwrap();
         let bitcoin_passwd = env::var("BITCOIN_PASSWD").unwrap();
-        let conn = new_db_conn(database_url.as_str());
+        let store = new_db_conn(database_url.as_str());
         let client = Client::new(
             bitcoin_url.as_str(),
-            Auth::UserPass(bitcoin_user, bitcoin_passwd),
+            Auth::UserPass(bitcoin_user.clone(), bitcoin_passwd.clone()),
         )
         .with_context(|| format!("failed to connect to Bitcoin Core RPC"))
         .unwrap();
 
-        let mut updater = Updater {
-            height: 840000,
-            client: &client,
-            conn: conn,
-        };
+        let mut updater = Updater::new(&client, Box::new(store), Network::Bitcoin, 840000).unwrap();
 
-        match updater.update_index("192.168.103.162:8332", "foo", "TQlDLNY6eJzZ5fYw") {
+        match updater.update_index(
+            bitcoin_url.as_str(),
+            Auth::UserPass(bitcoin_user, bitcoin_passwd),
+        ) {
             Ok(_) => {}
             Err(e) => {
                 println!("{}", e);